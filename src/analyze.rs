@@ -0,0 +1,319 @@
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use crate::cache::SortCache;
+use crate::config::Configuration;
+use crate::parser::FileFormat;
+use crate::sorter::{is_placeholder, same_class_order, variant_separator, Safelist, TailwindClass};
+use crate::{extract_matches, file_parser, sort_lines, sort_list, RewriteOptions};
+
+/// Class sorting statistics for one file
+///
+/// Serializes to JSON with camelCase keys, so a CI step can record it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+    /// Class lists found: attribute values, function arguments and templates
+    pub class_lists: usize,
+    /// Classes across all lists
+    pub total_classes: usize,
+    /// Lists whose classes are not in sorted order
+    pub unsorted_lists: usize,
+    /// Classes without a known utility prefix, by their name without
+    /// variants, with their number of occurrences, most common first
+    pub unknown_classes: Vec<(String, usize)>,
+    /// Classes written in syntax of Tailwind v3 that v4 deprecates or
+    /// dropped, such as a leading `!` or `bg-opacity-50`
+    pub v3_syntax: usize,
+    /// Classes written in syntax only Tailwind v4 understands, such as a
+    /// trailing `!` or `bg-(--brand)`
+    pub v4_syntax: usize,
+    /// Whether the file mixes v3 and v4 syntax, as happens halfway through
+    /// a migration
+    pub mixed_versions: bool,
+}
+
+/// Tailwind major version whose syntax a class is written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Syntax {
+    V3,
+    V4,
+}
+
+/// Utilities whose `-opacity-*` classes v4 replaced with color modifiers
+/// such as `bg-black/50`
+const OPACITY_UTILITIES: &[&str] = &["bg", "text", "border", "divide", "ring", "placeholder"];
+
+/// v3 names that v4 removed or renamed
+const V3_NAMES: &[&str] = &["overflow-ellipsis", "decoration-slice", "decoration-clone"];
+const V3_PREFIXES: &[&str] = &["flex-shrink", "flex-grow", "bg-gradient-to-"];
+
+/// Names that only exist since v4
+const V4_NAMES: &[&str] = &[
+    "shadow-xs",
+    "rounded-xs",
+    "blur-xs",
+    "drop-shadow-xs",
+    "backdrop-blur-xs",
+];
+const V4_PREFIXES: &[&str] = &[
+    "bg-linear-",
+    "bg-radial",
+    "bg-conic",
+    "inset-shadow-",
+    "inset-ring-",
+];
+
+/// Version whose syntax a class needs or belongs to, if it is specific to
+/// one
+fn syntax_version(class: &str) -> Option<Syntax> {
+    let (variants, utility) = match variant_separator(class) {
+        Some(colon) => (&class[..colon], &class[colon + 1..]),
+        None => ("", class),
+    };
+    if utility.starts_with('!') {
+        return Some(Syntax::V3);
+    }
+    if utility.ends_with('!') {
+        return Some(Syntax::V4);
+    }
+
+    let base = utility.strip_prefix('-').unwrap_or(utility);
+    let opacity = base
+        .split_once("-opacity-")
+        .is_some_and(|(utility, _)| OPACITY_UTILITIES.contains(&utility));
+    if opacity
+        || V3_NAMES.contains(&base)
+        || V3_PREFIXES.iter().any(|prefix| base.starts_with(prefix))
+    {
+        return Some(Syntax::V3);
+    }
+
+    // CSS variable shorthands as in `bg-(--brand)`, and the `starting:` and
+    // `not-*:` variants
+    let variable = base.contains("-(") && base.ends_with(')');
+    let v4_variant = !variants.is_empty()
+        && variants
+            .split(':')
+            .any(|variant| variant == "starting" || variant.starts_with("not-"));
+    if variable
+        || v4_variant
+        || V4_NAMES.contains(&base)
+        || V4_PREFIXES.iter().any(|prefix| base.starts_with(prefix))
+    {
+        return Some(Syntax::V4);
+    }
+
+    None
+}
+
+/// Analyze a file with the default configuration
+pub fn analyze(content: &str, format: Option<FileFormat>) -> Report {
+    analyze_with(content, format, &Configuration::default())
+}
+
+/// Measure how the class lists of a file are sorted, without rewriting it
+///
+/// Class lists are found exactly as when formatting, so `unsorted_lists` is
+/// the number of lists the formatter would change.
+pub fn analyze_with(content: &str, format: Option<FileFormat>, config: &Configuration) -> Report {
+    let parser = file_parser(config, format);
+    let mut matches = extract_matches(config, &parser, format, content);
+    matches.sort_by_key(|m| m.start);
+
+    let safelist = Safelist::new(config.safelist.clone());
+    let options = config.sort_options();
+    let mut cache = SortCache::new();
+    let mut sorter = cache.sorter(safelist.clone(), options);
+    let rewrite = RewriteOptions::new(config);
+    let mut report = Report::default();
+    let mut unknown: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut last_end = 0;
+
+    for class_match in &matches {
+        if class_match.start < last_end || !class_match.is_valid_in(content) {
+            continue;
+        }
+        last_end = class_match.end;
+
+        let classes = &content[class_match.start..class_match.end];
+        report.class_lists += 1;
+        let sorted = if config.sort_within_groups_only && classes.contains('\n') {
+            sort_lines(classes, &mut sorter, &rewrite)
+        } else {
+            sort_list(classes, &mut sorter, &rewrite)
+        };
+        if !same_class_order(&sorted, classes) {
+            report.unsorted_lists += 1;
+        }
+        for class in classes.split_whitespace() {
+            if config.group_separator.as_deref() == Some(class) {
+                continue;
+            }
+            report.total_classes += 1;
+            if options.limits.exceeded_by(class) || is_placeholder(class) {
+                continue;
+            }
+            match syntax_version(class) {
+                Some(Syntax::V3) => report.v3_syntax += 1,
+                Some(Syntax::V4) => report.v4_syntax += 1,
+                None => {}
+            }
+            let class = TailwindClass::parse_with(class, &safelist);
+            if !class.is_known() {
+                *unknown.entry(class.base).or_default() += 1;
+            }
+        }
+    }
+
+    // Ties stay in alphabetical order
+    report.unknown_classes = unknown
+        .into_iter()
+        .map(|(class, count)| (class.to_string(), count))
+        .collect();
+    report
+        .unknown_classes
+        .sort_by_key(|(_, count)| Reverse(*count));
+    report.mixed_versions = report.v3_syntax > 0 && report.v4_syntax > 0;
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_counts() {
+        let content = r#"<div class="p-4 z-10 btn">
+  <span class="flex mt-2 btn hover:card">x</span>
+  <p class="card">y</p>
+</div>"#;
+
+        let report = analyze(content, Some(FileFormat::Html));
+        assert_eq!(
+            report,
+            Report {
+                class_lists: 3,
+                total_classes: 8,
+                unsorted_lists: 1,
+                unknown_classes: vec![("btn".to_string(), 2), ("card".to_string(), 2)],
+                v3_syntax: 0,
+                v4_syntax: 0,
+                mixed_versions: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_analyze_with_config() {
+        let content = r#"<div class="p-4 legacy-grid">x</div>"#;
+
+        // Unknown classes already sort last
+        let report = analyze(content, Some(FileFormat::Html));
+        assert_eq!(report.unsorted_lists, 0);
+        assert_eq!(report.unknown_classes, vec![("legacy-grid".to_string(), 1)]);
+
+        let config = Configuration {
+            safelist: vec!["legacy-*".to_string()],
+            ..Default::default()
+        };
+        let report = analyze_with(content, Some(FileFormat::Html), &config);
+        assert_eq!(report.class_lists, 1);
+        assert!(report.unknown_classes.is_empty());
+
+        // Group separators aren't classes, and a list missing them is unsorted
+        let config = Configuration {
+            group_separator: Some("|".to_string()),
+            ..Default::default()
+        };
+        let content = r#"<div class="flex | p-4 | legacy-grid">x</div><p class="flex p-4">y</p>"#;
+        let report = analyze_with(content, Some(FileFormat::Html), &config);
+        assert_eq!(report.total_classes, 5);
+        assert_eq!(report.unsorted_lists, 1);
+        assert_eq!(report.unknown_classes, vec![("legacy-grid".to_string(), 1)]);
+
+        // Lines sorted on their own are sorted lists
+        let content = "<div class=\"p-4 z-10\n  flex\">x</div>";
+        assert_eq!(analyze(content, Some(FileFormat::Html)).unsorted_lists, 1);
+        let config = Configuration {
+            sort_within_groups_only: true,
+            ..Default::default()
+        };
+        let content = "<div class=\"z-10 p-4\n  flex\">x</div>";
+        let report = analyze_with(content, Some(FileFormat::Html), &config);
+        assert_eq!(report.unsorted_lists, 0);
+    }
+
+    #[test]
+    fn test_syntax_version() {
+        for class in [
+            "!p-4",
+            "hover:!mt-2",
+            "bg-opacity-50",
+            "md:text-opacity-75",
+            "flex-shrink-0",
+            "flex-grow",
+            "overflow-ellipsis",
+            "bg-gradient-to-r",
+        ] {
+            assert_eq!(syntax_version(class), Some(Syntax::V3), "{class}");
+        }
+        for class in [
+            "p-4!",
+            "bg-(--brand)",
+            "shadow-xs",
+            "bg-linear-to-r",
+            "inset-shadow-sm",
+            "starting:opacity-0",
+            "not-hover:underline",
+        ] {
+            assert_eq!(syntax_version(class), Some(Syntax::V4), "{class}");
+        }
+        for class in [
+            "p-4",
+            "bg-black/50",
+            "opacity-50",
+            "shrink-0",
+            "bg-[url(a)]",
+            "shadow-sm",
+        ] {
+            assert_eq!(syntax_version(class), None, "{class}");
+        }
+    }
+
+    #[test]
+    fn test_analyze_mixed_versions() {
+        let content = r#"<div class="!p-4 bg-opacity-50">
+  <span class="mt-2! bg-(--brand) flex">x</span>
+</div>"#;
+        let report = analyze(content, Some(FileFormat::Html));
+        assert_eq!(report.v3_syntax, 2);
+        assert_eq!(report.v4_syntax, 2);
+        assert!(report.mixed_versions);
+
+        let report = analyze(r#"<p class="!p-4 flex"></p>"#, Some(FileFormat::Html));
+        assert_eq!(report.v3_syntax, 1);
+        assert!(!report.mixed_versions);
+    }
+
+    #[test]
+    fn test_analyze_without_class_lists() {
+        assert_eq!(
+            analyze("<div>plain</div>", Some(FileFormat::Html)),
+            Report::default()
+        );
+        assert_eq!(analyze(r#"clsx("z-10 p-4")"#, None).class_lists, 1);
+    }
+
+    #[test]
+    fn test_report_serializes_to_json() {
+        let report = analyze(r#"<a class="p-4 z-10"></a>"#, Some(FileFormat::Html));
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(
+            json,
+            r#"{"classLists":1,"totalClasses":2,"unsortedLists":1,"unknownClasses":[],"v3Syntax":0,"v4Syntax":0,"mixedVersions":false}"#
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use crate::sorter::{sort_classes_within, with_group_separators, Safelist, SortOptions};
+
+/// Least-recently-used cache of sorted class lists
+///
+/// Lives in the plugin handler, so watch-mode reformats and the many
+/// repeated class lists of a big project reuse earlier sorting work across
+/// files and requests. Entries are keyed by a fingerprint of the sorting
+/// configuration plus the raw class string, so files with different
+/// configurations never share results. Only maps and counters are used so
+/// the cache can be built in a `const fn`.
+#[derive(Debug)]
+pub struct SortCache {
+    /// Sorted output and last use by configuration fingerprint and input
+    entries: BTreeMap<u64, BTreeMap<String, CacheEntry>>,
+    /// Entry keys by last use, oldest first
+    recency: BTreeMap<u64, (u64, String)>,
+    /// Use counter, increasing with every lookup and insert
+    tick: u64,
+    len: usize,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    sorted: String,
+    last_used: u64,
+}
+
+impl SortCache {
+    /// Maximum number of cached class lists
+    pub const CAPACITY: usize = 4096;
+
+    pub const fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            len: 0,
+        }
+    }
+
+    /// Sorter for one file, reusing cached results for its configuration
+    pub fn sorter(&mut self, safelist: Safelist, options: SortOptions) -> CachedSorter<'_> {
+        let fingerprint = fingerprint(&safelist, &options);
+        CachedSorter {
+            cache: self,
+            safelist,
+            options,
+            fingerprint,
+        }
+    }
+
+    /// Number of cached class lists
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&mut self, fingerprint: u64, classes: &str) -> Option<String> {
+        let entry = self.entries.get_mut(&fingerprint)?.get_mut(classes)?;
+        self.tick += 1;
+        if let Some(key) = self.recency.remove(&entry.last_used) {
+            self.recency.insert(self.tick, key);
+        }
+        entry.last_used = self.tick;
+        Some(entry.sorted.clone())
+    }
+
+    fn insert(&mut self, fingerprint: u64, classes: &str, sorted: String) {
+        if self.len >= Self::CAPACITY {
+            self.evict_oldest();
+        }
+        self.tick += 1;
+        self.entries.entry(fingerprint).or_default().insert(
+            classes.to_string(),
+            CacheEntry {
+                sorted,
+                last_used: self.tick,
+            },
+        );
+        self.recency
+            .insert(self.tick, (fingerprint, classes.to_string()));
+        self.len += 1;
+    }
+
+    fn evict_oldest(&mut self) {
+        let Some((_, (fingerprint, classes))) = self.recency.pop_first() else {
+            return;
+        };
+        if let Some(lists) = self.entries.get_mut(&fingerprint) {
+            lists.remove(&classes);
+            if lists.is_empty() {
+                self.entries.remove(&fingerprint);
+            }
+        }
+        self.len -= 1;
+    }
+}
+
+impl Default for SortCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sorts class lists for one configuration through a `SortCache`
+pub struct CachedSorter<'a> {
+    cache: &'a mut SortCache,
+    safelist: Safelist,
+    options: SortOptions,
+    fingerprint: u64,
+}
+
+/// Hash of the options that affect the sort order
+fn fingerprint(safelist: &Safelist, options: &SortOptions) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    safelist.fingerprint().hash(&mut hasher);
+    options.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl CachedSorter<'_> {
+    /// Sort a class list, reusing the result of an earlier identical one
+    pub fn sort(&mut self, classes: &str) -> String {
+        if let Some(sorted) = self.cache.get(self.fingerprint, classes) {
+            return sorted;
+        }
+        let sorted = sort_classes_within(classes, &self.safelist, &self.options);
+        self.cache.insert(self.fingerprint, classes, sorted.clone());
+        sorted
+    }
+
+    /// Put `separator` between the groups of a sorted class list
+    pub fn separate_groups(&self, sorted: &str, separator: &str) -> String {
+        with_group_separators(sorted, separator, &self.safelist, &self.options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_results_match_sorting() {
+        let mut cache = SortCache::new();
+        let mut sorter = cache.sorter(Safelist::default(), SortOptions::default());
+
+        assert_eq!(sorter.sort("z-10 p-4 mt-2"), "z-10 mt-2 p-4");
+        assert_eq!(sorter.sort("z-10 p-4 mt-2"), "z-10 mt-2 p-4");
+        assert_eq!(sorter.sort("flex"), "flex");
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_configurations_do_not_share_results() {
+        let mut cache = SortCache::new();
+        let input = "legacy-grid custom p-4";
+
+        let plain = cache
+            .sorter(Safelist::default(), SortOptions::default())
+            .sort(input);
+        let safelisted = cache
+            .sorter(
+                Safelist::new(vec!["legacy-*".to_string()]),
+                SortOptions::default(),
+            )
+            .sort(input);
+
+        assert_eq!(plain, "p-4 custom legacy-grid");
+        assert_eq!(safelisted, "p-4 legacy-grid custom");
+        assert_eq!(cache.len(), 2);
+
+        let options = SortOptions {
+            important_affects_order: false,
+            ..SortOptions::default()
+        };
+        let important = "!mt-2 p-4";
+        assert_eq!(
+            cache
+                .sorter(Safelist::default(), SortOptions::default())
+                .sort(important),
+            "p-4 !mt-2"
+        );
+        assert_eq!(
+            cache.sorter(Safelist::default(), options).sort(important),
+            "!mt-2 p-4"
+        );
+    }
+
+    #[test]
+    fn test_least_recently_used_entry_evicted() {
+        let mut cache = SortCache::new();
+        let mut sorter = cache.sorter(Safelist::default(), SortOptions::default());
+        for i in 0..SortCache::CAPACITY {
+            sorter.sort(&format!("p-{i} m-{i}"));
+        }
+        // Touch the oldest entry so the second oldest is evicted instead
+        sorter.sort("p-0 m-0");
+        sorter.sort("p-4 z-10");
+
+        assert_eq!(cache.len(), SortCache::CAPACITY);
+        let lists = &cache.entries[&fingerprint(&Safelist::default(), &SortOptions::default())];
+        assert!(lists.contains_key("p-0 m-0"));
+        assert!(!lists.contains_key("p-1 m-1"));
+        assert!(lists.contains_key("p-4 z-10"));
+    }
+}
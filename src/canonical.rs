@@ -0,0 +1,271 @@
+use crate::sorter::variant_separator;
+
+/// Default theme spacing steps with the arbitrary values they stand for,
+/// in `rem` and in `px` at the default 16px root font size
+const SPACING: &[(&str, &[&str])] = &[
+    ("0", &["0px", "0"]),
+    ("px", &["1px"]),
+    ("0.5", &["0.125rem", "2px"]),
+    ("1", &["0.25rem", "4px"]),
+    ("1.5", &["0.375rem", "6px"]),
+    ("2", &["0.5rem", "8px"]),
+    ("2.5", &["0.625rem", "10px"]),
+    ("3", &["0.75rem", "12px"]),
+    ("3.5", &["0.875rem", "14px"]),
+    ("4", &["1rem", "16px"]),
+    ("5", &["1.25rem", "20px"]),
+    ("6", &["1.5rem", "24px"]),
+    ("7", &["1.75rem", "28px"]),
+    ("8", &["2rem", "32px"]),
+    ("9", &["2.25rem", "36px"]),
+    ("10", &["2.5rem", "40px"]),
+    ("11", &["2.75rem", "44px"]),
+    ("12", &["3rem", "48px"]),
+    ("14", &["3.5rem", "56px"]),
+    ("16", &["4rem", "64px"]),
+    ("20", &["5rem", "80px"]),
+    ("24", &["6rem", "96px"]),
+    ("28", &["7rem", "112px"]),
+    ("32", &["8rem", "128px"]),
+    ("36", &["9rem", "144px"]),
+    ("40", &["10rem", "160px"]),
+    ("44", &["11rem", "176px"]),
+    ("48", &["12rem", "192px"]),
+    ("52", &["13rem", "208px"]),
+    ("56", &["14rem", "224px"]),
+    ("60", &["15rem", "240px"]),
+    ("64", &["16rem", "256px"]),
+    ("72", &["18rem", "288px"]),
+    ("80", &["20rem", "320px"]),
+    ("96", &["24rem", "384px"]),
+];
+
+/// Utilities whose values come from the spacing scale
+const SPACING_UTILITIES: &[&str] = &[
+    "m",
+    "mx",
+    "my",
+    "ms",
+    "me",
+    "mt",
+    "mr",
+    "mb",
+    "ml",
+    "p",
+    "px",
+    "py",
+    "ps",
+    "pe",
+    "pt",
+    "pr",
+    "pb",
+    "pl",
+    "gap",
+    "gap-x",
+    "gap-y",
+    "space-x",
+    "space-y",
+    "inset",
+    "inset-x",
+    "inset-y",
+    "start",
+    "end",
+    "top",
+    "right",
+    "bottom",
+    "left",
+    "w",
+    "h",
+    "size",
+    "max-h",
+    "basis",
+    "indent",
+    "translate-x",
+    "translate-y",
+    "scroll-m",
+    "scroll-mx",
+    "scroll-my",
+    "scroll-mt",
+    "scroll-mr",
+    "scroll-mb",
+    "scroll-ml",
+    "scroll-p",
+    "scroll-px",
+    "scroll-py",
+    "scroll-pt",
+    "scroll-pr",
+    "scroll-pb",
+    "scroll-pl",
+];
+
+/// Default theme colors with their exact arbitrary forms. The palette
+/// shades are left out, since Tailwind v4 defines them in OKLCH and no hex
+/// value renders the same
+const SPECIAL_COLORS: &[(&str, &[&str])] = &[
+    ("inherit", &["inherit"]),
+    ("current", &["currentColor"]),
+    ("transparent", &["transparent"]),
+    ("black", &["#000", "#000000"]),
+    ("white", &["#fff", "#ffffff"]),
+];
+
+/// Utilities whose values are colors
+const COLOR_UTILITIES: &[&str] = &[
+    "text",
+    "bg",
+    "border",
+    "border-x",
+    "border-y",
+    "border-s",
+    "border-e",
+    "border-t",
+    "border-r",
+    "border-b",
+    "border-l",
+    "divide",
+    "outline",
+    "ring",
+    "ring-offset",
+    "fill",
+    "stroke",
+    "decoration",
+    "accent",
+    "caret",
+    "placeholder",
+    "shadow",
+    "from",
+    "via",
+    "to",
+];
+
+/// Replace the arbitrary values of a class list that exactly match a
+/// default theme value with the named utility, keeping the whitespace
+///
+/// `mt-[16px]` becomes `mt-4` and `text-[#fff]` becomes `text-white`, with
+/// variants, `!` and a leading `-` kept. Values are compared byte for byte,
+/// so `mt-[1.0rem]` or `bg-[#FFF]` stay as they are, and so do hex values
+/// of palette shades such as `bg-[#3b82f6]`.
+pub fn canonicalize_classes(classes: &str) -> String {
+    let mut canonical = String::with_capacity(classes.len());
+    let mut rest = classes;
+
+    while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+        canonical.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        match canonical_class(&rest[..end]) {
+            Some(class) => canonical.push_str(&class),
+            None => canonical.push_str(&rest[..end]),
+        }
+        rest = &rest[end..];
+    }
+    canonical.push_str(rest);
+
+    canonical
+}
+
+/// Named form of a class with an arbitrary theme value, if there is one
+fn canonical_class(class: &str) -> Option<String> {
+    let (variants, rest) = class.split_at(variant_separator(class).map_or(0, |colon| colon + 1));
+    let (important, rest) = match rest.strip_prefix('!') {
+        Some(rest) => ("!", rest),
+        None => ("", rest),
+    };
+    let (rest, trailing_important) = match rest.strip_suffix('!') {
+        Some(rest) => (rest, "!"),
+        None => (rest, ""),
+    };
+    let (negative, rest) = match rest.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", rest),
+    };
+
+    // An opacity modifier as in `bg-[#fff]/50` may follow a color
+    let (utility, value, modifier) = {
+        let close = rest.rfind(']')?;
+        let open = rest[..close].find("-[")?;
+        (&rest[..open], &rest[open + 2..close], &rest[close + 1..])
+    };
+
+    let name = if SPACING_UTILITIES.contains(&utility) && modifier.is_empty() {
+        spacing_step(value)?
+    } else if COLOR_UTILITIES.contains(&utility) && negative.is_empty() {
+        if !modifier.is_empty() && !modifier.starts_with('/') {
+            return None;
+        }
+        color_name(value)?
+    } else {
+        return None;
+    };
+
+    Some(format!(
+        "{variants}{important}{negative}{utility}-{name}{modifier}{trailing_important}"
+    ))
+}
+
+/// Spacing step of an arbitrary length
+fn spacing_step(value: &str) -> Option<String> {
+    SPACING
+        .iter()
+        .find(|(_, values)| values.contains(&value))
+        .map(|(step, _)| step.to_string())
+}
+
+/// Color name of an arbitrary color
+fn color_name(value: &str) -> Option<String> {
+    SPECIAL_COLORS
+        .iter()
+        .find(|(_, values)| values.contains(&value))
+        .map(|(name, _)| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_spacing() {
+        assert_eq!(canonicalize_classes("mt-[16px]"), "mt-4");
+        assert_eq!(canonicalize_classes("p-[0.5rem] w-[1px]"), "p-2 w-px");
+        assert_eq!(
+            canonicalize_classes("-mx-[2px] hover:gap-[0]"),
+            "-mx-0.5 hover:gap-0"
+        );
+        assert_eq!(canonicalize_classes("!mt-[16px] mt-[16px]!"), "!mt-4 mt-4!");
+    }
+
+    #[test]
+    fn test_canonical_colors() {
+        assert_eq!(canonicalize_classes("text-[#fff]"), "text-white");
+        assert_eq!(
+            canonicalize_classes("bg-[#fff]/50 dark:border-t-[#000000]"),
+            "bg-white/50 dark:border-t-black"
+        );
+        assert_eq!(canonicalize_classes("fill-[currentColor]"), "fill-current");
+    }
+
+    #[test]
+    fn test_palette_shades_kept() {
+        // `blue-500` is `oklch(62.3% 0.214 259.815)` in Tailwind v4, not the
+        // v3 `#3b82f6`, so neither hex value stands for it
+        let kept = "bg-[#3b82f6] text-[#ef4444]/50 border-[#fafafa]";
+        assert_eq!(canonicalize_classes(kept), kept);
+        // The spacing scale is `calc(var(--spacing) * 4)` with a 0.25rem step
+        assert_eq!(canonicalize_classes("mt-[1rem]"), "mt-4");
+    }
+
+    #[test]
+    fn test_values_without_exact_match_kept() {
+        let kept = "mt-[1.0rem] mt-[17px] bg-[#FFF] text-[16px] w-[50%] bg-[#fafafa] \
+                    text-[color:#fff] [margin:16px] mt-[16px]/50 -bg-[#fff] mt-4";
+        assert_eq!(canonicalize_classes(kept), kept);
+    }
+
+    #[test]
+    fn test_whitespace_kept() {
+        assert_eq!(
+            canonicalize_classes("  p-4\n\tmt-[16px]  flex "),
+            "  p-4\n\tmt-4  flex "
+        );
+    }
+}
@@ -1,9 +1,17 @@
 use dprint_core::configuration::{
-    get_nullable_value, get_unknown_property_diagnostics, ConfigKeyMap, ConfigurationDiagnostic,
-    GlobalConfiguration,
+    get_nullable_value, get_unknown_property_diagnostics, ConfigKeyMap, ConfigKeyValue,
+    ConfigurationDiagnostic, GlobalConfiguration,
+};
+use dprint_core::plugins::{
+    ConfigChange, ConfigChangeKind, ConfigChangePathItem, FileMatchingInfo,
+    PluginResolveConfigurationResult,
 };
-use dprint_core::plugins::{FileMatchingInfo, PluginResolveConfigurationResult};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use crate::parser::{CustomFormat, FileFormat};
+use crate::sorter::{ClassLimits, SortOptions};
 
 /// Configuration for the TailwindCSS plugin
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +29,176 @@ pub struct Configuration {
 
     /// HTML attributes to format
     pub tailwind_attributes: Vec<String>,
+
+    /// Per-format attribute lists, overriding `tailwind_attributes`
+    pub format_attributes: FormatAttributes,
+
+    /// Tagged template names containing class lists
+    pub tailwind_templates: Vec<String>,
+
+    /// Allow attributes that hold non-class tokens, such as `rel`, in the
+    /// attribute lists
+    pub allow_non_class_attributes: bool,
+
+    /// Extra file extensions formatted as one of the supported formats,
+    /// e.g. `twig` or `blade.php` as HTML
+    pub format_as: BTreeMap<String, FileFormat>,
+
+    /// Template languages described in the config, whose extensions map to
+    /// `FileFormat::Custom` with their index unless `formatAs` maps them
+    pub custom_formats: Vec<CustomFormat>,
+
+    /// Also claim `.js`, `.mjs`, `.cjs`, `.ts`, `.mts` and `.cts` files
+    pub format_scripts: bool,
+
+    /// Also claim `.css` files to sort Tailwind v4 `@source inline(...)` lists
+    pub format_css: bool,
+
+    /// Also claim `.php` files, sorting the HTML between `<?php ... ?>` tags
+    pub format_php: bool,
+
+    /// Class builder methods and constructors, such as Blazor's `AddClass`
+    /// or `CssBuilder`, whose first string argument is a class list
+    pub class_builders: Vec<String>,
+
+    /// Also claim `.cs` files to sort class builder calls
+    pub format_csharp: bool,
+
+    /// Also claim `.kt` and `.kts` files to sort calls such as Compose
+    /// HTML's `classes(...)`
+    pub format_kotlin: bool,
+
+    /// Function names whose string arguments are class lists in Kotlin
+    /// files, in addition to `tailwind_functions`
+    pub kotlin_functions: Vec<String>,
+
+    /// Also claim `.py` files to sort class keyword arguments and dict
+    /// entries
+    pub format_python: bool,
+
+    /// Keyword argument and dict key names whose string values are class
+    /// lists in Python files, such as FastHTML's `cls` or Django's `"class"`
+    pub python_class_names: Vec<String>,
+
+    /// Also claim `.ipynb` files to sort the markup in markdown and `%%html`
+    /// cells
+    pub format_notebooks: bool,
+
+    /// Also claim `.xml`, `.xsl`, `.xslt` and `.svg` files, sorting only the
+    /// attributes listed for XML
+    pub format_xml: bool,
+
+    /// Claim Markdown and MDX files to sort the code blocks in this plugin's
+    /// languages by their format and send other tagged code blocks to the
+    /// host's plugins
+    pub markdown_code_blocks: bool,
+
+    /// Sort the string values of object literal properties named like the
+    /// attributes in script code, such as Storybook `args: { className }`
+    pub sort_object_properties: bool,
+
+    /// Leave test files and snapshots alone, such as `*.test.tsx` and
+    /// `__snapshots__/`, in addition to the `ignore` patterns
+    pub skip_test_files: bool,
+
+    /// Sort class attributes inside HTML-looking string literals in script
+    /// code
+    pub extract_from_html_strings: bool,
+
+    /// Run as a post-processor: format each file with the host's primary
+    /// plugin first, then sort classes in its output
+    pub post_process: bool,
+
+    /// Log which class lists were found, sorted and skipped, and why
+    pub verbose: bool,
+
+    /// Classes, or `*` patterns, that sort as known utilities rather than
+    /// with unknown classes
+    pub safelist: Vec<String>,
+
+    /// Gitignore-style path patterns of files to leave alone
+    pub ignore: Vec<String>,
+
+    /// Elements whose content is left alone in HTML-like markup, such as
+    /// `pre` or a CMS's wrapper tags. Their own attributes are still sorted
+    pub exclude_elements: Vec<String>,
+
+    /// Element factory functions, such as `createElement` or `h`, whose
+    /// object literal arguments hold class lists under the attribute names
+    pub element_factories: Vec<String>,
+
+    /// Sort the `class` props of component tags such as `<Button>` in markup
+    pub sort_component_classes: bool,
+
+    /// Sort neighbouring valueless utility attributes in markup, as in
+    /// `<div p-4 flex>` with UnoCSS's or Windi CSS's attributify mode
+    pub attributify: bool,
+
+    /// JSON and YAML property names whose string values are class lists.
+    /// Setting any also claims `.json`, `.jsonc`, `.yaml` and `.yml` files
+    pub json_class_properties: Vec<String>,
+
+    /// How files whose extension isn't claimed or deferred, such as `.md`
+    /// files routed here by dprint `associations`, are handled
+    pub fallback_extraction: FallbackExtraction,
+
+    /// Extensions left to other plugins unless an option claims them
+    pub defer_extensions: Vec<String>,
+
+    /// Check that sorting the output again leaves it unchanged, and keep
+    /// the original text otherwise. Always on in debug builds
+    pub verify_output: bool,
+
+    /// Quotes used around rewritten attribute values
+    pub quote_style: QuoteStyle,
+
+    /// Separators between the classes of rewritten class lists
+    pub whitespace: Whitespace,
+
+    /// Format files with invalid UTF-8 instead of failing, leaving the
+    /// invalid bytes and the class lists holding them as they are
+    pub lossy_utf8: bool,
+
+    /// Longest class, in bytes, that is parsed for sorting. Longer classes
+    /// move to the end of their list unparsed
+    pub max_class_length: usize,
+
+    /// Most variants a class may have to be parsed for sorting. Classes
+    /// with more move to the end of their list unparsed
+    pub max_variants: usize,
+
+    /// Where placeholder tokens that aren't classes, such as `...` or `*`,
+    /// are put when a class list is sorted
+    pub placeholder_position: PlaceholderPosition,
+
+    /// Whether `!important` classes sort after the other classes. When off,
+    /// the `!` is kept but doesn't change a class's position
+    pub important_affects_order: bool,
+
+    /// Wrap quoted attribute values whose line runs past a length once
+    /// sorted onto several lines. Off unless `wrapClasses` is set
+    pub wrap_classes: Option<WrapClasses>,
+
+    /// Token put between the groups of the Tailwind order, such as layout
+    /// and spacing, in sorted lists. Existing separators are dropped before
+    /// sorting. Off unless `groupSeparator` is set
+    pub group_separator: Option<String>,
+
+    /// Sort each line of a class list written across several lines on its
+    /// own, keeping manual groups instead of merging them
+    pub sort_within_groups_only: bool,
+
+    /// Replace arbitrary values that exactly match a default theme value
+    /// with the named utility, as `mt-[16px]` with `mt-4`
+    pub canonicalize: bool,
+
+    /// Merge pairs of side utilities with the same value into their
+    /// shorthand, as `mt-4 mb-4` into `my-4`
+    pub merge_shorthands: bool,
+
+    /// Sort classes in web component markup in script code: `html` tagged
+    /// templates (Lit, FAST) and templates returned from `render()`
+    pub web_components: bool,
 }
 
 impl Default for Configuration {
@@ -31,20 +209,553 @@ impl Default for Configuration {
             tailwind_functions: vec![
                 "classnames".to_string(),
                 "clsx".to_string(),
+                "cn".to_string(),
                 "ctl".to_string(),
                 "cva".to_string(),
+                "cx".to_string(),
                 "tw".to_string(),
+                "twJoin".to_string(),
+                "twMerge".to_string(),
             ],
             tailwind_attributes: vec!["class".to_string(), "className".to_string()],
+            format_attributes: FormatAttributes::recommended(),
+            tailwind_templates: vec!["tw".to_string(), "css".to_string()],
+            allow_non_class_attributes: false,
+            format_as: BTreeMap::new(),
+            custom_formats: Vec::new(),
+            exclude_elements: Vec::new(),
+            format_scripts: false,
+            format_css: false,
+            extract_from_html_strings: false,
+            post_process: false,
+            verbose: false,
+            safelist: Vec::new(),
+            ignore: Vec::new(),
+            element_factories: vec![
+                "createElement".to_string(),
+                "h".to_string(),
+                "jsx".to_string(),
+            ],
+            sort_component_classes: true,
+            attributify: false,
+            json_class_properties: Vec::new(),
+            fallback_extraction: FallbackExtraction::Full,
+            defer_extensions: ["json", "jsonc", "toml", "yaml", "yml"]
+                .iter()
+                .map(|extension| extension.to_string())
+                .collect(),
+            verify_output: false,
+            quote_style: QuoteStyle::Preserve,
+            whitespace: Whitespace::Normalize,
+            lossy_utf8: false,
+            web_components: false,
+            format_php: false,
+            class_builders: vec!["CssBuilder".to_string(), "AddClass".to_string()],
+            format_csharp: false,
+            format_kotlin: false,
+            kotlin_functions: vec!["classes".to_string()],
+            format_python: false,
+            python_class_names: ["cls", "class_", "klass", "class"]
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            format_notebooks: false,
+            format_xml: false,
+            markdown_code_blocks: false,
+            sort_object_properties: false,
+            skip_test_files: false,
+            max_class_length: ClassLimits::default().max_length,
+            max_variants: ClassLimits::default().max_variants,
+            placeholder_position: PlaceholderPosition::End,
+            important_affects_order: true,
+            wrap_classes: None,
+            group_separator: None,
+            sort_within_groups_only: false,
+            canonicalize: false,
+            merge_shorthands: false,
+        }
+    }
+}
+
+impl Configuration {
+    /// Limits past which classes are passed through without being parsed
+    pub fn class_limits(&self) -> ClassLimits {
+        ClassLimits {
+            max_length: self.max_class_length,
+            max_variants: self.max_variants,
+        }
+    }
+
+    /// Options besides the safelist that change the sort order
+    pub fn sort_options(&self) -> SortOptions {
+        SortOptions {
+            limits: self.class_limits(),
+            important_affects_order: self.important_affects_order,
+        }
+    }
+
+    /// Attributes to format in files of the given format
+    ///
+    /// Unknown formats use `tailwind_attributes`.
+    pub fn attributes_for(&self, format: Option<FileFormat>) -> &[String] {
+        format
+            .and_then(|format| self.format_attributes.get(format))
+            .unwrap_or(&self.tailwind_attributes)
+    }
+
+    /// Function names to look for in files of the given format
+    ///
+    /// Kotlin files also use `kotlin_functions`.
+    pub fn functions_for(&self, format: Option<FileFormat>) -> Vec<String> {
+        let mut functions = self.tailwind_functions.clone();
+        if format == Some(FileFormat::Kotlin) {
+            functions.extend(
+                self.kotlin_functions
+                    .iter()
+                    .filter(|name| !functions.contains(name))
+                    .cloned()
+                    .collect::<Vec<_>>(),
+            );
+        }
+        functions
+    }
+
+    /// File extensions claimed from dprint
+    ///
+    /// Only the extensions of formats that are enabled are listed, so other
+    /// plugins keep their files unless an option opts in.
+    pub fn file_extensions(&self) -> Vec<String> {
+        let mut file_extensions: Vec<String> = [
+            "html", "htm", "jsx", "tsx", "vue", "svelte", "astro", "marko", "riot", "mjml",
+            "razor", "cshtml",
+        ]
+        .iter()
+        .map(|extension| extension.to_string())
+        .collect();
+        // Script files are usually claimed by dprint-plugin-typescript, so they
+        // are opt-in, as are web component templates
+        if self.format_scripts || self.web_components {
+            file_extensions.extend(
+                ["js", "mjs", "cjs", "ts", "mts", "cts"]
+                    .iter()
+                    .map(|extension| extension.to_string()),
+            );
+        }
+        // Stylesheets are usually claimed by a CSS formatter, so they are opt-in
+        if self.format_css {
+            file_extensions.push("css".to_string());
+        }
+        // PHP, C#, Kotlin and Python files and notebooks may belong to a
+        // formatter of their language, so they are opt-in
+        if self.format_php {
+            file_extensions.push("php".to_string());
+        }
+        if self.format_csharp {
+            file_extensions.push("cs".to_string());
+        }
+        if self.format_kotlin {
+            file_extensions.extend(["kt".to_string(), "kts".to_string()]);
+        }
+        if self.format_python {
+            file_extensions.push("py".to_string());
+        }
+        if self.format_notebooks {
+            file_extensions.push("ipynb".to_string());
+        }
+        // XML files are usually claimed by a markup formatter as well
+        if self.format_xml {
+            file_extensions.extend(
+                ["xml", "xsl", "xslt", "svg"]
+                    .iter()
+                    .map(|extension| extension.to_string()),
+            );
+        }
+        // Data files are only claimed once class-holding properties are named
+        if !self.json_class_properties.is_empty() {
+            file_extensions.extend(
+                ["json", "jsonc", "yaml", "yml"]
+                    .iter()
+                    .map(|extension| extension.to_string()),
+            );
+        }
+        // dprint only sends files by their last extension, so `blade.php`
+        // registers `php`
+        for extension in self.format_as.keys() {
+            let last = extension
+                .rsplit('.')
+                .next()
+                .unwrap_or(extension)
+                .to_string();
+            if !file_extensions.contains(&last) {
+                file_extensions.push(last);
+            }
+        }
+
+        file_extensions
+    }
+}
+
+/// Extraction used for files of no known format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FallbackExtraction {
+    /// Leave the files alone
+    Off,
+    /// Only sort attributes inside element tags
+    Attributes,
+    /// Sort attributes anywhere, function calls and tagged templates
+    Full,
+}
+
+impl FromStr for FallbackExtraction {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "off" => Ok(FallbackExtraction::Off),
+            "attributes" => Ok(FallbackExtraction::Attributes),
+            "full" => Ok(FallbackExtraction::Full),
+            _ => Err(format!(
+                "'{}' is not a fallback extraction; expected 'off', 'attributes' or 'full'",
+                value
+            )),
+        }
+    }
+}
+
+/// Quotes around class attribute values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuoteStyle {
+    /// Keep `'` or `"` exactly as written
+    Preserve,
+    /// Use `"`
+    Double,
+    /// Use `'`
+    Single,
+}
+
+impl QuoteStyle {
+    /// Quote to put around a value currently delimited by `quote`, or
+    /// `None` to keep the value's quotes
+    ///
+    /// Values containing the new quote or a backslash keep theirs, since
+    /// requoting them would end the value early or change its meaning.
+    pub fn requote(self, quote: Option<char>, content: &str) -> Option<char> {
+        let target = match self {
+            QuoteStyle::Preserve => return None,
+            QuoteStyle::Double => '"',
+            QuoteStyle::Single => '\'',
+        };
+        (quote.is_some_and(|quote| quote != target)
+            && !content.contains(target)
+            && !content.contains('\\'))
+        .then_some(target)
+    }
+}
+
+impl FromStr for QuoteStyle {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "preserve" => Ok(QuoteStyle::Preserve),
+            "double" => Ok(QuoteStyle::Double),
+            "single" => Ok(QuoteStyle::Single),
+            _ => Err(format!(
+                "'{}' is not a quote style; expected 'preserve', 'double' or 'single'",
+                value
+            )),
+        }
+    }
+}
+
+/// Whitespace between the classes of a rewritten list
+///
+/// Classes are told apart by any Unicode whitespace, such as line breaks or
+/// non-breaking spaces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Whitespace {
+    /// Join the sorted classes with single spaces
+    Normalize,
+    /// Keep every separator and the leading and trailing whitespace in place
+    /// and only move the classes
+    Preserve,
+}
+
+impl FromStr for Whitespace {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "normalize" => Ok(Whitespace::Normalize),
+            "preserve" => Ok(Whitespace::Preserve),
+            _ => Err(format!(
+                "'{}' is not a whitespace mode; expected 'normalize' or 'preserve'",
+                value
+            )),
+        }
+    }
+}
+
+/// Where placeholder tokens such as `...` end up in a sorted class list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaceholderPosition {
+    /// After every class, as prettier-plugin-tailwindcss does
+    End,
+    /// Before every class
+    Start,
+    /// At the same index in the list as before sorting
+    Preserve,
+}
+
+impl FromStr for PlaceholderPosition {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "end" => Ok(PlaceholderPosition::End),
+            "start" => Ok(PlaceholderPosition::Start),
+            "preserve" => Ok(PlaceholderPosition::Preserve),
+            _ => Err(format!(
+                "'{}' is not a placeholder position; expected 'end', 'start' or 'preserve'",
+                value
+            )),
+        }
+    }
+}
+
+/// Wrapping of long class attribute values
+///
+/// A sorted value that doesn't fit on its line is broken between classes,
+/// filling each line as far as it goes. Continuation lines are indented one
+/// level deeper than the line the attribute is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WrapClasses {
+    /// Longest line, in columns, before a value is wrapped
+    pub max_line_length: usize,
+    /// Columns in one level of indentation
+    pub indent: usize,
+    /// Indent with tabs, each counted as `indent` columns, instead of spaces
+    pub use_tabs: bool,
+}
+
+impl WrapClasses {
+    /// Settings taken from dprint's `lineWidth`, `indentWidth` and `useTabs`
+    pub fn from_global(global_config: &GlobalConfiguration) -> Self {
+        WrapClasses {
+            max_line_length: global_config.line_width.unwrap_or(120) as usize,
+            indent: global_config.indent_width.unwrap_or(2) as usize,
+            use_tabs: global_config.use_tabs.unwrap_or(false),
+        }
+    }
+
+    /// `classes` broken across lines with `newline` when they don't fit
+    /// after `line`, the text their line holds before them, or `None` when
+    /// they fit
+    ///
+    /// The quote closing the value counts toward the last line. A class
+    /// longer than a whole line gets a line of its own.
+    pub fn wrap(&self, line: &str, classes: &str, newline: &str) -> Option<String> {
+        let width = |text: &str| -> usize {
+            text.chars()
+                .map(|c| if c == '\t' { self.indent } else { 1 })
+                .sum()
+        };
+        let start = width(line);
+        if start + width(classes) < self.max_line_length {
+            return None;
+        }
+
+        let mut indentation = line[..line.len() - line.trim_start().len()].to_string();
+        if self.use_tabs {
+            indentation.push('\t');
+        } else {
+            indentation.push_str(&" ".repeat(self.indent));
+        }
+
+        let classes: Vec<&str> = classes.split_whitespace().collect();
+        let mut wrapped = String::new();
+        let mut column = start;
+        for (index, class) in classes.iter().enumerate() {
+            let end = if index + 1 == classes.len() { 1 } else { 0 };
+            let class_width = width(class);
+            if index > 0 {
+                if column + 1 + class_width + end > self.max_line_length {
+                    wrapped.push_str(newline);
+                    wrapped.push_str(&indentation);
+                    column = width(&indentation);
+                } else {
+                    wrapped.push(' ');
+                    column += 1;
+                }
+            }
+            wrapped.push_str(class);
+            column += class_width;
+        }
+        Some(wrapped)
+    }
+}
+
+/// Attribute lists for individual file formats
+///
+/// A format without its own list uses `tailwind_attributes`. TSX and plain
+/// JavaScript/TypeScript modules share the JSX list, and Marko, Riot, PHP,
+/// Razor, notebooks and custom formats share the HTML list. MJML has its
+/// own list, as its components take `css-class` as well, and so does XML,
+/// which has no default attributes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatAttributes {
+    pub html: Option<Vec<String>>,
+    pub jsx: Option<Vec<String>>,
+    pub vue: Option<Vec<String>>,
+    pub svelte: Option<Vec<String>>,
+    pub astro: Option<Vec<String>>,
+    pub mjml: Option<Vec<String>>,
+    pub xml: Option<Vec<String>>,
+}
+
+impl FormatAttributes {
+    /// Config key prefixes for per-format overrides, e.g. `jsx.tailwindAttributes`
+    const PREFIXES: [(&'static str, FileFormat); 7] = [
+        ("html", FileFormat::Html),
+        ("jsx", FileFormat::Jsx),
+        ("vue", FileFormat::Vue),
+        ("svelte", FileFormat::Svelte),
+        ("astro", FileFormat::Astro),
+        ("mjml", FileFormat::Mjml),
+        ("xml", FileFormat::Xml),
+    ];
+
+    /// Defaults used while `tailwindAttributes` is not configured, so HTML
+    /// doesn't scan for `className` and only JSX does. XML attributes must be
+    /// listed explicitly
+    pub fn recommended() -> Self {
+        let list = |names: &[&str]| Some(names.iter().map(|name| name.to_string()).collect());
+        FormatAttributes {
+            html: list(&["class"]),
+            jsx: list(&["className", "class"]),
+            vue: list(&["class"]),
+            svelte: list(&["class"]),
+            astro: list(&["class", "className", "class:list"]),
+            mjml: list(&["class", "css-class"]),
+            xml: list(&[]),
+        }
+    }
+
+    pub fn get(&self, format: FileFormat) -> Option<&Vec<String>> {
+        match format {
+            FileFormat::Html
+            | FileFormat::Marko
+            | FileFormat::Riot
+            | FileFormat::Php
+            | FileFormat::Razor
+            | FileFormat::Notebook
+            | FileFormat::Custom(_) => self.html.as_ref(),
+            FileFormat::Jsx | FileFormat::Tsx | FileFormat::Js | FileFormat::Ts => {
+                self.jsx.as_ref()
+            }
+            FileFormat::Vue => self.vue.as_ref(),
+            FileFormat::Svelte => self.svelte.as_ref(),
+            FileFormat::Astro => self.astro.as_ref(),
+            FileFormat::Mjml => self.mjml.as_ref(),
+            FileFormat::Xml => self.xml.as_ref(),
+            // Stylesheets have no attributes, and Markdown, with code blocks
+            // of any format, uses `tailwindAttributes` like unknown formats
+            FileFormat::Css
+            | FileFormat::Json
+            | FileFormat::Yaml
+            | FileFormat::CSharp
+            | FileFormat::Kotlin
+            | FileFormat::Python
+            | FileFormat::Markdown => None,
+        }
+    }
+
+    fn get_mut(&mut self, format: FileFormat) -> &mut Option<Vec<String>> {
+        match format {
+            FileFormat::Html
+            | FileFormat::Marko
+            | FileFormat::Riot
+            | FileFormat::Php
+            | FileFormat::Razor
+            | FileFormat::Notebook
+            | FileFormat::Custom(_) => &mut self.html,
+            FileFormat::Jsx | FileFormat::Tsx | FileFormat::Js | FileFormat::Ts => &mut self.jsx,
+            FileFormat::Vue => &mut self.vue,
+            FileFormat::Svelte => &mut self.svelte,
+            FileFormat::Astro => &mut self.astro,
+            FileFormat::Mjml => &mut self.mjml,
+            FileFormat::Xml => &mut self.xml,
+            FileFormat::Css
+            | FileFormat::Json
+            | FileFormat::Yaml
+            | FileFormat::CSharp
+            | FileFormat::Kotlin
+            | FileFormat::Python
+            | FileFormat::Markdown => {
+                unreachable!("only markup and JSX formats have an attribute list")
+            }
         }
     }
 }
 
+/// Default `tailwindFunctions` of the first release
+const FIRST_DEFAULT_FUNCTIONS: [&str; 5] = ["classnames", "clsx", "ctl", "cva", "tw"];
+
+/// Function names added to the default `tailwindFunctions` since the first
+/// release, which `config_updates` appends to lists extending the first
+/// defaults
+const ADDED_DEFAULT_FUNCTIONS: [&str; 4] = ["cn", "cx", "twJoin", "twMerge"];
+
+/// Changes bringing an existing configuration up to date with new defaults
+///
+/// A `tailwindFunctions` list holding every name of the first defaults was
+/// meant to extend them, so the names added to the defaults since are
+/// appended to it. Lists that left out a default name are deliberate and
+/// stay as they are, and so does a configuration without the option, which
+/// gets the new defaults anyway.
+pub fn config_updates(config: &ConfigKeyMap) -> Vec<ConfigChange> {
+    let Some(ConfigKeyValue::Array(functions)) = config.get("tailwindFunctions") else {
+        return Vec::new();
+    };
+    let names: Vec<&str> = functions
+        .iter()
+        .filter_map(|value| match value {
+            ConfigKeyValue::String(name) => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+    if !FIRST_DEFAULT_FUNCTIONS
+        .iter()
+        .all(|name| names.contains(name))
+    {
+        return Vec::new();
+    }
+
+    ADDED_DEFAULT_FUNCTIONS
+        .iter()
+        .filter(|name| !names.contains(name))
+        .enumerate()
+        .map(|(index, name)| ConfigChange {
+            path: vec![
+                ConfigChangePathItem::String("tailwindFunctions".to_string()),
+                ConfigChangePathItem::Number(functions.len() + index),
+            ],
+            kind: ConfigChangeKind::Add(ConfigKeyValue::String(name.to_string())),
+        })
+        .collect()
+}
+
 /// Resolve the configuration from the provided config map
 #[allow(dead_code)]
 pub fn resolve_config(
     mut config: ConfigKeyMap,
-    _global_config: &GlobalConfiguration,
+    global_config: &GlobalConfiguration,
 ) -> PluginResolveConfigurationResult<Configuration> {
     let mut diagnostics = Vec::new();
     let mut resolved_config = Configuration::default();
@@ -61,120 +772,855 @@ pub fn resolve_config(
     }
 
     // Parse tailwindFunctions
-    if let Some(functions) = get_nullable_vec(&mut config, "tailwindFunctions", &mut diagnostics) {
+    if let Some(mut functions) =
+        get_nullable_vec(&mut config, "tailwindFunctions", &mut diagnostics)
+    {
+        check_names(
+            &mut functions,
+            "tailwindFunctions",
+            is_valid_function_name,
+            &mut diagnostics,
+        );
         resolved_config.tailwind_functions = functions;
     }
 
     // Parse tailwindAttributes
-    if let Some(attributes) = get_nullable_vec(&mut config, "tailwindAttributes", &mut diagnostics)
+    if let Some(mut attributes) =
+        get_nullable_vec(&mut config, "tailwindAttributes", &mut diagnostics)
     {
+        check_names(
+            &mut attributes,
+            "tailwindAttributes",
+            is_valid_attribute_name,
+            &mut diagnostics,
+        );
         resolved_config.tailwind_attributes = attributes;
+        // An explicit list applies to every format
+        resolved_config.format_attributes = FormatAttributes::default();
     }
 
-    // Check for unknown properties
-    diagnostics.extend(get_unknown_property_diagnostics(config));
+    // Parse per-format overrides such as jsx.tailwindAttributes
+    for (prefix, format) in FormatAttributes::PREFIXES {
+        let key = format!("{}.tailwindAttributes", prefix);
+        if let Some(mut attributes) = get_nullable_vec(&mut config, &key, &mut diagnostics) {
+            check_names(
+                &mut attributes,
+                &key,
+                is_valid_attribute_name,
+                &mut diagnostics,
+            );
+            *resolved_config.format_attributes.get_mut(format) = Some(attributes);
+        }
+    }
 
-    PluginResolveConfigurationResult {
-        config: resolved_config,
-        diagnostics,
-        file_matching: FileMatchingInfo {
-            file_extensions: vec![
-                "html".to_string(),
-                "htm".to_string(),
-                "jsx".to_string(),
-                "tsx".to_string(),
-                "vue".to_string(),
-                "svelte".to_string(),
-                "astro".to_string(),
-            ],
-            file_names: vec![],
-        },
+    // Parse tailwindTemplates
+    if let Some(mut templates) =
+        get_nullable_vec(&mut config, "tailwindTemplates", &mut diagnostics)
+    {
+        check_names(
+            &mut templates,
+            "tailwindTemplates",
+            is_valid_function_name,
+            &mut diagnostics,
+        );
+        resolved_config.tailwind_templates = templates;
     }
-}
 
-#[allow(dead_code)]
-fn get_nullable_vec(
-    config: &mut ConfigKeyMap,
-    key: &str,
-    diagnostics: &mut Vec<ConfigurationDiagnostic>,
-) -> Option<Vec<String>> {
-    use dprint_core::configuration::ConfigKeyValue;
+    // Parse allowNonClassAttributes
+    resolved_config.allow_non_class_attributes =
+        get_nullable_value(&mut config, "allowNonClassAttributes", &mut diagnostics)
+            .unwrap_or(resolved_config.allow_non_class_attributes);
 
-    if let Some(value) = config.swap_remove(key) {
-        match value {
-            ConfigKeyValue::Array(arr) => {
-                let result: Option<Vec<String>> = arr
-                    .iter()
-                    .map(|v| match v {
-                        ConfigKeyValue::String(s) => Some(s.clone()),
-                        _ => None,
-                    })
-                    .collect();
-                if result.is_none() {
-                    diagnostics.push(ConfigurationDiagnostic {
-                        property_name: key.to_string(),
-                        message: format!("Expected array of strings for '{}'", key),
-                    });
-                }
-                result
-            }
-            _ => {
-                diagnostics.push(ConfigurationDiagnostic {
-                    property_name: key.to_string(),
-                    message: format!("Expected array for '{}'", key),
-                });
-                None
+    // Drop attributes whose tokens are not classes, so IDs can't be reordered.
+    // Custom element and shadow part attributes are dropped in any case
+    let allow_non_class = resolved_config.allow_non_class_attributes;
+    remove_non_class_attributes(
+        &mut resolved_config.tailwind_attributes,
+        "tailwindAttributes",
+        allow_non_class,
+        &mut diagnostics,
+    );
+    for (prefix, format) in FormatAttributes::PREFIXES {
+        if let Some(attributes) = resolved_config.format_attributes.get_mut(format) {
+            let key = format!("{}.tailwindAttributes", prefix);
+            remove_non_class_attributes(attributes, &key, allow_non_class, &mut diagnostics);
+        }
+    }
+
+    // Parse formatAs
+    if let Some(format_as) = get_format_as(&mut config, &mut diagnostics) {
+        resolved_config.format_as = format_as;
+    }
+
+    // Parse customFormats. Their extensions are claimed through `formatAs`
+    if let Some(custom_formats) = get_custom_formats(&mut config, &mut diagnostics) {
+        for (index, custom) in custom_formats.iter().enumerate() {
+            for extension in &custom.extensions {
+                resolved_config
+                    .format_as
+                    .entry(extension.clone())
+                    .or_insert(FileFormat::Custom(index));
             }
         }
-    } else {
-        None
+        resolved_config.custom_formats = custom_formats;
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    // Parse excludeElements. An empty list excludes nothing, so it isn't
+    // reported
+    if let Some(mut elements) = get_nullable_vec(&mut config, "excludeElements", &mut diagnostics) {
+        if !elements.is_empty() {
+            check_names(
+                &mut elements,
+                "excludeElements",
+                is_valid_element_name,
+                &mut diagnostics,
+            );
+        }
+        resolved_config.exclude_elements = elements;
+    }
 
-    #[test]
-    fn test_default_config() {
-        let config = Configuration::default();
-        assert!(config.enabled);
-        assert_eq!(config.tailwind_functions.len(), 5);
-        assert_eq!(config.tailwind_attributes.len(), 2);
+    // Parse formatScripts
+    resolved_config.format_scripts =
+        get_nullable_value(&mut config, "formatScripts", &mut diagnostics)
+            .unwrap_or(resolved_config.format_scripts);
+
+    // Parse formatCss
+    resolved_config.format_css = get_nullable_value(&mut config, "formatCss", &mut diagnostics)
+        .unwrap_or(resolved_config.format_css);
+
+    // Parse formatPhp
+    resolved_config.format_php = get_nullable_value(&mut config, "formatPhp", &mut diagnostics)
+        .unwrap_or(resolved_config.format_php);
+
+    // Parse formatCSharp
+    resolved_config.format_csharp =
+        get_nullable_value(&mut config, "formatCSharp", &mut diagnostics)
+            .unwrap_or(resolved_config.format_csharp);
+
+    // Parse formatKotlin
+    resolved_config.format_kotlin =
+        get_nullable_value(&mut config, "formatKotlin", &mut diagnostics)
+            .unwrap_or(resolved_config.format_kotlin);
+
+    // Parse kotlinFunctions
+    if let Some(mut functions) = get_nullable_vec(&mut config, "kotlinFunctions", &mut diagnostics)
+    {
+        if !functions.is_empty() {
+            check_names(
+                &mut functions,
+                "kotlinFunctions",
+                is_valid_function_name,
+                &mut diagnostics,
+            );
+        }
+        resolved_config.kotlin_functions = functions;
     }
 
-    #[test]
-    fn test_resolve_config_with_custom_values() {
-        use dprint_core::configuration::ConfigKeyValue;
+    // Parse formatPython
+    resolved_config.format_python =
+        get_nullable_value(&mut config, "formatPython", &mut diagnostics)
+            .unwrap_or(resolved_config.format_python);
 
-        let mut config_map = ConfigKeyMap::new();
-        config_map.insert("enabled".to_string(), ConfigKeyValue::Bool(false));
-        config_map.insert(
-            "tailwindConfig".to_string(),
-            ConfigKeyValue::String("./tailwind.config.js".to_string()),
-        );
-        config_map.insert(
-            "tailwindFunctions".to_string(),
-            ConfigKeyValue::Array(vec![ConfigKeyValue::String("cn".to_string())]),
+    // Parse pythonClassNames
+    if let Some(mut names) = get_nullable_vec(&mut config, "pythonClassNames", &mut diagnostics) {
+        check_names(
+            &mut names,
+            "pythonClassNames",
+            is_valid_attribute_name,
+            &mut diagnostics,
         );
+        resolved_config.python_class_names = names;
+    }
 
-        let global_config = GlobalConfiguration::default();
-        let result = resolve_config(config_map, &global_config);
+    // Parse formatNotebooks
+    resolved_config.format_notebooks =
+        get_nullable_value(&mut config, "formatNotebooks", &mut diagnostics)
+            .unwrap_or(resolved_config.format_notebooks);
 
-        assert!(!result.config.enabled);
-        assert_eq!(
-            result.config.tailwind_config,
-            Some("./tailwind.config.js".to_string())
-        );
-        assert_eq!(result.config.tailwind_functions, vec!["cn"]);
-        assert!(result.diagnostics.is_empty());
+    // Parse formatXml
+    resolved_config.format_xml = get_nullable_value(&mut config, "formatXml", &mut diagnostics)
+        .unwrap_or(resolved_config.format_xml);
+
+    // Parse markdownCodeBlocks. Markdown extensions map to the Markdown
+    // format unless `formatAs` says otherwise, which also claims them
+    resolved_config.markdown_code_blocks =
+        get_nullable_value(&mut config, "markdownCodeBlocks", &mut diagnostics)
+            .unwrap_or(resolved_config.markdown_code_blocks);
+    if resolved_config.markdown_code_blocks {
+        for extension in ["md", "markdown", "mdx"] {
+            resolved_config
+                .format_as
+                .entry(extension.to_string())
+                .or_insert(FileFormat::Markdown);
+        }
     }
 
-    #[test]
-    fn test_resolve_config_with_all_custom_values() {
-        use dprint_core::configuration::ConfigKeyValue;
+    // Parse extractFromHtmlStrings
+    resolved_config.extract_from_html_strings =
+        get_nullable_value(&mut config, "extractFromHtmlStrings", &mut diagnostics)
+            .unwrap_or(resolved_config.extract_from_html_strings);
 
-        let mut config_map = ConfigKeyMap::new();
+    // Parse postProcess
+    resolved_config.post_process = get_nullable_value(&mut config, "postProcess", &mut diagnostics)
+        .unwrap_or(resolved_config.post_process);
+
+    // Parse verbose
+    resolved_config.verbose = get_nullable_value(&mut config, "verbose", &mut diagnostics)
+        .unwrap_or(resolved_config.verbose);
+
+    // Parse safelist. An empty list is a common default, so it isn't
+    // reported
+    if let Some(mut safelist) = get_nullable_vec(&mut config, "safelist", &mut diagnostics) {
+        if !safelist.is_empty() {
+            check_names(
+                &mut safelist,
+                "safelist",
+                is_valid_safelist_entry,
+                &mut diagnostics,
+            );
+        }
+        resolved_config.safelist = safelist;
+    }
+
+    // Parse ignore
+    if let Some(ignore) = get_nullable_vec(&mut config, "ignore", &mut diagnostics) {
+        resolved_config.ignore = ignore;
+    }
+
+    // Parse skipTestFiles
+    resolved_config.skip_test_files =
+        get_nullable_value(&mut config, "skipTestFiles", &mut diagnostics)
+            .unwrap_or(resolved_config.skip_test_files);
+
+    // Parse elementFactories. An empty list turns factory calls off, so it
+    // isn't reported
+    if let Some(mut factories) = get_nullable_vec(&mut config, "elementFactories", &mut diagnostics)
+    {
+        if !factories.is_empty() {
+            check_names(
+                &mut factories,
+                "elementFactories",
+                is_valid_function_name,
+                &mut diagnostics,
+            );
+        }
+        resolved_config.element_factories = factories;
+    }
+
+    // Parse classBuilders. An empty list turns builder calls off, so it
+    // isn't reported
+    if let Some(mut builders) = get_nullable_vec(&mut config, "classBuilders", &mut diagnostics) {
+        if !builders.is_empty() {
+            check_names(
+                &mut builders,
+                "classBuilders",
+                is_valid_function_name,
+                &mut diagnostics,
+            );
+        }
+        resolved_config.class_builders = builders;
+    }
+
+    // Parse sortComponentClasses
+    resolved_config.sort_component_classes =
+        get_nullable_value(&mut config, "sortComponentClasses", &mut diagnostics)
+            .unwrap_or(resolved_config.sort_component_classes);
+
+    // Parse attributify
+    resolved_config.attributify = get_nullable_value(&mut config, "attributify", &mut diagnostics)
+        .unwrap_or(resolved_config.attributify);
+
+    // Parse sortObjectProperties
+    resolved_config.sort_object_properties =
+        get_nullable_value(&mut config, "sortObjectProperties", &mut diagnostics)
+            .unwrap_or(resolved_config.sort_object_properties);
+
+    // Parse jsonClassProperties. The list is opt-in, so an empty one isn't
+    // reported
+    if let Some(mut properties) =
+        get_nullable_vec(&mut config, "jsonClassProperties", &mut diagnostics)
+    {
+        if !properties.is_empty() {
+            check_names(
+                &mut properties,
+                "jsonClassProperties",
+                |name| !name.is_empty(),
+                &mut diagnostics,
+            );
+        }
+        resolved_config.json_class_properties = properties;
+    }
+
+    // Parse fallbackExtraction
+    resolved_config.fallback_extraction =
+        get_nullable_value(&mut config, "fallbackExtraction", &mut diagnostics)
+            .unwrap_or(resolved_config.fallback_extraction);
+
+    // Parse verifyOutput
+    resolved_config.verify_output =
+        get_nullable_value(&mut config, "verifyOutput", &mut diagnostics)
+            .unwrap_or(resolved_config.verify_output);
+
+    // Parse webComponents
+    resolved_config.web_components =
+        get_nullable_value(&mut config, "webComponents", &mut diagnostics)
+            .unwrap_or(resolved_config.web_components);
+
+    // Parse quoteStyle
+    resolved_config.quote_style = get_nullable_value(&mut config, "quoteStyle", &mut diagnostics)
+        .unwrap_or(resolved_config.quote_style);
+
+    // Parse whitespace
+    resolved_config.whitespace = get_nullable_value(&mut config, "whitespace", &mut diagnostics)
+        .unwrap_or(resolved_config.whitespace);
+
+    // Parse lossyUtf8
+    resolved_config.lossy_utf8 = get_nullable_value(&mut config, "lossyUtf8", &mut diagnostics)
+        .unwrap_or(resolved_config.lossy_utf8);
+
+    // Parse placeholderPosition
+    resolved_config.placeholder_position =
+        get_nullable_value(&mut config, "placeholderPosition", &mut diagnostics)
+            .unwrap_or(resolved_config.placeholder_position);
+
+    // Parse importantAffectsOrder
+    resolved_config.important_affects_order =
+        get_nullable_value(&mut config, "importantAffectsOrder", &mut diagnostics)
+            .unwrap_or(resolved_config.important_affects_order);
+
+    // Parse wrapClasses
+    resolved_config.wrap_classes = get_wrap_classes(&mut config, global_config, &mut diagnostics);
+
+    // Parse groupSeparator
+    if let Some(separator) =
+        get_nullable_value::<String>(&mut config, "groupSeparator", &mut diagnostics)
+    {
+        if is_valid_group_separator(&separator) {
+            resolved_config.group_separator = Some(separator);
+        } else {
+            diagnostics.push(ConfigurationDiagnostic {
+                property_name: "groupSeparator".to_string(),
+                message: format!(
+                    "'{}' can't separate class groups; expected one token without whitespace, quotes or backslashes",
+                    separator
+                ),
+            });
+        }
+    }
+
+    // Parse sortWithinGroupsOnly
+    resolved_config.sort_within_groups_only =
+        get_nullable_value(&mut config, "sortWithinGroupsOnly", &mut diagnostics)
+            .unwrap_or(resolved_config.sort_within_groups_only);
+
+    // Parse canonicalize
+    resolved_config.canonicalize =
+        get_nullable_value(&mut config, "canonicalize", &mut diagnostics)
+            .unwrap_or(resolved_config.canonicalize);
+
+    // Parse mergeShorthands
+    resolved_config.merge_shorthands =
+        get_nullable_value(&mut config, "mergeShorthands", &mut diagnostics)
+            .unwrap_or(resolved_config.merge_shorthands);
+
+    // Parse maxClassLength and maxVariants
+    resolved_config.max_class_length =
+        get_positive_number(&mut config, "maxClassLength", &mut diagnostics)
+            .unwrap_or(resolved_config.max_class_length);
+    resolved_config.max_variants =
+        get_positive_number(&mut config, "maxVariants", &mut diagnostics)
+            .unwrap_or(resolved_config.max_variants);
+
+    // Parse deferExtensions. Extensions are compared without a leading dot
+    // and case-insensitively
+    if let Some(extensions) = get_nullable_vec(&mut config, "deferExtensions", &mut diagnostics) {
+        resolved_config.defer_extensions = extensions
+            .iter()
+            .map(|extension| extension.trim_start_matches('.').to_lowercase())
+            .collect();
+    }
+
+    // Check for unknown properties
+    diagnostics.extend(get_unknown_property_diagnostics(config));
+
+    let file_extensions = resolved_config.file_extensions();
+
+    PluginResolveConfigurationResult {
+        config: resolved_config,
+        diagnostics,
+        file_matching: FileMatchingInfo {
+            file_extensions,
+            file_names: vec![],
+        },
+    }
+}
+
+/// Check a configured name list, reporting an empty list and removing
+/// invalid and duplicate names with a diagnostic for each
+///
+/// The diagnostics are non-fatal: the rest of the list is still used.
+fn check_names(
+    names: &mut Vec<String>,
+    key: &str,
+    is_valid: fn(&str) -> bool,
+    diagnostics: &mut Vec<ConfigurationDiagnostic>,
+) {
+    if names.is_empty() {
+        diagnostics.push(ConfigurationDiagnostic {
+            property_name: key.to_string(),
+            message: format!("'{}' is empty, so nothing is matched by it", key),
+        });
+        return;
+    }
+
+    let mut seen = Vec::with_capacity(names.len());
+    names.retain(|name| {
+        if !is_valid(name) {
+            diagnostics.push(ConfigurationDiagnostic {
+                property_name: key.to_string(),
+                message: format!("'{}' is not a valid name and is ignored", name),
+            });
+            return false;
+        }
+        if seen.contains(name) {
+            diagnostics.push(ConfigurationDiagnostic {
+                property_name: key.to_string(),
+                message: format!(
+                    "'{}' is listed more than once; duplicates are removed",
+                    name
+                ),
+            });
+            return false;
+        }
+        seen.push(name.clone());
+        true
+    });
+}
+
+/// Function and template names are JS identifiers, optionally dotted as in
+/// `utils.cn`
+fn is_valid_function_name(name: &str) -> bool {
+    name.split('.').all(|part| {
+        part.chars().next().is_some_and(|c| !c.is_ascii_digit())
+            && part
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+    })
+}
+
+/// Safelist entries are single class names, optionally with `*` wildcards
+fn is_valid_safelist_entry(name: &str) -> bool {
+    !name.is_empty() && !name.chars().any(char::is_whitespace)
+}
+
+/// Element names start with a letter and hold letters, digits, `-`, `_`,
+/// `.` and `:`, as in `markdown-block` or `x:wrapper`
+fn is_valid_element_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
+
+/// Group separators are tokens of their own that can go in any quoted
+/// value, so they hold no whitespace, quotes or backslashes
+fn is_valid_group_separator(separator: &str) -> bool {
+    !separator.is_empty()
+        && !separator
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '`' | '\\'))
+}
+
+/// Attribute names may include framework syntax such as `:class`,
+/// `class:list` or `[ngClass]`, but no whitespace, quotes, `=`, `<`, `>`,
+/// `/` or regex metacharacters
+fn is_valid_attribute_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.chars().any(|c| {
+            c.is_whitespace()
+                || matches!(
+                    c,
+                    '"' | '\''
+                        | '='
+                        | '<'
+                        | '>'
+                        | '/'
+                        | '\\'
+                        | '^'
+                        | '$'
+                        | '|'
+                        | '?'
+                        | '+'
+                        | '*'
+                        | '{'
+                        | '}'
+                )
+        })
+}
+
+/// Attributes holding space-separated tokens that are not classes, such as
+/// ID references, link types or URLs
+const NON_CLASS_ATTRIBUTES: &[&str] = &[
+    "accept",
+    "accesskey",
+    "aria-controls",
+    "aria-describedby",
+    "aria-details",
+    "aria-flowto",
+    "aria-keyshortcuts",
+    "aria-labelledby",
+    "aria-owns",
+    "autocomplete",
+    "blocking",
+    "for",
+    "headers",
+    "htmlfor",
+    "itemprop",
+    "itemref",
+    "itemtype",
+    "ping",
+    "rel",
+    "rev",
+    "sandbox",
+    "sizes",
+    "srcset",
+];
+
+/// Attributes of custom elements and shadow DOM that are never sorted, even
+/// with `allowNonClassAttributes`: `is` names the custom element a built-in
+/// element upgrades to, and `part`/`exportparts` hold the part names and
+/// mappings that `::part()` selectors of the host page match
+const NEVER_SORTED_ATTRIBUTES: &[&str] = &["exportparts", "is", "part"];
+
+/// Remove known non-class attributes from `attributes`, with a diagnostic
+/// for each one
+///
+/// Attributes that are never sorted are removed even if `allow_non_class`
+/// is set.
+fn remove_non_class_attributes(
+    attributes: &mut Vec<String>,
+    key: &str,
+    allow_non_class: bool,
+    diagnostics: &mut Vec<ConfigurationDiagnostic>,
+) {
+    attributes.retain(|attribute| {
+        let lowercase = attribute.to_ascii_lowercase();
+        let message = if NEVER_SORTED_ATTRIBUTES.contains(&lowercase.as_str()) {
+            format!(
+                "'{}' belongs to custom elements or shadow parts and is never sorted",
+                attribute
+            )
+        } else if !allow_non_class && NON_CLASS_ATTRIBUTES.contains(&lowercase.as_str()) {
+            format!(
+                "'{}' holds tokens that are not classes and is ignored; set 'allowNonClassAttributes' to true to format it anyway",
+                attribute
+            )
+        } else {
+            return true;
+        };
+        diagnostics.push(ConfigurationDiagnostic {
+            property_name: key.to_string(),
+            message,
+        });
+        false
+    });
+}
+
+/// Parse `formatAs`, an object mapping extensions to format names
+fn get_format_as(
+    config: &mut ConfigKeyMap,
+    diagnostics: &mut Vec<ConfigurationDiagnostic>,
+) -> Option<BTreeMap<String, FileFormat>> {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let value = config.swap_remove("formatAs")?;
+    let ConfigKeyValue::Object(entries) = value else {
+        diagnostics.push(ConfigurationDiagnostic {
+            property_name: "formatAs".to_string(),
+            message: "Expected object for 'formatAs'".to_string(),
+        });
+        return None;
+    };
+
+    let mut format_as = BTreeMap::new();
+    for (extension, format) in entries {
+        let extension = extension.trim_start_matches('.').to_lowercase();
+        let format = match format {
+            ConfigKeyValue::String(name) => FileFormat::from_name(&name.to_lowercase()),
+            _ => None,
+        };
+        match format {
+            Some(format) if !extension.is_empty() => {
+                format_as.insert(extension, format);
+            }
+            _ => diagnostics.push(ConfigurationDiagnostic {
+                property_name: "formatAs".to_string(),
+                message: format!(
+                    "Expected one of {} for extension '{}'",
+                    FileFormat::names().collect::<Vec<_>>().join(", "),
+                    extension
+                ),
+            }),
+        }
+    }
+
+    Some(format_as)
+}
+
+/// Parse `customFormats`, a list of objects with `extensions`,
+/// `skipBlocks` delimiter pairs and `skipScriptAndStyle`
+///
+/// Bad entries are reported and left out, and a format without any valid
+/// extension is dropped.
+fn get_custom_formats(
+    config: &mut ConfigKeyMap,
+    diagnostics: &mut Vec<ConfigurationDiagnostic>,
+) -> Option<Vec<CustomFormat>> {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let mut report = |message: String| {
+        diagnostics.push(ConfigurationDiagnostic {
+            property_name: "customFormats".to_string(),
+            message,
+        })
+    };
+
+    let value = config.swap_remove("customFormats")?;
+    let ConfigKeyValue::Array(entries) = value else {
+        report("Expected array for 'customFormats'".to_string());
+        return None;
+    };
+
+    let mut custom_formats = Vec::new();
+    for (index, entry) in entries.into_iter().enumerate() {
+        let ConfigKeyValue::Object(entry) = entry else {
+            report(format!("Expected object for custom format {}", index));
+            continue;
+        };
+
+        let mut custom = CustomFormat::default();
+        for (key, value) in entry {
+            match (key.as_str(), value) {
+                ("extensions", ConfigKeyValue::Array(extensions)) => {
+                    for extension in extensions {
+                        match extension {
+                            ConfigKeyValue::String(extension)
+                                if !extension.trim_start_matches('.').is_empty() =>
+                            {
+                                let extension = extension.trim_start_matches('.').to_lowercase();
+                                if !custom.extensions.contains(&extension) {
+                                    custom.extensions.push(extension);
+                                }
+                            }
+                            _ => report(format!(
+                                "Expected extension names in 'extensions' of custom format {}",
+                                index
+                            )),
+                        }
+                    }
+                }
+                ("skipBlocks", ConfigKeyValue::Array(blocks)) => {
+                    for block in blocks {
+                        let pair = match block {
+                            ConfigKeyValue::Array(pair) => match pair.as_slice() {
+                                [ConfigKeyValue::String(open), ConfigKeyValue::String(close)]
+                                    if !open.is_empty() && !close.is_empty() =>
+                                {
+                                    Some((open.clone(), close.clone()))
+                                }
+                                _ => None,
+                            },
+                            _ => None,
+                        };
+                        match pair {
+                            Some(pair) => custom.skip_blocks.push(pair),
+                            None => report(format!(
+                                "Expected pairs of non-empty delimiters such as [\"{{%\", \"%}}\"] in 'skipBlocks' of custom format {}",
+                                index
+                            )),
+                        }
+                    }
+                }
+                ("skipScriptAndStyle", ConfigKeyValue::Bool(skip)) => {
+                    custom.skip_script_and_style = skip;
+                }
+                ("extensions" | "skipBlocks" | "skipScriptAndStyle", _) => report(format!(
+                    "Unexpected value for '{}' of custom format {}",
+                    key, index
+                )),
+                _ => report(format!(
+                    "Unknown property '{}' in custom format {}",
+                    key, index
+                )),
+            }
+        }
+
+        if custom.extensions.is_empty() {
+            report(format!(
+                "Custom format {} has no extensions and is ignored",
+                index
+            ));
+            continue;
+        }
+        custom_formats.push(custom);
+    }
+
+    Some(custom_formats)
+}
+
+/// Parse `wrapClasses`: `true` to wrap with dprint's line width and
+/// indentation, or an object overriding `maxLineLength` and `indent`
+fn get_wrap_classes(
+    config: &mut ConfigKeyMap,
+    global_config: &GlobalConfiguration,
+    diagnostics: &mut Vec<ConfigurationDiagnostic>,
+) -> Option<WrapClasses> {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let mut wrap = WrapClasses::from_global(global_config);
+    let entries = match config.swap_remove("wrapClasses")? {
+        ConfigKeyValue::Bool(true) => return Some(wrap),
+        ConfigKeyValue::Bool(false) | ConfigKeyValue::Null => return None,
+        ConfigKeyValue::Object(entries) => entries,
+        _ => {
+            diagnostics.push(ConfigurationDiagnostic {
+                property_name: "wrapClasses".to_string(),
+                message: "Expected boolean or object for 'wrapClasses'".to_string(),
+            });
+            return None;
+        }
+    };
+
+    for (key, value) in entries {
+        let target = match key.as_str() {
+            "maxLineLength" => &mut wrap.max_line_length,
+            "indent" => &mut wrap.indent,
+            _ => {
+                diagnostics.push(ConfigurationDiagnostic {
+                    property_name: "wrapClasses".to_string(),
+                    message: format!("Unknown property '{}' in 'wrapClasses'", key),
+                });
+                continue;
+            }
+        };
+        match value {
+            ConfigKeyValue::Number(number) if number > 0 => *target = number as usize,
+            _ => diagnostics.push(ConfigurationDiagnostic {
+                property_name: "wrapClasses".to_string(),
+                message: format!("Expected a positive number for '{}' in 'wrapClasses'", key),
+            }),
+        }
+    }
+
+    Some(wrap)
+}
+
+/// Parse a number option that must be at least 1
+fn get_positive_number(
+    config: &mut ConfigKeyMap,
+    key: &str,
+    diagnostics: &mut Vec<ConfigurationDiagnostic>,
+) -> Option<usize> {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    match config.swap_remove(key)? {
+        ConfigKeyValue::Number(number) if number > 0 => Some(number as usize),
+        ConfigKeyValue::Null => None,
+        _ => {
+            diagnostics.push(ConfigurationDiagnostic {
+                property_name: key.to_string(),
+                message: format!("Expected a positive number for '{}'", key),
+            });
+            None
+        }
+    }
+}
+
+#[allow(dead_code)]
+fn get_nullable_vec(
+    config: &mut ConfigKeyMap,
+    key: &str,
+    diagnostics: &mut Vec<ConfigurationDiagnostic>,
+) -> Option<Vec<String>> {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    if let Some(value) = config.swap_remove(key) {
+        match value {
+            ConfigKeyValue::Array(arr) => {
+                let result: Option<Vec<String>> = arr
+                    .iter()
+                    .map(|v| match v {
+                        ConfigKeyValue::String(s) => Some(s.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                if result.is_none() {
+                    diagnostics.push(ConfigurationDiagnostic {
+                        property_name: key.to_string(),
+                        message: format!("Expected array of strings for '{}'", key),
+                    });
+                }
+                result
+            }
+            _ => {
+                diagnostics.push(ConfigurationDiagnostic {
+                    property_name: key.to_string(),
+                    message: format!("Expected array for '{}'", key),
+                });
+                None
+            }
+        }
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        let config = Configuration::default();
+        assert!(config.enabled);
+        assert_eq!(config.tailwind_functions.len(), 9);
+        assert_eq!(config.tailwind_attributes.len(), 2);
+        assert_eq!(config.tailwind_templates, vec!["tw", "css"]);
+    }
+
+    #[test]
+    fn test_resolve_config_with_custom_values() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("enabled".to_string(), ConfigKeyValue::Bool(false));
+        config_map.insert(
+            "tailwindConfig".to_string(),
+            ConfigKeyValue::String("./tailwind.config.js".to_string()),
+        );
+        config_map.insert(
+            "tailwindFunctions".to_string(),
+            ConfigKeyValue::Array(vec![ConfigKeyValue::String("cn".to_string())]),
+        );
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(config_map, &global_config);
+
+        assert!(!result.config.enabled);
+        assert_eq!(
+            result.config.tailwind_config,
+            Some("./tailwind.config.js".to_string())
+        );
+        assert_eq!(result.config.tailwind_functions, vec!["cn"]);
+        assert_eq!(result.config.tailwind_templates, vec!["tw", "css"]);
+        assert!(result.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_config_with_all_custom_values() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let mut config_map = ConfigKeyMap::new();
         config_map.insert("enabled".to_string(), ConfigKeyValue::Bool(true));
         config_map.insert(
             "tailwindConfig".to_string(),
@@ -236,7 +1682,7 @@ mod tests {
         // enabled should use default value when type is wrong
         assert!(result.config.enabled);
         // tailwindFunctions should use default value and produce diagnostic
-        assert_eq!(result.config.tailwind_functions.len(), 5);
+        assert_eq!(result.config.tailwind_functions.len(), 9);
         assert!(!result.diagnostics.is_empty());
         assert!(result
             .diagnostics
@@ -290,7 +1736,7 @@ mod tests {
         let result = resolve_config(config_map, &global_config);
 
         // Should use default value and produce diagnostic
-        assert_eq!(result.config.tailwind_functions.len(), 5);
+        assert_eq!(result.config.tailwind_functions.len(), 9);
         assert!(!result.diagnostics.is_empty());
         assert!(result.diagnostics.iter().any(|d| {
             d.property_name == "tailwindFunctions" && d.message.contains("array of strings")
@@ -311,9 +1757,13 @@ mod tests {
             vec![
                 "classnames".to_string(),
                 "clsx".to_string(),
+                "cn".to_string(),
                 "ctl".to_string(),
                 "cva".to_string(),
+                "cx".to_string(),
                 "tw".to_string(),
+                "twJoin".to_string(),
+                "twMerge".to_string(),
             ]
         );
         assert_eq!(
@@ -323,6 +1773,997 @@ mod tests {
         assert!(result.diagnostics.is_empty());
     }
 
+    #[test]
+    fn test_config_updates_add_new_default_functions() {
+        let functions = |names: &[&str]| {
+            let mut config = ConfigKeyMap::new();
+            config.insert(
+                "tailwindFunctions".to_string(),
+                ConfigKeyValue::Array(
+                    names
+                        .iter()
+                        .map(|name| ConfigKeyValue::String(name.to_string()))
+                        .collect(),
+                ),
+            );
+            config
+        };
+        // `ConfigChange` has no `PartialEq`, so compare the appended names
+        let added = |config: &ConfigKeyMap| -> Vec<(usize, String)> {
+            config_updates(config)
+                .into_iter()
+                .map(|change| match (change.path.as_slice(), change.kind) {
+                    (
+                        [ConfigChangePathItem::String(key), ConfigChangePathItem::Number(index)],
+                        ConfigChangeKind::Add(ConfigKeyValue::String(name)),
+                    ) if key == "tailwindFunctions" => (*index, name),
+                    (path, kind) => panic!("unexpected change {path:?}: {kind:?}"),
+                })
+                .collect()
+        };
+
+        // Lists extending the first defaults get the names added since
+        let config = functions(&["classnames", "clsx", "ctl", "cva", "tw", "cn", "myFn"]);
+        assert_eq!(
+            added(&config),
+            [
+                (7, "cx".to_string()),
+                (8, "twJoin".to_string()),
+                (9, "twMerge".to_string())
+            ]
+        );
+
+        // Up-to-date, custom and missing lists are left alone
+        let current = functions(&[
+            "classnames",
+            "clsx",
+            "cn",
+            "ctl",
+            "cva",
+            "cx",
+            "tw",
+            "twJoin",
+            "twMerge",
+        ]);
+        assert!(config_updates(&current).is_empty());
+        assert!(config_updates(&functions(&["clsx", "cva"])).is_empty());
+        assert!(config_updates(&ConfigKeyMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_default_attributes_per_format() {
+        let config = Configuration::default();
+
+        assert_eq!(config.attributes_for(Some(FileFormat::Html)), ["class"]);
+        assert_eq!(
+            config.attributes_for(Some(FileFormat::Tsx)),
+            ["className", "class"]
+        );
+        assert_eq!(config.attributes_for(Some(FileFormat::Vue)), ["class"]);
+        assert_eq!(
+            config.attributes_for(Some(FileFormat::Astro)),
+            ["class", "className", "class:list"]
+        );
+        assert_eq!(
+            config.attributes_for(Some(FileFormat::Mjml)),
+            ["class", "css-class"]
+        );
+        assert!(config.attributes_for(Some(FileFormat::Xml)).is_empty());
+        assert_eq!(config.attributes_for(None), ["class", "className"]);
+    }
+
+    #[test]
+    fn test_resolve_config_attributes_per_format() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "tailwindAttributes".to_string(),
+            ConfigKeyValue::Array(vec![ConfigKeyValue::String("tw".to_string())]),
+        );
+        config_map.insert(
+            "jsx.tailwindAttributes".to_string(),
+            ConfigKeyValue::Array(vec![ConfigKeyValue::String("className".to_string())]),
+        );
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(config_map, &global_config);
+
+        // The explicit list replaces every per-format default...
+        assert_eq!(result.config.attributes_for(Some(FileFormat::Html)), ["tw"]);
+        assert_eq!(result.config.attributes_for(Some(FileFormat::Vue)), ["tw"]);
+        // ...unless a format has its own list
+        assert_eq!(
+            result.config.attributes_for(Some(FileFormat::Tsx)),
+            ["className"]
+        );
+        assert!(result.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_config_rejects_non_class_attributes() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "tailwindAttributes".to_string(),
+            ConfigKeyValue::Array(vec![
+                ConfigKeyValue::String("class".to_string()),
+                ConfigKeyValue::String("aria-labelledby".to_string()),
+            ]),
+        );
+        config_map.insert(
+            "html.tailwindAttributes".to_string(),
+            ConfigKeyValue::Array(vec![ConfigKeyValue::String("rel".to_string())]),
+        );
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(config_map, &global_config);
+
+        assert_eq!(result.config.tailwind_attributes, vec!["class"]);
+        assert_eq!(result.config.format_attributes.html, Some(vec![]));
+        assert_eq!(result.diagnostics.len(), 2);
+        assert!(result
+            .diagnostics
+            .iter()
+            .any(|d| d.property_name == "html.tailwindAttributes" && d.message.contains("'rel'")));
+    }
+
+    #[test]
+    fn test_resolve_config_allows_non_class_attributes() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "tailwindAttributes".to_string(),
+            ConfigKeyValue::Array(vec![ConfigKeyValue::String("headers".to_string())]),
+        );
+        config_map.insert(
+            "allowNonClassAttributes".to_string(),
+            ConfigKeyValue::Bool(true),
+        );
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(config_map, &global_config);
+
+        assert_eq!(result.config.tailwind_attributes, vec!["headers"]);
+        assert!(result.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_config_never_sorts_part_attributes() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "tailwindAttributes".to_string(),
+            ConfigKeyValue::Array(vec![
+                ConfigKeyValue::String("class".to_string()),
+                ConfigKeyValue::String("part".to_string()),
+                ConfigKeyValue::String("exportParts".to_string()),
+            ]),
+        );
+        config_map.insert(
+            "html.tailwindAttributes".to_string(),
+            ConfigKeyValue::Array(vec![
+                ConfigKeyValue::String("class".to_string()),
+                ConfigKeyValue::String("is".to_string()),
+            ]),
+        );
+        config_map.insert(
+            "allowNonClassAttributes".to_string(),
+            ConfigKeyValue::Bool(true),
+        );
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(config_map, &global_config);
+
+        assert_eq!(result.config.tailwind_attributes, vec!["class"]);
+        assert_eq!(
+            result.config.format_attributes.html,
+            Some(vec!["class".to_string()])
+        );
+        assert_eq!(result.diagnostics.len(), 3);
+        assert!(result
+            .diagnostics
+            .iter()
+            .all(|d| d.message.contains("never sorted")));
+    }
+
+    #[test]
+    fn test_resolve_config_suspicious_names() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "tailwindFunctions".to_string(),
+            ConfigKeyValue::Array(vec![
+                ConfigKeyValue::String("cn".to_string()),
+                ConfigKeyValue::String("clsx".to_string()),
+                ConfigKeyValue::String("cn".to_string()),
+                ConfigKeyValue::String("my fn".to_string()),
+                ConfigKeyValue::String("tw.*".to_string()),
+                ConfigKeyValue::String("utils.cn".to_string()),
+            ]),
+        );
+        config_map.insert(
+            "tailwindAttributes".to_string(),
+            ConfigKeyValue::Array(vec![
+                ConfigKeyValue::String(":class".to_string()),
+                ConfigKeyValue::String("[ngClass]".to_string()),
+                ConfigKeyValue::String("class=".to_string()),
+            ]),
+        );
+        config_map.insert(
+            "tailwindTemplates".to_string(),
+            ConfigKeyValue::Array(vec![]),
+        );
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(config_map, &global_config);
+
+        assert_eq!(
+            result.config.tailwind_functions,
+            vec!["cn", "clsx", "utils.cn"]
+        );
+        assert_eq!(
+            result.config.tailwind_attributes,
+            vec![":class", "[ngClass]"]
+        );
+        assert!(result.config.tailwind_templates.is_empty());
+
+        let messages = |key: &str| {
+            result
+                .diagnostics
+                .iter()
+                .filter(|d| d.property_name == key)
+                .count()
+        };
+        assert_eq!(messages("tailwindFunctions"), 3);
+        assert_eq!(messages("tailwindAttributes"), 1);
+        assert_eq!(messages("tailwindTemplates"), 1);
+    }
+
+    #[test]
+    fn test_resolve_config_format_as() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let mut format_as = ConfigKeyMap::new();
+        format_as.insert(
+            "twig".to_string(),
+            ConfigKeyValue::String("html".to_string()),
+        );
+        format_as.insert(
+            ".blade.php".to_string(),
+            ConfigKeyValue::String("html".to_string()),
+        );
+        format_as.insert(
+            "hbs".to_string(),
+            ConfigKeyValue::String("handlebars".to_string()),
+        );
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("formatAs".to_string(), ConfigKeyValue::Object(format_as));
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(config_map, &global_config);
+
+        assert_eq!(result.config.format_as.get("twig"), Some(&FileFormat::Html));
+        assert_eq!(
+            result.config.format_as.get("blade.php"),
+            Some(&FileFormat::Html)
+        );
+        assert!(!result.config.format_as.contains_key("hbs"));
+        assert_eq!(result.diagnostics.len(), 1);
+        for name in ["mjml", "php", "markdown", "yaml"] {
+            assert!(result.diagnostics[0].message.contains(name));
+        }
+
+        let extensions = &result.file_matching.file_extensions;
+        assert!(extensions.contains(&"twig".to_string()));
+        assert!(extensions.contains(&"php".to_string()));
+    }
+
+    #[test]
+    fn test_resolve_config_format_scripts() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(ConfigKeyMap::new(), &global_config);
+        assert!(!result
+            .file_matching
+            .file_extensions
+            .contains(&"js".to_string()));
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("formatScripts".to_string(), ConfigKeyValue::Bool(true));
+        let result = resolve_config(config_map, &global_config);
+
+        assert!(result.config.format_scripts);
+        for extension in ["js", "mjs", "cjs", "ts", "mts", "cts"] {
+            assert!(result
+                .file_matching
+                .file_extensions
+                .contains(&extension.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_resolve_config_format_php() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(ConfigKeyMap::new(), &global_config);
+        assert!(!result
+            .file_matching
+            .file_extensions
+            .contains(&"php".to_string()));
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("formatPhp".to_string(), ConfigKeyValue::Bool(true));
+        let result = resolve_config(config_map, &global_config);
+
+        assert!(result.config.format_php);
+        assert!(result
+            .file_matching
+            .file_extensions
+            .contains(&"php".to_string()));
+    }
+
+    #[test]
+    fn test_resolve_config_format_xml() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("formatXml".to_string(), ConfigKeyValue::Bool(true));
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(config_map, &global_config);
+
+        assert!(result.config.format_xml);
+        for extension in ["xml", "xsl", "xslt", "svg"] {
+            assert!(result
+                .file_matching
+                .file_extensions
+                .contains(&extension.to_string()));
+        }
+        assert!(result.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_config_markdown_code_blocks() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(ConfigKeyMap::new(), &global_config);
+        assert!(!result.config.markdown_code_blocks);
+        assert!(!result
+            .file_matching
+            .file_extensions
+            .contains(&"md".to_string()));
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("markdownCodeBlocks".to_string(), ConfigKeyValue::Bool(true));
+        let mut format_as = ConfigKeyMap::new();
+        format_as.insert(
+            "markdown".to_string(),
+            ConfigKeyValue::String("html".to_string()),
+        );
+        config_map.insert("formatAs".to_string(), ConfigKeyValue::Object(format_as));
+        let result = resolve_config(config_map, &global_config);
+
+        assert!(result.config.markdown_code_blocks);
+        assert_eq!(result.config.format_as["md"], FileFormat::Markdown);
+        assert_eq!(result.config.format_as["mdx"], FileFormat::Markdown);
+        assert_eq!(result.config.format_as["markdown"], FileFormat::Html);
+        for extension in ["md", "markdown", "mdx"] {
+            assert!(result
+                .file_matching
+                .file_extensions
+                .contains(&extension.to_string()));
+        }
+        assert!(result.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_config_placeholder_position() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(ConfigKeyMap::new(), &global_config);
+        assert_eq!(result.config.placeholder_position, PlaceholderPosition::End);
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "placeholderPosition".to_string(),
+            ConfigKeyValue::String("preserve".to_string()),
+        );
+        let result = resolve_config(config_map, &global_config);
+        assert_eq!(
+            result.config.placeholder_position,
+            PlaceholderPosition::Preserve
+        );
+        assert!(result.diagnostics.is_empty());
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "placeholderPosition".to_string(),
+            ConfigKeyValue::String("middle".to_string()),
+        );
+        let result = resolve_config(config_map, &global_config);
+        assert_eq!(result.config.placeholder_position, PlaceholderPosition::End);
+        assert_eq!(result.diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_resolve_config_important_affects_order() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(ConfigKeyMap::new(), &global_config);
+        assert!(result.config.important_affects_order);
+        assert_eq!(result.config.sort_options(), SortOptions::default());
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "importantAffectsOrder".to_string(),
+            ConfigKeyValue::Bool(false),
+        );
+        let result = resolve_config(config_map, &global_config);
+        assert!(!result.config.important_affects_order);
+        assert!(!result.config.sort_options().important_affects_order);
+        assert!(result.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_config_wrap_classes() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let result = resolve_config(ConfigKeyMap::new(), &GlobalConfiguration::default());
+        assert_eq!(result.config.wrap_classes, None);
+
+        // `true` takes the global settings
+        let global_config = GlobalConfiguration {
+            line_width: Some(100),
+            indent_width: Some(4),
+            use_tabs: Some(true),
+            ..Default::default()
+        };
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("wrapClasses".to_string(), ConfigKeyValue::Bool(true));
+        let result = resolve_config(config_map, &global_config);
+        assert_eq!(
+            result.config.wrap_classes,
+            Some(WrapClasses {
+                max_line_length: 100,
+                indent: 4,
+                use_tabs: true,
+            })
+        );
+        assert!(result.diagnostics.is_empty());
+
+        // An object overrides them, reporting bad entries
+        let mut wrap = ConfigKeyMap::new();
+        wrap.insert("maxLineLength".to_string(), ConfigKeyValue::Number(80));
+        wrap.insert("indent".to_string(), ConfigKeyValue::Number(0));
+        wrap.insert("width".to_string(), ConfigKeyValue::Number(80));
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("wrapClasses".to_string(), ConfigKeyValue::Object(wrap));
+        let result = resolve_config(config_map, &GlobalConfiguration::default());
+        assert_eq!(
+            result.config.wrap_classes,
+            Some(WrapClasses {
+                max_line_length: 80,
+                indent: 2,
+                use_tabs: false,
+            })
+        );
+        assert_eq!(result.diagnostics.len(), 2);
+        assert!(result
+            .diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.property_name == "wrapClasses"));
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "wrapClasses".to_string(),
+            ConfigKeyValue::String("80".to_string()),
+        );
+        let result = resolve_config(config_map, &GlobalConfiguration::default());
+        assert_eq!(result.config.wrap_classes, None);
+        assert_eq!(result.diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_resolve_config_group_separator() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(ConfigKeyMap::new(), &global_config);
+        assert_eq!(result.config.group_separator, None);
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "groupSeparator".to_string(),
+            ConfigKeyValue::String("|".to_string()),
+        );
+        let result = resolve_config(config_map, &global_config);
+        assert_eq!(result.config.group_separator.as_deref(), Some("|"));
+        assert!(result.diagnostics.is_empty());
+
+        for separator in ["", "a b", "'|'", "\\"] {
+            let mut config_map = ConfigKeyMap::new();
+            config_map.insert(
+                "groupSeparator".to_string(),
+                ConfigKeyValue::String(separator.to_string()),
+            );
+            let result = resolve_config(config_map, &global_config);
+            assert_eq!(result.config.group_separator, None, "{separator}");
+            assert_eq!(result.diagnostics.len(), 1, "{separator}");
+            assert_eq!(result.diagnostics[0].property_name, "groupSeparator");
+        }
+    }
+
+    #[test]
+    fn test_resolve_config_attributify() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(ConfigKeyMap::new(), &global_config);
+        assert!(!result.config.attributify);
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("attributify".to_string(), ConfigKeyValue::Bool(true));
+        let result = resolve_config(config_map, &global_config);
+        assert!(result.config.attributify);
+        assert!(result.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_config_canonicalize() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(ConfigKeyMap::new(), &global_config);
+        assert!(!result.config.canonicalize);
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("canonicalize".to_string(), ConfigKeyValue::Bool(true));
+        let result = resolve_config(config_map, &global_config);
+        assert!(result.config.canonicalize);
+        assert!(result.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_config_merge_shorthands() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(ConfigKeyMap::new(), &global_config);
+        assert!(!result.config.merge_shorthands);
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("mergeShorthands".to_string(), ConfigKeyValue::Bool(true));
+        let result = resolve_config(config_map, &global_config);
+        assert!(result.config.merge_shorthands);
+        assert!(result.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_config_sort_within_groups_only() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(ConfigKeyMap::new(), &global_config);
+        assert!(!result.config.sort_within_groups_only);
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "sortWithinGroupsOnly".to_string(),
+            ConfigKeyValue::Bool(true),
+        );
+        let result = resolve_config(config_map, &global_config);
+        assert!(result.config.sort_within_groups_only);
+        assert!(result.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_config_custom_formats() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let string = |value: &str| ConfigKeyValue::String(value.to_string());
+        let mut njk = ConfigKeyMap::new();
+        njk.insert(
+            "extensions".to_string(),
+            ConfigKeyValue::Array(vec![string(".NJK"), string("njk"), string("twig")]),
+        );
+        njk.insert(
+            "skipBlocks".to_string(),
+            ConfigKeyValue::Array(vec![
+                ConfigKeyValue::Array(vec![string("{%"), string("%}")]),
+                ConfigKeyValue::Array(vec![string("{{")]),
+            ]),
+        );
+        njk.insert("skipScriptAndStyle".to_string(), ConfigKeyValue::Bool(true));
+        let mut unnamed = ConfigKeyMap::new();
+        unnamed.insert("skipBlocks".to_string(), ConfigKeyValue::Array(vec![]));
+        let mut format_as = ConfigKeyMap::new();
+        format_as.insert("twig".to_string(), string("html"));
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "customFormats".to_string(),
+            ConfigKeyValue::Array(vec![
+                ConfigKeyValue::Object(njk),
+                ConfigKeyValue::Object(unnamed),
+            ]),
+        );
+        config_map.insert("formatAs".to_string(), ConfigKeyValue::Object(format_as));
+        let result = resolve_config(config_map, &GlobalConfiguration::default());
+
+        assert_eq!(
+            result.config.custom_formats,
+            vec![CustomFormat {
+                extensions: vec!["njk".to_string(), "twig".to_string()],
+                skip_blocks: vec![("{%".to_string(), "%}".to_string())],
+                skip_script_and_style: true,
+            }]
+        );
+        // `formatAs` wins over a custom format
+        assert_eq!(result.config.format_as["njk"], FileFormat::Custom(0));
+        assert_eq!(result.config.format_as["twig"], FileFormat::Html);
+        assert!(result
+            .file_matching
+            .file_extensions
+            .contains(&"njk".to_string()));
+        // The one-sided block and the format without extensions
+        assert_eq!(result.diagnostics.len(), 2);
+        assert!(result
+            .diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.property_name == "customFormats"));
+    }
+
+    #[test]
+    fn test_resolve_config_exclude_elements() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(ConfigKeyMap::new(), &global_config);
+        assert!(result.config.exclude_elements.is_empty());
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "excludeElements".to_string(),
+            ConfigKeyValue::Array(
+                ["pre", "markdown-block", "<code>", "pre"]
+                    .iter()
+                    .map(|name| ConfigKeyValue::String(name.to_string()))
+                    .collect(),
+            ),
+        );
+        let result = resolve_config(config_map, &global_config);
+        assert_eq!(result.config.exclude_elements, ["pre", "markdown-block"]);
+        assert_eq!(result.diagnostics.len(), 2);
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("excludeElements".to_string(), ConfigKeyValue::Array(vec![]));
+        let result = resolve_config(config_map, &global_config);
+        assert!(result.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_wrap_classes() {
+        let wrap = WrapClasses {
+            max_line_length: 28,
+            indent: 2,
+            use_tabs: false,
+        };
+
+        // Fits, counting the closing quote
+        assert_eq!(wrap.wrap("  <p class=\"", "flex p-4 mt-2", "\n"), None);
+        assert_eq!(
+            wrap.wrap("  <p class=\"", "flex p-4 mt-2 w-8", "\n")
+                .as_deref(),
+            Some("flex p-4 mt-2\n    w-8")
+        );
+
+        // A class too long for any line gets a line of its own
+        assert_eq!(
+            wrap.wrap("<p class=\"", "flex grid-cols-[1fr_2fr_1fr] p-4", "\r\n")
+                .as_deref(),
+            Some("flex\r\n  grid-cols-[1fr_2fr_1fr]\r\n  p-4")
+        );
+
+        // Tabs count as a level of indentation
+        let wrap = WrapClasses {
+            use_tabs: true,
+            ..wrap
+        };
+        assert_eq!(
+            wrap.wrap("\t<p class=\"", "flex p-4 mt-2 w-8", "\n")
+                .as_deref(),
+            Some("flex p-4 mt-2\n\t\tw-8")
+        );
+    }
+
+    #[test]
+    fn test_resolve_config_class_limits() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(ConfigKeyMap::new(), &global_config);
+        assert_eq!(result.config.class_limits(), ClassLimits::default());
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("maxClassLength".to_string(), ConfigKeyValue::Number(200));
+        config_map.insert("maxVariants".to_string(), ConfigKeyValue::Number(4));
+        let result = resolve_config(config_map, &global_config);
+
+        assert_eq!(result.config.max_class_length, 200);
+        assert_eq!(result.config.max_variants, 4);
+        assert!(result.diagnostics.is_empty());
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("maxVariants".to_string(), ConfigKeyValue::Number(-1));
+        let result = resolve_config(config_map, &global_config);
+
+        assert_eq!(
+            result.config.max_variants,
+            ClassLimits::default().max_variants
+        );
+        assert_eq!(result.diagnostics.len(), 1);
+
+        // With a limit of 0 every class would be passed through unsorted
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("maxClassLength".to_string(), ConfigKeyValue::Number(0));
+        config_map.insert("maxVariants".to_string(), ConfigKeyValue::Number(0));
+        let result = resolve_config(config_map, &global_config);
+
+        assert_eq!(result.config.class_limits(), ClassLimits::default());
+        assert_eq!(result.diagnostics.len(), 2);
+        assert_eq!(result.diagnostics[0].property_name, "maxClassLength");
+    }
+
+    #[test]
+    fn test_resolve_config_format_css() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(ConfigKeyMap::new(), &global_config);
+        assert!(!result
+            .file_matching
+            .file_extensions
+            .contains(&"css".to_string()));
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("formatCss".to_string(), ConfigKeyValue::Bool(true));
+        let result = resolve_config(config_map, &global_config);
+
+        assert!(result.config.format_css);
+        assert!(result
+            .file_matching
+            .file_extensions
+            .contains(&"css".to_string()));
+    }
+
+    #[test]
+    fn test_resolve_config_json_class_properties() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(ConfigKeyMap::new(), &global_config);
+        assert!(!result
+            .file_matching
+            .file_extensions
+            .contains(&"json".to_string()));
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "jsonClassProperties".to_string(),
+            ConfigKeyValue::Array(vec![ConfigKeyValue::String("wrapperClasses".to_string())]),
+        );
+        let result = resolve_config(config_map, &global_config);
+
+        assert_eq!(result.config.json_class_properties, vec!["wrapperClasses"]);
+        for extension in ["json", "jsonc", "yaml", "yml"] {
+            assert!(result
+                .file_matching
+                .file_extensions
+                .contains(&extension.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_resolve_config_fallback_extraction() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let global_config = GlobalConfiguration::default();
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "fallbackExtraction".to_string(),
+            ConfigKeyValue::String("attributes".to_string()),
+        );
+        let result = resolve_config(config_map, &global_config);
+        assert_eq!(
+            result.config.fallback_extraction,
+            FallbackExtraction::Attributes
+        );
+        assert!(result.diagnostics.is_empty());
+
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "fallbackExtraction".to_string(),
+            ConfigKeyValue::String("regex".to_string()),
+        );
+        let result = resolve_config(config_map, &global_config);
+        assert_eq!(result.config.fallback_extraction, FallbackExtraction::Full);
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].property_name, "fallbackExtraction");
+    }
+
+    #[test]
+    fn test_resolve_config_safelist() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let mut config = ConfigKeyMap::new();
+        config.insert(
+            "safelist".to_string(),
+            ConfigKeyValue::Array(vec![
+                ConfigKeyValue::String("btn-primary".to_string()),
+                ConfigKeyValue::String("badge-*".to_string()),
+                ConfigKeyValue::String("two classes".to_string()),
+            ]),
+        );
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(config, &global_config);
+
+        assert_eq!(result.config.safelist, vec!["btn-primary", "badge-*"]);
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].property_name, "safelist");
+
+        let mut config = ConfigKeyMap::new();
+        config.insert("safelist".to_string(), ConfigKeyValue::Array(vec![]));
+        let result = resolve_config(config, &global_config);
+        assert!(result.config.safelist.is_empty());
+        assert!(result.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_config_element_factories() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(ConfigKeyMap::new(), &global_config);
+        assert_eq!(
+            result.config.element_factories,
+            vec!["createElement", "h", "jsx"]
+        );
+
+        let mut config = ConfigKeyMap::new();
+        config.insert(
+            "elementFactories".to_string(),
+            ConfigKeyValue::Array(vec![
+                ConfigKeyValue::String("preact.h".to_string()),
+                ConfigKeyValue::String("not valid".to_string()),
+            ]),
+        );
+        let result = resolve_config(config, &global_config);
+        assert_eq!(result.config.element_factories, vec!["preact.h"]);
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].property_name, "elementFactories");
+
+        let mut config = ConfigKeyMap::new();
+        config.insert(
+            "elementFactories".to_string(),
+            ConfigKeyValue::Array(vec![]),
+        );
+        let result = resolve_config(config, &global_config);
+        assert!(result.config.element_factories.is_empty());
+        assert!(result.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_config_class_builders() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(ConfigKeyMap::new(), &global_config);
+        assert_eq!(result.config.class_builders, vec!["CssBuilder", "AddClass"]);
+        assert!(!result
+            .file_matching
+            .file_extensions
+            .contains(&"cs".to_string()));
+        assert!(result
+            .file_matching
+            .file_extensions
+            .contains(&"razor".to_string()));
+
+        let mut config = ConfigKeyMap::new();
+        config.insert(
+            "classBuilders".to_string(),
+            ConfigKeyValue::Array(vec![ConfigKeyValue::String("AddStyle(".to_string())]),
+        );
+        config.insert("formatCSharp".to_string(), ConfigKeyValue::Bool(true));
+        let result = resolve_config(config, &global_config);
+        assert!(result.config.class_builders.is_empty());
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].property_name, "classBuilders");
+        assert!(result
+            .file_matching
+            .file_extensions
+            .contains(&"cs".to_string()));
+    }
+
+    #[test]
+    fn test_resolve_config_kotlin() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(ConfigKeyMap::new(), &global_config);
+        assert!(!result.config.format_kotlin);
+        assert!(!result
+            .file_matching
+            .file_extensions
+            .contains(&"kt".to_string()));
+        assert!(!result
+            .config
+            .functions_for(Some(FileFormat::Html))
+            .contains(&"classes".to_string()));
+        assert!(result
+            .config
+            .functions_for(Some(FileFormat::Kotlin))
+            .contains(&"classes".to_string()));
+
+        let mut config = ConfigKeyMap::new();
+        config.insert("formatKotlin".to_string(), ConfigKeyValue::Bool(true));
+        config.insert(
+            "kotlinFunctions".to_string(),
+            ConfigKeyValue::Array(vec![ConfigKeyValue::String("classNames".to_string())]),
+        );
+        let result = resolve_config(config, &global_config);
+        assert!(result.diagnostics.is_empty());
+        assert_eq!(result.config.kotlin_functions, vec!["classNames"]);
+        assert!(result
+            .file_matching
+            .file_extensions
+            .contains(&"kts".to_string()));
+    }
+
+    #[test]
+    fn test_resolve_config_python() {
+        use dprint_core::configuration::ConfigKeyValue;
+
+        let global_config = GlobalConfiguration::default();
+        let result = resolve_config(ConfigKeyMap::new(), &global_config);
+        assert!(!result.config.format_python);
+        assert_eq!(
+            result.config.python_class_names,
+            vec!["cls", "class_", "klass", "class"]
+        );
+        assert!(!result
+            .file_matching
+            .file_extensions
+            .contains(&"py".to_string()));
+
+        let mut config = ConfigKeyMap::new();
+        config.insert("formatPython".to_string(), ConfigKeyValue::Bool(true));
+        config.insert(
+            "pythonClassNames".to_string(),
+            ConfigKeyValue::Array(vec![
+                ConfigKeyValue::String("css_class".to_string()),
+                ConfigKeyValue::String("cls".to_string()),
+                ConfigKeyValue::String("cls".to_string()),
+            ]),
+        );
+        let result = resolve_config(config, &global_config);
+        assert_eq!(result.config.python_class_names, vec!["css_class", "cls"]);
+        assert_eq!(result.diagnostics.len(), 1);
+        assert!(result
+            .file_matching
+            .file_extensions
+            .contains(&"py".to_string()));
+    }
+
     #[test]
     fn test_file_matching_extensions() {
         let config_map = ConfigKeyMap::new();
@@ -14,14 +14,13 @@ mod custom_config_tests {
     #[test]
     fn test_custom_function_names() {
         let config = Configuration {
-            enabled: true,
-            tailwind_config: None,
             tailwind_functions: vec![
                 "myCustomFunction".to_string(),
                 "tw".to_string(),
                 "css".to_string(),
             ],
             tailwind_attributes: vec!["class".to_string()],
+            ..Configuration::default()
         };
 
         let extractor = ClassExtractor::new(
@@ -38,8 +37,6 @@ mod custom_config_tests {
     #[test]
     fn test_custom_attribute_names() {
         let config = Configuration {
-            enabled: true,
-            tailwind_config: None,
             tailwind_functions: vec![],
             tailwind_attributes: vec![
                 "class".to_string(),
@@ -47,6 +44,7 @@ mod custom_config_tests {
                 "styleName".to_string(),
                 "css".to_string(),
             ],
+            ..Configuration::default()
         };
 
         let extractor = ClassExtractor::new(
@@ -63,10 +61,9 @@ mod custom_config_tests {
     #[test]
     fn test_config_with_single_function() {
         let config = Configuration {
-            enabled: true,
-            tailwind_config: None,
             tailwind_functions: vec!["tw".to_string()],
             tailwind_attributes: vec!["class".to_string()],
+            ..Configuration::default()
         };
 
         assert_eq!(config.tailwind_functions.len(), 1);
@@ -78,10 +75,9 @@ mod custom_config_tests {
         let functions: Vec<String> = (0..20).map(|i| format!("func{}", i)).collect();
 
         let config = Configuration {
-            enabled: true,
-            tailwind_config: None,
             tailwind_functions: functions.clone(),
             tailwind_attributes: vec!["class".to_string()],
+            ..Configuration::default()
         };
 
         assert_eq!(config.tailwind_functions.len(), 20);
@@ -91,9 +87,7 @@ mod custom_config_tests {
     fn test_disabled_config() {
         let config = Configuration {
             enabled: false,
-            tailwind_config: None,
-            tailwind_functions: vec!["clsx".to_string()],
-            tailwind_attributes: vec!["class".to_string()],
+            ..Configuration::default()
         };
 
         assert!(!config.enabled);
@@ -102,10 +96,8 @@ mod custom_config_tests {
     #[test]
     fn test_config_with_tailwind_config_path() {
         let config = Configuration {
-            enabled: true,
             tailwind_config: Some("./custom-tailwind.config.js".to_string()),
-            tailwind_functions: vec!["clsx".to_string()],
-            tailwind_attributes: vec!["class".to_string()],
+            ..Configuration::default()
         };
 
         assert!(config.tailwind_config.is_some());
@@ -374,4 +366,24 @@ mod custom_config_tests {
         // Implementation may choose to include or exclude
         assert!(matches.is_empty() || matches[0].content.trim().is_empty());
     }
+
+    #[test]
+    fn test_cva_compose_and_cx_helpers() {
+        let extractor = ClassExtractor::new(
+            vec!["cva".to_string(), "compose".to_string(), "cx".to_string()],
+            vec!["class".to_string()],
+        );
+
+        let content = r#"const card = compose(box, cva("shadow p-4", { variants: { size: { sm: "text-sm p-2" } } }));
+const title = cx("font-bold z-10", isActive() && "text-blue-500 underline");"#;
+        let matches = extractor.extract_from_functions(content);
+        let class_strings: Vec<&str> = matches.iter().map(|m| m.content.as_str()).collect();
+
+        // Nested calls are matched as a whole, so strings after an inner `)`
+        // are still found
+        assert!(class_strings.contains(&"shadow p-4"));
+        assert!(class_strings.contains(&"text-sm p-2"));
+        assert!(class_strings.contains(&"font-bold z-10"));
+        assert!(class_strings.contains(&"text-blue-500 underline"));
+    }
 }
@@ -0,0 +1,370 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::ops::Range;
+
+use crate::parser::FileFormat;
+
+/// Why a class list was found but not rewritten
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// Attribute text outside of an element tag, e.g. in text content, a
+    /// comment or `<script>`/`<style>` contents
+    OutsideTag,
+    /// String literal or template with `${...}` holes
+    DynamicContent,
+    /// Tagged template holding CSS declarations rather than classes
+    CssTemplate,
+    /// `@source inline(...)` list using brace expansion
+    BraceExpansion,
+    /// Attribute given more than once on the same element, which is invalid
+    /// markup
+    DuplicateAttribute,
+    /// `class` prop of a component tag while `sortComponentClasses` is off
+    ComponentProp,
+    /// Attribute value whose `{...}` interpolations don't balance
+    UnbalancedInterpolation,
+    /// Class list overlapping one that was already handled
+    Overlapping,
+    /// Match whose range doesn't hold its content in the file text
+    InvalidRange,
+    /// Class list written with escape sequences in a JSON string, as in a
+    /// notebook cell
+    EscapedContent,
+    /// Class list holding bytes that aren't valid UTF-8, with `lossyUtf8`
+    InvalidUtf8,
+    /// Vue template written in another language than HTML, such as Pug
+    TemplateLanguage,
+    /// Inside an element listed in `excludeElements`
+    ExcludedElement,
+    /// Classes already in sorted order
+    AlreadySorted,
+}
+
+impl SkipReason {
+    /// Human-readable explanation used in log messages
+    pub fn describe(self) -> &'static str {
+        match self {
+            SkipReason::OutsideTag => {
+                "not inside an element tag (text, comment, script or style content)"
+            }
+            SkipReason::DynamicContent => "contains dynamic content (`${...}`)",
+            SkipReason::CssTemplate => "tagged template looks like CSS",
+            SkipReason::BraceExpansion => "uses brace expansion",
+            SkipReason::DuplicateAttribute => "attribute is repeated on the same element",
+            SkipReason::ComponentProp => "prop of a component, which is left alone",
+            SkipReason::UnbalancedInterpolation => "interpolations in the value don't balance",
+            SkipReason::Overlapping => "overlaps another class list",
+            SkipReason::InvalidRange => "range doesn't match the file text",
+            SkipReason::EscapedContent => "written with escape sequences",
+            SkipReason::InvalidUtf8 => "holds bytes that aren't valid UTF-8",
+            SkipReason::TemplateLanguage => "template isn't HTML (e.g. `lang=\"pug\"`)",
+            SkipReason::ExcludedElement => "inside an element listed in `excludeElements`",
+            SkipReason::AlreadySorted => "already sorted",
+        }
+    }
+}
+
+/// Something that happened while formatting a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// No configured attribute, function or template name appears in the file
+    NoCandidates,
+    /// Number of class lists found by the extractor
+    Found(usize),
+    /// Class list at the given byte offset was sorted
+    Sorted(usize),
+    /// Class list or candidate at the given byte offset was left alone
+    Skipped { offset: usize, reason: SkipReason },
+    /// Sorting the output again changed it, so the file was left unchanged
+    Unstable(FormatError),
+}
+
+/// Message for `Event::Unstable`, which is logged even without `verbose`
+pub const UNSTABLE_MESSAGE: &str =
+    "sorting the output again changed it, so the file was left unchanged";
+
+/// Stage of formatting a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatStage {
+    /// Running the primary formatter first, with `postProcess`
+    HostFormat,
+    /// Decoding the file bytes as UTF-8
+    Decode,
+    /// Sorting the output again to check that it is stable
+    Verify,
+}
+
+impl FormatStage {
+    fn describe(self) -> &'static str {
+        match self {
+            FormatStage::HostFormat => "host formatting",
+            FormatStage::Decode => "decoding",
+            FormatStage::Verify => "verification",
+        }
+    }
+}
+
+/// Error or failed check while formatting a file
+///
+/// Carries the file path and format, the stage that failed and, where
+/// known, the class list and byte offsets involved, so a bug report can be
+/// diagnosed without the file. Offsets are into the text of that stage: the
+/// file bytes when decoding, the sorted output when verifying.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatError {
+    pub file_path: String,
+    pub format: Option<FileFormat>,
+    pub stage: FormatStage,
+    /// Index of the class list among those found, in source order
+    pub match_index: Option<usize>,
+    pub range: Option<Range<usize>>,
+    pub message: String,
+}
+
+impl FormatError {
+    pub fn new(
+        file_path: &str,
+        format: Option<FileFormat>,
+        stage: FormatStage,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            file_path: file_path.to_string(),
+            format,
+            stage,
+            match_index: None,
+            range: None,
+            message: message.into(),
+        }
+    }
+
+    /// Point the error at a class list
+    pub fn at_match(mut self, index: usize, range: Range<usize>) -> Self {
+        self.match_index = Some(index);
+        self.range = Some(range);
+        self
+    }
+
+    /// Point the error at a range of bytes
+    pub fn at_bytes(mut self, range: Range<usize>) -> Self {
+        self.range = Some(range);
+        self
+    }
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.format {
+            Some(format) => write!(f, "{} ({format:?})", self.file_path)?,
+            None => write!(f, "{} (unknown format)", self.file_path)?,
+        }
+        write!(f, ": {} failed", self.stage.describe())?;
+        if let Some(index) = self.match_index {
+            write!(f, " at class list {index}")?;
+        }
+        if let Some(range) = &self.range {
+            write!(f, " (bytes {}..{})", range.start, range.end)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// Collects troubleshooting events for one file when `verbose` is enabled
+///
+/// Recording is a no-op when disabled, so the extractor can report skips
+/// unconditionally. Offsets are relative to the text that was scanned until
+/// callers that scan a section shift them with `offset_since`.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    enabled: bool,
+    events: RefCell<Vec<Event>>,
+}
+
+impl Diagnostics {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            events: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Record an event
+    pub fn record(&self, event: Event) {
+        if self.enabled {
+            self.events.borrow_mut().push(event);
+        }
+    }
+
+    /// Record a skipped class list or candidate
+    pub fn skipped(&self, offset: usize, reason: SkipReason) {
+        self.record(Event::Skipped { offset, reason });
+    }
+
+    /// Number of events recorded so far, to pass to `offset_since`
+    pub fn mark(&self) -> usize {
+        self.events.borrow().len()
+    }
+
+    /// Shift the offsets of events recorded after `mark` by `offset`
+    pub fn offset_since(&self, mark: usize, offset: usize) {
+        self.map_since(mark, |start| start + offset);
+    }
+
+    /// Map the offsets of events recorded after `mark`, for text that was
+    /// decoded before scanning
+    pub fn map_since(&self, mark: usize, map: impl Fn(usize) -> usize) {
+        for event in self.events.borrow_mut().iter_mut().skip(mark) {
+            match event {
+                Event::Sorted(start) | Event::Skipped { offset: start, .. } => *start = map(*start),
+                Event::NoCandidates | Event::Found(_) | Event::Unstable(_) => {}
+            }
+        }
+    }
+
+    /// Recorded events in order
+    #[allow(dead_code)]
+    pub fn events(&self) -> Vec<Event> {
+        self.events.borrow().clone()
+    }
+
+    /// Render the recorded events as log lines with `line:column` positions
+    pub fn report(&self, file_path: &str, file_text: &str) -> Vec<String> {
+        self.events
+            .borrow()
+            .iter()
+            .map(|event| match event {
+                Event::NoCandidates => {
+                    format!("{file_path}: no configured attribute, function or template name found")
+                }
+                Event::Unstable(error) => error.to_string(),
+                Event::Found(count) => format!("{file_path}: found {count} class list(s)"),
+                Event::Sorted(offset) => {
+                    format!("{}: sorted", location(file_path, file_text, *offset))
+                }
+                Event::Skipped { offset, reason } => format!(
+                    "{}: skipped, {}",
+                    location(file_path, file_text, *offset),
+                    reason.describe()
+                ),
+            })
+            .collect()
+    }
+
+    /// Write the report to stderr
+    pub fn emit(&self, file_path: &str, file_text: &str) {
+        if !self.enabled {
+            return;
+        }
+        for line in self.report(file_path, file_text) {
+            warn(&line);
+        }
+    }
+}
+
+/// Write a message to stderr
+///
+/// Only native and WASI builds have a stderr to write to; the
+/// wasm32-unknown-unknown build drops the message.
+pub fn warn(message: &str) {
+    #[cfg(any(not(target_arch = "wasm32"), target_os = "wasi"))]
+    eprintln!("[tailwindcss] {message}");
+    #[cfg(all(target_arch = "wasm32", not(target_os = "wasi")))]
+    let _ = message;
+}
+
+/// `path:line:column` for a byte offset, or `path@offset` when the offset
+/// doesn't fall on a character of the text
+fn location(file_path: &str, file_text: &str, offset: usize) -> String {
+    match file_text.get(..offset) {
+        Some(before) => {
+            let line = before.matches('\n').count() + 1;
+            let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+            let column = before[line_start..].chars().count() + 1;
+            format!("{file_path}:{line}:{column}")
+        }
+        None => format!("{file_path}@{offset}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_records_nothing() {
+        let diagnostics = Diagnostics::default();
+        diagnostics.record(Event::Found(2));
+        diagnostics.skipped(0, SkipReason::AlreadySorted);
+        assert!(diagnostics.events().is_empty());
+    }
+
+    #[test]
+    fn test_offset_since_shifts_later_events() {
+        let diagnostics = Diagnostics::new(true);
+        diagnostics.skipped(1, SkipReason::OutsideTag);
+        let mark = diagnostics.mark();
+        diagnostics.skipped(2, SkipReason::DynamicContent);
+        diagnostics.offset_since(mark, 10);
+
+        assert_eq!(
+            diagnostics.events(),
+            vec![
+                Event::Skipped {
+                    offset: 1,
+                    reason: SkipReason::OutsideTag
+                },
+                Event::Skipped {
+                    offset: 12,
+                    reason: SkipReason::DynamicContent
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_error_context() {
+        let error = FormatError::new(
+            "src/App.tsx",
+            Some(FileFormat::Tsx),
+            FormatStage::Verify,
+            UNSTABLE_MESSAGE,
+        )
+        .at_match(2, 40..52);
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "src/App.tsx (Tsx): verification failed at class list 2 (bytes 40..52): {UNSTABLE_MESSAGE}"
+            )
+        );
+
+        let error =
+            FormatError::new("notes", None, FormatStage::Decode, "invalid UTF-8").at_bytes(7..8);
+        assert_eq!(
+            error.to_string(),
+            "notes (unknown format): decoding failed (bytes 7..8): invalid UTF-8"
+        );
+    }
+
+    #[test]
+    fn test_report_locations() {
+        let text = "<a>\n  <b class=\"z-10 p-4\">é</b>\n</a>";
+        let diagnostics = Diagnostics::new(true);
+        diagnostics.record(Event::Found(1));
+        diagnostics.record(Event::Sorted(text.find("z-10").unwrap()));
+        diagnostics.skipped(text.len() + 5, SkipReason::InvalidRange);
+
+        assert_eq!(
+            diagnostics.report("index.html", text),
+            vec![
+                "index.html: found 1 class list(s)".to_string(),
+                "index.html:2:13: sorted".to_string(),
+                format!(
+                    "index.html@{}: skipped, range doesn't match the file text",
+                    text.len() + 5
+                ),
+            ]
+        );
+    }
+}
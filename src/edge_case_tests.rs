@@ -259,10 +259,9 @@ mod edge_case_tests {
     #[test]
     fn test_config_with_empty_arrays() {
         let config = Configuration {
-            enabled: true,
-            tailwind_config: None,
             tailwind_functions: vec![],
             tailwind_attributes: vec![],
+            ..Configuration::default()
         };
 
         // Should not panic with empty configuration
@@ -1,5 +1,13 @@
-use once_cell::sync::Lazy;
-use regex::Regex;
+use std::collections::BTreeMap;
+
+use crate::diagnostics::{Diagnostics, SkipReason};
+use crate::scanner::{
+    interpolation_segments, json_array_elements, json_object_members, json_string,
+    markup_string_literals, markup_templates, object_property_values, python_class_values,
+    source_inline_arguments, string_literals, yaml_property_values, Candidate, CandidateKind,
+    CandidateScanner, TagRanges,
+};
+use crate::sorter::TailwindClass;
 
 /// Patterns for detecting TailwindCSS classes in different contexts
 pub struct ClassExtractor {
@@ -7,78 +15,618 @@ pub struct ClassExtractor {
     pub function_names: Vec<String>,
     /// Attribute names to look for
     pub attribute_names: Vec<String>,
+    /// Tagged template names to look for
+    pub template_names: Vec<String>,
+    /// Element factory names, whose object literal arguments hold class
+    /// lists under the attribute names
+    pub factory_names: Vec<String>,
+    /// Class builder method and constructor names, whose first argument is
+    /// a class list
+    pub builder_names: Vec<String>,
+    /// Whether attributes of component tags such as `<Button>` are sorted
+    pub component_classes: bool,
+    /// Whether object literal properties named like the attributes are
+    /// sorted in script code
+    pub object_properties: bool,
+    /// JSON and YAML property names holding class lists
+    pub data_property_names: Vec<String>,
+    /// Python keyword argument and dict key names holding class lists
+    pub python_names: Vec<String>,
+    /// Whether valueless utility attributes in markup are sorted, as in
+    /// UnoCSS's attributify mode
+    pub attributify: bool,
+    /// Single-pass scanner covering every configured attribute and function name
+    scanner: CandidateScanner,
+    /// Skipped candidates, recorded when verbose logging is enabled
+    diagnostics: Diagnostics,
 }
 
 impl ClassExtractor {
     #[allow(dead_code)]
     pub fn new(function_names: Vec<String>, attribute_names: Vec<String>) -> Self {
+        let scanner = CandidateScanner::new(&function_names, &[], &attribute_names);
         Self {
             function_names,
             attribute_names,
+            template_names: Vec::new(),
+            factory_names: Vec::new(),
+            builder_names: Vec::new(),
+            component_classes: true,
+            object_properties: false,
+            data_property_names: Vec::new(),
+            python_names: Vec::new(),
+            attributify: false,
+            scanner,
+            diagnostics: Diagnostics::default(),
         }
     }
 
+    /// Also look for tagged templates such as tw`...` with the given tags
+    pub fn with_templates(mut self, template_names: Vec<String>) -> Self {
+        self.template_names = template_names;
+        self.scanner = self.build_scanner();
+        self
+    }
+
+    /// Also look for class-bearing properties in the arguments of element
+    /// factories, as in `createElement("div", { className: "..." })`
+    pub fn with_factories(mut self, factory_names: Vec<String>) -> Self {
+        self.factory_names = factory_names;
+        self.scanner = self.build_scanner();
+        self
+    }
+
+    /// Also look for class builder calls such as
+    /// `new CssBuilder("...").AddClass("...", when: active)`
+    pub fn with_builders(mut self, builder_names: Vec<String>) -> Self {
+        self.builder_names = builder_names;
+        self.scanner = self.build_scanner();
+        self
+    }
+
+    fn build_scanner(&self) -> CandidateScanner {
+        CandidateScanner::new(
+            &self.function_names,
+            &self.template_names,
+            &self.attribute_names,
+        )
+        .with_factories(&self.factory_names)
+        .with_builders(&self.builder_names)
+    }
+
+    /// Whether to sort the class props of component tags in markup
+    ///
+    /// Some teams treat `<Button class="...">` as an opaque prop rather
+    /// than a class list. Only markup scanned with element tags is affected.
+    pub fn with_component_classes(mut self, enabled: bool) -> Self {
+        self.component_classes = enabled;
+        self
+    }
+
+    /// Whether to sort object literal properties named like the attributes,
+    /// as in Storybook's `args: { className: "..." }`, in script code
+    pub fn with_object_properties(mut self, enabled: bool) -> Self {
+        self.object_properties = enabled;
+        self
+    }
+
+    /// Property names whose values are class lists in JSON and YAML files
+    pub fn with_data_properties(mut self, names: Vec<String>) -> Self {
+        self.data_property_names = names;
+        self
+    }
+
+    /// Keyword argument and dict key names whose values are class lists in
+    /// Python files
+    pub fn with_python_names(mut self, names: Vec<String>) -> Self {
+        self.python_names = names;
+        self
+    }
+
+    /// Whether to sort valueless utility attributes of element tags, as in
+    /// `<div p-4 flex>` with UnoCSS's or Windi CSS's attributify mode
+    pub fn with_attributify(mut self, enabled: bool) -> Self {
+        self.attributify = enabled;
+        self
+    }
+
+    /// Record why candidates are skipped, for the `verbose` option
+    pub fn with_diagnostics(mut self, enabled: bool) -> Self {
+        self.diagnostics = Diagnostics::new(enabled);
+        self
+    }
+
+    /// Events recorded while extracting
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    /// Cheap substring prescan for any configured attribute or function name
+    ///
+    /// Returns `false` when none of the names appear anywhere in the content,
+    /// in which case no extraction can produce a match and callers can bail
+    /// out before running any regex or format parser.
+    pub fn has_candidates(&self, content: &str) -> bool {
+        if self.attributify && content.contains('<') {
+            return true;
+        }
+        self.attribute_names
+            .iter()
+            .chain(self.function_names.iter())
+            .chain(self.template_names.iter())
+            .chain(self.factory_names.iter())
+            .chain(self.builder_names.iter())
+            .any(|name| !name.is_empty() && content.contains(name.as_str()))
+    }
+
     /// Extract all class strings from HTML/JSX attributes
+    #[allow(dead_code)]
     pub fn extract_from_attributes(&self, content: &str) -> Vec<ClassMatch> {
+        self.scan(content, true, false, None)
+    }
+
+    /// Extract class strings from utility function calls
+    pub fn extract_from_functions(&self, content: &str) -> Vec<ClassMatch> {
+        self.scan(content, false, true, None)
+    }
+
+    /// Extract class strings from attributes of HTML-like markup
+    ///
+    /// Attribute matches only count when they sit inside an element tag, so
+    /// class-looking text in text content, comments or `<script>` contents
+    /// is left alone.
+    pub fn extract_attributes_from_markup(&self, content: &str) -> Vec<ClassMatch> {
+        let tags = TagRanges::new(content);
+        let mut matches = self.scan(content, true, false, Some(&tags));
+        matches.extend(self.extract_attributify(content, &tags));
+        matches
+    }
+
+    /// Extract all class strings from HTML-like markup
+    ///
+    /// Like `extract_all`, but attribute matches are validated against the
+    /// element tags as in `extract_attributes_from_markup`. Function calls
+    /// are matched anywhere.
+    pub fn extract_from_markup(&self, content: &str) -> Vec<ClassMatch> {
+        let tags = TagRanges::new(content);
+        let mut matches = self.scan(content, true, true, Some(&tags));
+        matches.extend(self.extract_attributify(content, &tags));
+        matches
+    }
+
+    /// Extract neighbouring valueless utility attributes as class lists
+    ///
+    /// Only known utilities count, so `<input disabled p-4 m-2>` has `p-4 m-2`
+    /// reordered while `disabled` stays put, and a lone utility is left alone.
+    fn extract_attributify(&self, content: &str, tags: &TagRanges) -> Vec<ClassMatch> {
+        if !self.attributify {
+            return Vec::new();
+        }
+
+        let mut matches = Vec::new();
+        for run in tags.bare_attribute_runs(content) {
+            if !self.component_classes
+                && tags
+                    .tag_name(content, run[0].start)
+                    .is_some_and(is_component_tag)
+            {
+                continue;
+            }
+            for group in run.split(|name| !TailwindClass::parse(&content[name.clone()]).is_known())
+            {
+                if let [first, .., last] = group {
+                    matches.push(ClassMatch {
+                        start: first.start,
+                        end: last.end,
+                        content: content[first.start..last.end].to_string(),
+                        quote: None,
+                    });
+                }
+            }
+        }
+        matches
+    }
+
+    /// Extract class strings from HTML inside string literals
+    ///
+    /// Server-side code often builds markup in strings, as in
+    /// `res.send('<div class="z-10 p-4">')`. Every string literal that
+    /// contains a `<` is treated as markup, and only attributes inside its
+    /// element tags are matched.
+    pub fn extract_from_html_strings(&self, content: &str) -> Vec<ClassMatch> {
         let mut matches = Vec::new();
 
-        for attr_name in &self.attribute_names {
-            // Match class="..." or className="..." or class='...'
-            let pattern = format!(r#"{}=["']([^"']*)["']"#, regex::escape(attr_name));
-            if let Ok(re) = Regex::new(&pattern) {
-                for cap in re.captures_iter(content) {
-                    if let Some(classes) = cap.get(1) {
-                        let class_content = classes.as_str();
-                        if !class_content.trim().is_empty() {
-                            matches.push(ClassMatch {
-                                start: classes.start(),
-                                end: classes.end(),
-                                content: class_content.to_string(),
-                            });
-                        }
-                    }
+        for literal in string_literals(content) {
+            let markup = &content[literal.clone()];
+            if !markup.contains('<') {
+                continue;
+            }
+            let mark = self.diagnostics.mark();
+            let mut literal_matches = self.extract_attributes_from_markup(markup);
+            // Attribute quotes can't change inside a string literal without
+            // ending it
+            for class_match in &mut literal_matches {
+                class_match.start += literal.start;
+                class_match.end += literal.start;
+                class_match.quote = None;
+            }
+            self.diagnostics.offset_since(mark, literal.start);
+            matches.extend(literal_matches);
+        }
+
+        matches
+    }
+
+    /// Extract class strings from web component markup in script code
+    ///
+    /// Lit and FAST write markup in `html` tagged templates, and Stencil-style
+    /// or vanilla custom elements return it from `render()`. Only attributes
+    /// inside the element tags of those templates are matched.
+    pub fn extract_from_markup_templates(&self, content: &str) -> Vec<ClassMatch> {
+        let mut matches = Vec::new();
+
+        for template in markup_templates(content) {
+            let mark = self.diagnostics.mark();
+            let mut template_matches =
+                self.extract_attributes_from_markup(&content[template.clone()]);
+            // Attribute quotes are kept as written inside code
+            for class_match in &mut template_matches {
+                class_match.start += template.start;
+                class_match.end += template.start;
+                class_match.quote = None;
+            }
+            self.diagnostics.offset_since(mark, template.start);
+            matches.extend(template_matches);
+        }
+
+        matches
+    }
+
+    /// Extract class strings from Tailwind v4 `@source inline("...")` rules
+    ///
+    /// Lists using brace expansion, such as `bg-red-{50,{100..900..100}}`,
+    /// are left alone since their tokens aren't classes yet.
+    pub fn extract_from_css(&self, content: &str) -> Vec<ClassMatch> {
+        let mut matches = Vec::new();
+
+        for arguments in source_inline_arguments(content) {
+            let args = &content[arguments.clone()];
+            for literal in string_literals(args) {
+                if args[literal.clone()].contains('{') {
+                    self.diagnostics
+                        .skipped(arguments.start + literal.start, SkipReason::BraceExpansion);
+                    continue;
                 }
+                matches.extend(self.extract_literals(args, vec![literal], arguments.start));
+            }
+        }
+
+        matches
+    }
+
+    /// Extract class strings from object literal properties named like the
+    /// attributes, as in `args: { className: "z-10 p-4" }`
+    ///
+    /// The string literals of each value are class lists, so conditional
+    /// values such as `open ? "flex" : "hidden"` are handled too.
+    pub fn extract_from_object_properties(&self, content: &str) -> Vec<ClassMatch> {
+        object_property_values(content, &self.attribute_names)
+            .into_iter()
+            .flat_map(|value| self.extract_strings_from_args(&content[value.clone()], value.start))
+            .collect()
+    }
+
+    /// Extract class strings from the configured properties of a JSON file
+    ///
+    /// String values are class lists, and so are the strings of array
+    /// values; other values such as nested objects are left alone.
+    pub fn extract_from_json(&self, content: &str) -> Vec<ClassMatch> {
+        let mut matches = Vec::new();
+
+        for value in object_property_values(content, &self.data_property_names) {
+            let text = &content[value.clone()];
+            if text.trim_start().starts_with(['"', '[']) {
+                matches.extend(self.extract_strings_from_args(text, value.start));
             }
+        }
+
+        matches
+    }
+
+    /// Extract class strings from the HTML in the cells of a Jupyter notebook
+    ///
+    /// Markdown cells and `%%html` code cells are scanned like markup after
+    /// their JSON `source` strings are decoded and joined, so tags spanning
+    /// source lines are found. Class lists are rewritten in place in the
+    /// JSON text, leaving outputs and metadata untouched; lists written with
+    /// escape sequences are skipped.
+    pub fn extract_from_notebook(&self, content: &str) -> Vec<ClassMatch> {
+        let mut matches = Vec::new();
+        let root = content.len() - content.trim_start().len();
 
-            // Match class={...} or className={...} (JSX)
-            let jsx_pattern = format!(r#"{}\s*=\s*\{{([^}}]+)\}}"#, regex::escape(attr_name));
-            if let Ok(re) = Regex::new(&jsx_pattern) {
-                for cap in re.captures_iter(content) {
-                    if let Some(expr) = cap.get(1) {
-                        // Extract string literals from JSX expressions
-                        let jsx_matches =
-                            self.extract_from_jsx_expression(expr.as_str(), expr.start());
-                        matches.extend(jsx_matches);
+        let cells = json_object_members(content, root)
+            .into_iter()
+            .filter(|(key, _)| &content[key.clone()] == "cells")
+            .flat_map(|(_, value)| json_array_elements(content, value.start));
+        for cell in cells {
+            let mut cell_type = None;
+            let mut sources = Vec::new();
+            for (key, value) in json_object_members(content, cell.start) {
+                match &content[key] {
+                    "cell_type" => cell_type = json_string(content, value.start),
+                    "source" if content[value.clone()].starts_with('[') => {
+                        sources = json_array_elements(content, value.start)
                     }
+                    "source" => sources = vec![value],
+                    _ => {}
+                }
+            }
+
+            let mut text = String::new();
+            let mut positions = Vec::new();
+            for source in sources {
+                if let Some((line, line_positions)) = json_string(content, source.start) {
+                    text.push_str(&line);
+                    positions.extend(line_positions);
+                }
+            }
+            let is_html = match cell_type.as_ref().map(|(cell_type, _)| cell_type.as_str()) {
+                Some("markdown") => true,
+                Some("code") => text.trim_start().starts_with("%%html"),
+                _ => false,
+            };
+            if !is_html {
+                continue;
+            }
+
+            let mark = self.diagnostics.mark();
+            let cell_matches = self.extract_attributes_from_markup(&text);
+            let position = |offset: usize| positions.get(offset).copied().unwrap_or(cell.end);
+            self.diagnostics.map_since(mark, position);
+            for class_match in cell_matches {
+                // Without escapes the list has the same length in the JSON text
+                let start = position(class_match.start);
+                let end = start + class_match.content.len();
+                if content.get(start..end) != Some(class_match.content.as_str()) {
+                    self.diagnostics.skipped(start, SkipReason::EscapedContent);
+                    continue;
                 }
+                // The attribute quotes are escaped in the JSON text
+                matches.push(ClassMatch {
+                    start,
+                    end,
+                    quote: None,
+                    ..class_match
+                });
             }
         }
 
         matches
     }
 
-    /// Extract class strings from utility function calls
-    pub fn extract_from_functions(&self, content: &str) -> Vec<ClassMatch> {
+    /// Extract class strings from the configured keys of a YAML file
+    pub fn extract_from_yaml(&self, content: &str) -> Vec<ClassMatch> {
+        let literals = yaml_property_values(content, &self.data_property_names);
+        self.extract_literals(content, literals, 0)
+    }
+
+    /// Extract class strings from the configured keyword arguments and dict
+    /// keys of a Python file
+    ///
+    /// f-strings such as `cls=f"p-4 {extra}"` are dynamic and skipped.
+    pub fn extract_from_python(&self, content: &str) -> Vec<ClassMatch> {
+        let mut literals = Vec::new();
+        for value in python_class_values(content, &self.python_names) {
+            if value.formatted {
+                self.diagnostics
+                    .skipped(value.range.start, SkipReason::DynamicContent);
+            } else {
+                literals.push(value.range);
+            }
+        }
+        self.extract_literals(content, literals, 0)
+    }
+
+    /// Find attribute and function candidates in a single pass over the content
+    ///
+    /// The scanner looks for every configured name at once, so the cost of a
+    /// scan no longer grows with the number of attributes and functions, and
+    /// it runs in linear time even on unterminated quotes or calls. Each
+    /// candidate is dispatched by its kind. When `tags` is given, attribute
+    /// candidates outside of element tags are dropped, and so are attributes
+    /// repeated on the same element: `<div class="p-4" class="mt-2">` is
+    /// invalid markup, and sorting both copies would hide the mistake.
+    fn scan(
+        &self,
+        content: &str,
+        attributes: bool,
+        functions: bool,
+        tags: Option<&TagRanges>,
+    ) -> Vec<ClassMatch> {
         let mut matches = Vec::new();
+        let candidates = self.scanner.scan(content);
+        let duplicates = match tags {
+            Some(tags) if attributes => duplicate_attributes(content, &candidates, tags),
+            _ => vec![false; candidates.len()],
+        };
 
-        for func_name in &self.function_names {
-            // Match function calls: clsx("...", "...")
-            let pattern = format!(r#"{}\s*\(([^)]+)\)"#, regex::escape(func_name));
-            if let Ok(re) = Regex::new(&pattern) {
-                for cap in re.captures_iter(content) {
-                    if let Some(args) = cap.get(1) {
-                        // Extract string literals from function arguments
-                        let func_matches =
-                            self.extract_strings_from_args(args.as_str(), args.start());
-                        matches.extend(func_matches);
+        for (candidate, duplicate) in candidates.into_iter().zip(duplicates) {
+            let range = candidate.range;
+            let outside_tag = !matches!(
+                candidate.kind,
+                CandidateKind::FunctionArguments
+                    | CandidateKind::FactoryArguments
+                    | CandidateKind::BuilderArguments
+                    | CandidateKind::TaggedTemplate
+            ) && !tags
+                .is_none_or(|tags| tags.is_attribute_start(candidate.name_start));
+            if attributes && outside_tag {
+                self.diagnostics
+                    .skipped(candidate.name_start, SkipReason::OutsideTag);
+            } else if attributes && duplicate {
+                self.diagnostics
+                    .skipped(candidate.name_start, SkipReason::DuplicateAttribute);
+            }
+            let attributes = attributes && !outside_tag && !duplicate;
+            let component_prop = attributes
+                && !self.component_classes
+                && matches!(
+                    candidate.kind,
+                    CandidateKind::AttributeValue
+                        | CandidateKind::AttributeExpression
+                        | CandidateKind::AttributeBinding
+                )
+                && tags.is_some_and(|tags| {
+                    tags.tag_name(content, candidate.name_start)
+                        .is_some_and(is_component_tag)
+                });
+            if component_prop {
+                self.diagnostics
+                    .skipped(candidate.name_start, SkipReason::ComponentProp);
+            }
+            let attributes = attributes && !component_prop;
+            match candidate.kind {
+                // Match class="..." or className="..." or class='...'
+                // Values with `{...}` interpolations, as in Svelte's
+                // class="p-4 {active ? 'a' : 'b'}", only have their static
+                // parts and the string literals inside the expressions sorted
+                CandidateKind::AttributeValue
+                    if attributes && content[range.clone()].contains('{') =>
+                {
+                    matches.extend(
+                        self.extract_from_interpolated_value(&content[range.clone()], range.start),
+                    );
+                }
+                CandidateKind::AttributeValue if attributes => {
+                    let class_content = &content[range.clone()];
+                    if !class_content.trim().is_empty() {
+                        matches.push(ClassMatch {
+                            start: range.start,
+                            end: range.end,
+                            content: class_content.to_string(),
+                            quote: quote_around(content, &range),
+                        });
+                    }
+                }
+                // Match class={...} or className={...} (JSX), where only
+                // string literals are class lists
+                CandidateKind::AttributeExpression if attributes => {
+                    matches.extend(
+                        self.extract_from_jsx_expression(&content[range.clone()], range.start),
+                    );
+                }
+                // Match bound :class="..." expressions, whose string literals
+                // may be delimited by encoded quotes such as &quot;
+                CandidateKind::AttributeBinding if attributes => {
+                    let expr = &content[range.clone()];
+                    matches.extend(self.extract_literals(
+                        expr,
+                        markup_string_literals(expr),
+                        range.start,
+                    ));
+                }
+                // Match function calls: clsx("...", "...")
+                CandidateKind::FunctionArguments if functions => {
+                    matches.extend(
+                        self.extract_strings_from_args(&content[range.clone()], range.start),
+                    );
+                }
+                // Match element factory calls: h("div", { class: "..." }),
+                // where only the attribute-named properties hold class lists
+                CandidateKind::FactoryArguments if functions => {
+                    let args = &content[range.clone()];
+                    for value in object_property_values(args, &self.attribute_names) {
+                        matches.extend(self.extract_strings_from_args(
+                            &args[value.clone()],
+                            range.start + value.start,
+                        ));
                     }
                 }
+                // Match class builder calls: .AddClass("...", when: active),
+                // where only a string literal first argument is a class list
+                CandidateKind::BuilderArguments if functions => {
+                    matches
+                        .extend(self.extract_first_argument(&content[range.clone()], range.start));
+                }
+                // Match tagged templates: tw`...`. Templates with `${...}`
+                // holes, and CSS text such as css`color: red;`, are skipped
+                CandidateKind::TaggedTemplate if functions => {
+                    let template = &content[range.clone()];
+                    if template.contains('$') {
+                        self.diagnostics
+                            .skipped(range.start, SkipReason::DynamicContent);
+                    } else if template.contains(';') {
+                        self.diagnostics
+                            .skipped(range.start, SkipReason::CssTemplate);
+                    } else if !template.trim().is_empty() {
+                        matches.push(ClassMatch {
+                            start: range.start,
+                            end: range.end,
+                            content: template.to_string(),
+                            quote: None,
+                        });
+                    }
+                }
+                _ => {}
             }
         }
 
         matches
     }
 
+    /// Extract the static class lists and the string literals of the
+    /// interpolations in an attribute value
+    ///
+    /// Values whose braces don't balance are left alone.
+    fn extract_from_interpolated_value(&self, value: &str, base_offset: usize) -> Vec<ClassMatch> {
+        let Some(segments) = interpolation_segments(value) else {
+            self.diagnostics
+                .skipped(base_offset, SkipReason::UnbalancedInterpolation);
+            return Vec::new();
+        };
+
+        let mut matches: Vec<ClassMatch> = segments
+            .statics
+            .into_iter()
+            .map(|segment| ClassMatch {
+                start: base_offset + segment.start,
+                end: base_offset + segment.end,
+                content: value[segment].to_string(),
+                quote: None,
+            })
+            .collect();
+        for expression in segments.expressions {
+            matches.extend(self.extract_strings_from_args(
+                &value[expression.clone()],
+                base_offset + expression.start,
+            ));
+        }
+        matches.sort_by_key(|m| m.start);
+
+        matches
+    }
+
+    /// Extract the first argument of a call when it is a string literal
+    ///
+    /// C# verbatim strings such as `@"..."` count; interpolated strings such
+    /// as `$"btn-{size}"` are dynamic and skipped.
+    fn extract_first_argument(&self, args: &str, base_offset: usize) -> Vec<ClassMatch> {
+        let start = args.len() - args.trim_start().len();
+        let Some(literal) = string_literals(args).into_iter().next() else {
+            return Vec::new();
+        };
+        let prefix = &args[start..literal.start - 1];
+        if prefix.contains('$') {
+            self.diagnostics
+                .skipped(base_offset + literal.start, SkipReason::DynamicContent);
+            return Vec::new();
+        }
+        if !prefix.is_empty() && prefix != "@" {
+            return Vec::new();
+        }
+        self.extract_literals(args, vec![literal], base_offset)
+    }
+
     /// Extract string literals from JSX expression
     fn extract_from_jsx_expression(&self, expr: &str, base_offset: usize) -> Vec<ClassMatch> {
         self.extract_strings_from_args(expr, base_offset)
@@ -86,22 +634,44 @@ impl ClassExtractor {
 
     /// Extract string literals from function arguments or JSX expressions
     fn extract_strings_from_args(&self, args: &str, base_offset: usize) -> Vec<ClassMatch> {
-        static STRING_REGEX: Lazy<Regex> =
-            Lazy::new(|| Regex::new(r#"["'`]([^"'`]*)["'`]"#).unwrap());
+        self.extract_literals(args, string_literals(args), base_offset)
+    }
 
+    /// Turn string literal ranges found in `source` into class matches
+    fn extract_literals(
+        &self,
+        source: &str,
+        literals: Vec<std::ops::Range<usize>>,
+        base_offset: usize,
+    ) -> Vec<ClassMatch> {
         let mut matches = Vec::new();
 
-        for cap in STRING_REGEX.captures_iter(args) {
-            if let Some(string_content) = cap.get(1) {
-                let content = string_content.as_str();
-                // Skip if it looks like a variable or expression
-                if !content.contains('$') && !content.is_empty() {
+        for literal in literals {
+            let content = &source[literal.clone()];
+            // Skip if it looks like a variable or expression
+            if content.contains('$') {
+                self.diagnostics
+                    .skipped(base_offset + literal.start, SkipReason::DynamicContent);
+            } else if is_concatenated(source, &literal) {
+                // The spaces at the edges of `"z-10 p-4 " + extra` keep the
+                // classes apart once joined, so only the classes are sorted
+                let start = literal.start + (content.len() - content.trim_start().len());
+                let end = literal.start + content.trim_end().len();
+                if start < end {
                     matches.push(ClassMatch {
-                        start: base_offset + string_content.start(),
-                        end: base_offset + string_content.end(),
-                        content: content.to_string(),
+                        start: base_offset + start,
+                        end: base_offset + end,
+                        content: source[start..end].to_string(),
+                        quote: None,
                     });
                 }
+            } else if !content.is_empty() {
+                matches.push(ClassMatch {
+                    start: base_offset + literal.start,
+                    end: base_offset + literal.end,
+                    content: content.to_string(),
+                    quote: None,
+                });
             }
         }
 
@@ -111,9 +681,7 @@ impl ClassExtractor {
     /// Extract all class strings from content
     #[allow(dead_code)]
     pub fn extract_all(&self, content: &str) -> Vec<ClassMatch> {
-        let mut matches = Vec::new();
-        matches.extend(self.extract_from_attributes(content));
-        matches.extend(self.extract_from_functions(content));
+        let mut matches = self.scan(content, true, true, None);
 
         // Sort by position and remove duplicates
         matches.sort_by_key(|m| m.start);
@@ -123,6 +691,95 @@ impl ClassExtractor {
     }
 }
 
+/// Whether the string literal at `literal` in `source` is an operand of a
+/// `+` concatenation, as in `"p-4 " + extra`
+///
+/// The literal's delimiters may be plain or encoded quotes, as in Vue's
+/// `:class="&quot;p-4 &quot; + extra"`.
+fn is_concatenated(source: &str, literal: &std::ops::Range<usize>) -> bool {
+    const DELIMITERS: [&str; 6] = ["\"", "'", "`", "&quot;", "&#39;", "&apos;"];
+    let before = &source[..literal.start];
+    let after = &source[literal.end..];
+    let before = DELIMITERS
+        .iter()
+        .find_map(|delimiter| before.strip_suffix(delimiter))
+        .unwrap_or(before);
+    let after = DELIMITERS
+        .iter()
+        .find_map(|delimiter| after.strip_prefix(delimiter))
+        .unwrap_or(after);
+    before.trim_end().ends_with('+') || after.trim_start().starts_with('+')
+}
+
+/// The `"` or `'` on both sides of `range`, if any
+fn quote_around(content: &str, range: &std::ops::Range<usize>) -> Option<char> {
+    let bytes = content.as_bytes();
+    let before = *bytes.get(range.start.checked_sub(1)?)?;
+    (matches!(before, b'"' | b'\'') && bytes.get(range.end) == Some(&before))
+        .then_some(before as char)
+}
+
+/// Whether a tag name refers to a component rather than an element
+///
+/// Components are capitalized (`<Button>`), namespaced (`<Form.Input>`) or
+/// Svelte's `<svelte:component>` and `<svelte:self>`. `<svelte:element>`
+/// renders a plain element, and so do custom elements, which may contain
+/// dots as in `<x-foo.bar>`.
+fn is_component_tag(name: &str) -> bool {
+    if is_custom_element_tag(name) {
+        return false;
+    }
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+        || name.contains('.')
+        || name == "svelte:component"
+        || name == "svelte:self"
+}
+
+/// Whether a tag name is a custom element name: a lowercase ASCII letter
+/// followed by a name containing a dash, such as `<sl-button>` or
+/// `<math-ω>`
+fn is_custom_element_tag(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.contains('-')
+        && !name.contains(':')
+        && !name.chars().any(|c| c.is_ascii_uppercase())
+}
+
+/// Flag the attribute candidates whose attribute appears more than once on
+/// the same element tag
+///
+/// A static `class` and a bound `:class` are distinct attributes.
+fn duplicate_attributes(content: &str, candidates: &[Candidate], tags: &TagRanges) -> Vec<bool> {
+    let keys: Vec<Option<(usize, bool, &str)>> = candidates
+        .iter()
+        .map(|candidate| {
+            if candidate.kind == CandidateKind::FunctionArguments
+                || candidate.kind == CandidateKind::FactoryArguments
+                || candidate.kind == CandidateKind::TaggedTemplate
+                || !tags.is_attribute_start(candidate.name_start)
+            {
+                return None;
+            }
+            let tag = tags.tag_at(candidate.name_start)?;
+            let name = &content[candidate.name_start..];
+            let name_end = name
+                .find(|c: char| c.is_whitespace() || matches!(c, '=' | '"' | '\'' | '{'))
+                .unwrap_or(name.len());
+            let binding = candidate.kind == CandidateKind::AttributeBinding;
+            Some((tag, binding, &name[..name_end]))
+        })
+        .collect();
+
+    let mut counts: BTreeMap<(usize, bool, &str), usize> = BTreeMap::new();
+    for key in keys.iter().flatten() {
+        *counts.entry(*key).or_default() += 1;
+    }
+
+    keys.iter()
+        .map(|key| key.is_some_and(|key| counts[&key] > 1))
+        .collect()
+}
+
 /// Represents a matched class string in the source code
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ClassMatch {
@@ -132,11 +789,33 @@ pub struct ClassMatch {
     pub end: usize,
     /// The matched class content
     pub content: String,
+    /// Quote delimiting the whole match, for attribute values whose quotes
+    /// can be rewritten without touching surrounding code. `None` for
+    /// unquoted values, string literals in code and parts of a value
+    pub quote: Option<char>,
+}
+
+impl ClassMatch {
+    /// Whether the match covers an in-bounds range of `source` that holds
+    /// exactly its content
+    pub fn is_valid_in(&self, source: &str) -> bool {
+        self.start <= self.end && source.get(self.start..self.end) == Some(self.content.as_str())
+    }
+
+    /// Range of the match including its quotes, when `source` still has
+    /// the recorded quote on both sides
+    pub fn quoted_range(&self, source: &str) -> Option<std::ops::Range<usize>> {
+        let quote = self.quote?;
+        let range = self.start.checked_sub(1)?..self.end + 1;
+        let quoted = source.get(range.clone())?;
+        (quoted.starts_with(quote) && quoted.ends_with(quote)).then_some(range)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::diagnostics::Event;
 
     fn create_extractor() -> ClassExtractor {
         ClassExtractor::new(
@@ -237,6 +916,20 @@ mod tests {
         assert_eq!(matches[0].content, "text-red-500 bg-blue-500");
     }
 
+    #[test]
+    fn test_extract_jsx_concatenation() {
+        let extractor = create_extractor();
+        let jsx = r#"<div className={"z-10 p-4 " + extra + " mt-2 flex" + ' '}>Test</div>"#;
+        let matches = extractor.extract_from_attributes(jsx);
+        let contents: Vec<&str> = matches.iter().map(|m| m.content.as_str()).collect();
+
+        // Only the classes are matched, not the spaces joining them
+        assert_eq!(contents, ["z-10 p-4", "mt-2 flex"]);
+        for m in &matches {
+            assert_eq!(&jsx[m.start..m.end], m.content);
+        }
+    }
+
     #[test]
     fn test_extract_empty_class() {
         let extractor = create_extractor();
@@ -320,4 +1013,212 @@ mod tests {
         assert_eq!(matches[0].content, "text-red-500");
         assert_eq!(matches[1].content, "bg-blue-500");
     }
+
+    #[test]
+    fn test_single_scan_does_not_duplicate_nested_candidates() {
+        let extractor = create_extractor();
+        let jsx = r#"<div className={clsx("p-4 flex")} class="mt-2">Test</div>"#;
+        let matches = extractor.extract_all(jsx);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].content, "p-4 flex");
+        assert_eq!(matches[1].content, "mt-2");
+    }
+
+    #[test]
+    fn test_longest_attribute_name_wins() {
+        let extractor =
+            ClassExtractor::new(vec![], vec!["class".to_string(), "class-list".to_string()]);
+        let html = r#"<div class-list="p-4 flex">Test</div>"#;
+        let matches = extractor.extract_from_attributes(html);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].content, "p-4 flex");
+    }
+
+    #[test]
+    fn test_extract_tagged_templates() {
+        let extractor =
+            create_extractor().with_templates(vec!["tw".to_string(), "css".to_string()]);
+        let code =
+            "const a = tw`z-10 p-4`;\nconst b = css`color: red;`;\nconst c = clsx`flex m-2`;";
+        let matches = extractor.extract_from_functions(code);
+        let class_strings: Vec<&str> = matches.iter().map(|m| m.content.as_str()).collect();
+
+        assert_eq!(class_strings, vec!["z-10 p-4", "flex m-2"]);
+        assert!(extractor.has_candidates("tw`p-4`"));
+    }
+
+    #[test]
+    fn test_extract_from_html_strings() {
+        let extractor = create_extractor();
+        let code = r#"res.send('<div class="z-10 p-4">' + name + "</div>");
+const label = "class=\"not-markup\"";
+const row = `<tr class="border-b p-2"><td class='text-sm'>${cell}</td></tr>`;"#;
+        let matches = extractor.extract_from_html_strings(code);
+
+        let class_strings: Vec<&str> = matches.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(class_strings, vec!["z-10 p-4", "border-b p-2", "text-sm"]);
+        for m in &matches {
+            assert_eq!(&code[m.start..m.end], m.content);
+        }
+    }
+
+    #[test]
+    fn test_has_candidates() {
+        let extractor = create_extractor();
+
+        assert!(extractor.has_candidates(r#"<div class="p-4"></div>"#));
+        assert!(extractor.has_candidates(r#"const x = clsx("p-4");"#));
+        assert!(!extractor.has_candidates("const data = [1, 2, 3];"));
+    }
+
+    #[test]
+    fn test_attribute_value_quotes() {
+        let extractor = create_extractor();
+        let html = r#"<a class='b a'></a><b class="d c"></b><u class="f {x ? 'g' : 'h'}"></u>"#;
+        let quotes: Vec<(&str, Option<char>)> = extractor
+            .extract_from_markup(html)
+            .iter()
+            .map(|m| (&html[m.start..m.end], m.quote))
+            .collect();
+
+        assert_eq!(
+            quotes,
+            vec![
+                ("b a", Some('\'')),
+                ("d c", Some('"')),
+                ("f", None),
+                ("g", None),
+                ("h", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_component_class_props() {
+        let html = r#"<Button class="z-10 p-4" /><Form.Input class="b a" />
+<svelte:element this={tag} class="mt-2 flex"><svelte:component this={C} class="d c" /></svelte:element>"#;
+
+        let class_strings = |extractor: &ClassExtractor| -> Vec<String> {
+            extractor
+                .extract_from_markup(html)
+                .into_iter()
+                .map(|m| m.content)
+                .collect()
+        };
+        assert_eq!(
+            class_strings(&create_extractor()),
+            vec!["z-10 p-4", "b a", "mt-2 flex", "d c"]
+        );
+
+        let extractor = create_extractor()
+            .with_component_classes(false)
+            .with_diagnostics(true);
+        assert_eq!(class_strings(&extractor), vec!["mt-2 flex"]);
+        assert_eq!(
+            extractor.diagnostics().events()[0],
+            Event::Skipped {
+                offset: html.find("class").unwrap(),
+                reason: SkipReason::ComponentProp
+            }
+        );
+    }
+
+    #[test]
+    fn test_custom_elements_are_not_components() {
+        assert!(!is_component_tag("sl-button"));
+        assert!(!is_component_tag("x-foo.bar"));
+        assert!(!is_component_tag("math-ω"));
+        assert!(is_component_tag("motion.div"));
+        assert!(is_component_tag("My-Element"));
+        assert!(is_component_tag("svelte:component"));
+
+        let html = r#"<x-foo.bar class="z-10 p-4"></x-foo.bar><ui.card class="b a" />"#;
+        let matches = create_extractor()
+            .with_component_classes(false)
+            .extract_from_markup(html);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].content, "z-10 p-4");
+    }
+
+    #[test]
+    fn test_duplicate_attributes_skipped() {
+        let extractor = create_extractor().with_diagnostics(true);
+        let html = r#"<div class="z-10 p-4" id="a" class="mt-2 flex">
+  <span class="z-10 p-4" :class="{ 'mt-2 flex': open }" className="b a">x</span>
+</div>"#;
+        let matches = extractor.extract_from_markup(html);
+
+        // Both copies on the div are left alone; on the span `class`,
+        // `:class` and `className` are distinct attributes
+        let class_strings: Vec<&str> = matches.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(class_strings, vec!["z-10 p-4", "mt-2 flex", "b a"]);
+        let first = html.find("class=").unwrap();
+        let second = html[first + 1..].find("class=").unwrap() + first + 1;
+        assert_eq!(
+            extractor.diagnostics().events(),
+            vec![
+                Event::Skipped {
+                    offset: first,
+                    reason: SkipReason::DuplicateAttribute
+                },
+                Event::Skipped {
+                    offset: second,
+                    reason: SkipReason::DuplicateAttribute
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_from_element_factories() {
+        let extractor =
+            create_extractor().with_factories(vec!["createElement".to_string(), "h".to_string()]);
+        let code = r#"React.createElement("div", { id: "z-10", className: open ? "z-10 p-4" : "m-2" },
+  h("span", { class: clsx("b a") }));
+push("x", { className: "no" });"#;
+        let matches = extractor.extract_from_functions(code);
+
+        let class_strings: Vec<&str> = matches.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(class_strings, vec!["z-10 p-4", "m-2", "b a"]);
+        for m in &matches {
+            assert_eq!(&code[m.start..m.end], m.content);
+        }
+    }
+
+    #[test]
+    fn test_extract_from_object_properties() {
+        let extractor = create_extractor();
+        let code = r#"export const Primary = {
+  args: { label: "Save now", className: "z-10 p-4", "class": open ? "b a" : "" },
+};
+type Props = { className: string };"#;
+        let matches = extractor.extract_from_object_properties(code);
+
+        let class_strings: Vec<&str> = matches.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(class_strings, vec!["z-10 p-4", "b a"]);
+        for m in &matches {
+            assert_eq!(&code[m.start..m.end], m.content);
+        }
+    }
+
+    #[test]
+    fn test_extract_from_class_builders() {
+        let extractor = create_extractor()
+            .with_builders(vec!["CssBuilder".to_string(), "AddClass".to_string()]);
+        let code = r#"new CssBuilder(@"z-10 p-4")
+    .AddClass("m-2 flex", when: IsActive)
+    .AddClass($"btn-{Size} p-4")
+    .AddClass(Extra, "no")
+    .AddClass(() => "no")
+    .Build();"#;
+        let matches = extractor.extract_from_functions(code);
+
+        let class_strings: Vec<&str> = matches.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(class_strings, vec!["z-10 p-4", "m-2 flex"]);
+        for m in &matches {
+            assert_eq!(&code[m.start..m.end], m.content);
+        }
+    }
 }
@@ -0,0 +1,165 @@
+//! Golden-file tests over the fixture directories in `tests/fixtures`
+//!
+//! Every directory holding an `input.<ext>` file is a case: the input is
+//! formatted as a file with that extension and compared with the
+//! `expected.<ext>` file next to it. An optional `config.json` in the case
+//! directory holds plugin options. Run with `UPDATE_SNAPSHOTS=1` to write the
+//! current output to the expected files instead of comparing, for new cases
+//! or after an intended change in behavior.
+
+use super::*;
+use dprint_core::configuration::{ConfigKeyMap, ConfigKeyValue, GlobalConfiguration};
+use dprint_core::plugins::FormatConfigId;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// One fixture case
+struct Fixture {
+    dir: PathBuf,
+    input: PathBuf,
+    expected: PathBuf,
+}
+
+fn fixtures_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+/// Find the cases below a directory, in path order
+fn find_fixtures(dir: &Path, fixtures: &mut Vec<Fixture>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        if path.is_dir() {
+            find_fixtures(&path, fixtures);
+        } else if path.file_stem().is_some_and(|stem| stem == "input") {
+            let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+            fixtures.push(Fixture {
+                dir: dir.to_path_buf(),
+                expected: dir.join(format!("expected.{extension}")),
+                input: path,
+            });
+        }
+    }
+}
+
+/// Plugin options from a case's `config.json`
+fn fixture_config(dir: &Path) -> ConfigKeyMap {
+    let Ok(text) = fs::read_to_string(dir.join("config.json")) else {
+        return ConfigKeyMap::new();
+    };
+    let json: serde_json::Value = serde_json::from_str(&text)
+        .unwrap_or_else(|error| panic!("{}: invalid config.json: {error}", dir.display()));
+    let name = dir.display().to_string();
+    match config_value(json, &name, "config.json") {
+        ConfigKeyValue::Object(map) => map,
+        _ => panic!("{}: config.json must hold an object", dir.display()),
+    }
+}
+
+/// Convert a JSON value of fixture `name`'s config, found under `key`
+fn config_value(json: serde_json::Value, name: &str, key: &str) -> ConfigKeyValue {
+    match json {
+        serde_json::Value::Null => ConfigKeyValue::Null,
+        serde_json::Value::Bool(value) => ConfigKeyValue::Bool(value),
+        serde_json::Value::Number(value) => ConfigKeyValue::Number(
+            value
+                .as_i64()
+                .and_then(|n| n.try_into().ok())
+                .unwrap_or_else(|| panic!("fixture {name}: bad config value for {key}")),
+        ),
+        serde_json::Value::String(value) => ConfigKeyValue::String(value),
+        serde_json::Value::Array(values) => ConfigKeyValue::Array(
+            values
+                .into_iter()
+                .map(|value| config_value(value, name, key))
+                .collect(),
+        ),
+        serde_json::Value::Object(entries) => {
+            let mut map = ConfigKeyMap::new();
+            for (key, value) in entries {
+                let value = config_value(value, name, &key);
+                map.insert(key, value);
+            }
+            ConfigKeyValue::Object(map)
+        }
+    }
+}
+
+/// Format a case's input, returning it unchanged when nothing was sorted
+fn format_fixture(fixture: &Fixture) -> Vec<u8> {
+    let mut handler = TailwindCssPluginHandler::new();
+    let config_result = handler.resolve_config(
+        fixture_config(&fixture.dir),
+        &GlobalConfiguration::default(),
+    );
+    assert!(
+        config_result.diagnostics.is_empty(),
+        "{}: {:?}",
+        fixture.dir.display(),
+        config_result.diagnostics
+    );
+
+    let input = fs::read(&fixture.input).unwrap();
+    let request = SyncFormatRequest {
+        file_path: &fixture.input,
+        file_bytes: input.clone(),
+        range: None,
+        config: &config_result.config,
+        config_id: FormatConfigId::from_raw(0),
+        token: &dprint_core::plugins::NullCancellationToken,
+    };
+    match handler.format(request, |_| Ok(None)) {
+        Ok(output) => output.unwrap_or(input),
+        Err(error) => panic!("{}: {error}", fixture.input.display()),
+    }
+}
+
+#[test]
+fn test_fixtures() {
+    let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some_and(|value| value != "0");
+    let mut fixtures = Vec::new();
+    find_fixtures(&fixtures_root(), &mut fixtures);
+    assert!(
+        !fixtures.is_empty(),
+        "no fixtures in {}",
+        fixtures_root().display()
+    );
+
+    let mut failures = Vec::new();
+    for fixture in &fixtures {
+        let output = format_fixture(fixture);
+        if update {
+            fs::write(&fixture.expected, &output).unwrap();
+            continue;
+        }
+        let Ok(expected) = fs::read(&fixture.expected) else {
+            failures.push(format!(
+                "{}: missing, run with UPDATE_SNAPSHOTS=1 to create it",
+                fixture.expected.display()
+            ));
+            continue;
+        };
+        if output != expected {
+            failures.push(format!(
+                "{}: output differs\n--- expected\n{}\n--- actual\n{}",
+                fixture.expected.display(),
+                String::from_utf8_lossy(&expected),
+                String::from_utf8_lossy(&output)
+            ));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} fixtures failed:\n\n{}",
+        failures.len(),
+        fixtures.len(),
+        failures.join("\n\n")
+    );
+}
@@ -6,15 +6,16 @@
 #[cfg(test)]
 mod format_aware_tests {
     use crate::config::Configuration;
+    use crate::diagnostics::{Event, SkipReason};
     use crate::extractor::ClassExtractor;
     use crate::parser::{FileFormat, FormatParser};
+    use std::collections::BTreeMap;
 
     fn create_test_config() -> Configuration {
         Configuration {
-            enabled: true,
-            tailwind_config: None,
             tailwind_functions: vec!["clsx".to_string(), "cn".to_string()],
             tailwind_attributes: vec!["class".to_string(), "className".to_string()],
+            ..Configuration::default()
         }
     }
 
@@ -260,9 +261,98 @@ export function Button({ active }) {
         );
 
         // Unknown formats should return None
-        assert_eq!(FileFormat::from_path("styles.css"), None);
-        assert_eq!(FileFormat::from_path("script.js"), None);
-        assert_eq!(FileFormat::from_path("data.json"), None);
+        assert_eq!(FileFormat::from_path("data.json"), Some(FileFormat::Json));
+
+        // Stylesheets
+        assert_eq!(FileFormat::from_path("styles.css"), Some(FileFormat::Css));
+
+        // Plain script modules
+        assert_eq!(FileFormat::from_path("src/App.js"), Some(FileFormat::Js));
+        assert_eq!(FileFormat::from_path("lib/util.mjs"), Some(FileFormat::Js));
+        assert_eq!(FileFormat::from_path("lib/util.cts"), Some(FileFormat::Ts));
+    }
+
+    #[test]
+    fn test_js_module_with_jsx() {
+        let config = create_test_config();
+        let extractor = ClassExtractor::new(
+            config.tailwind_functions.clone(),
+            config.tailwind_attributes.clone(),
+        );
+        let parser = FormatParser::new(extractor);
+
+        let content = r#"export function Card({ open }) {
+  return (
+    <div className="z-10 p-4">
+      <span className={cn("mt-2 flex", open && "block")} />
+    </div>
+  );
+}"#;
+
+        let matches = parser.parse(content, FileFormat::Js);
+        let class_strings: Vec<&str> = matches.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(class_strings, vec!["z-10 p-4", "mt-2 flex", "block"]);
+    }
+
+    #[test]
+    fn test_js_module_without_jsx() {
+        let config = create_test_config();
+        let extractor = ClassExtractor::new(
+            config.tailwind_functions.clone(),
+            config.tailwind_attributes.clone(),
+        );
+        let parser = FormatParser::new(extractor);
+
+        // No JSX: only function calls are extracted, and comparisons or
+        // markup inside strings don't count as JSX
+        let content = r#"const html = '<div class="z-10 p-4"></div>';
+const small = a < b && c > d;
+export const button = cn("z-10 p-4");
+export const props = { className: "z-10 p-4" };"#;
+
+        let matches = parser.parse(content, FileFormat::Js);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(&content[matches[0].start..matches[0].end], "z-10 p-4");
+        assert!(matches[0].start > content.find("cn(").unwrap());
+
+        // TypeScript modules never contain JSX
+        let content = r#"const el = <div className="z-10 p-4" />; cn("p-2")"#;
+        let matches = parser.parse(content, FileFormat::Ts);
+        let class_strings: Vec<&str> = matches.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(class_strings, vec!["p-2"]);
+    }
+
+    #[test]
+    fn test_format_detection_compound_extensions() {
+        assert_eq!(
+            FileFormat::from_path("src/Button.stories.tsx"),
+            Some(FileFormat::Tsx)
+        );
+        assert_eq!(
+            FileFormat::from_path("templates/page.html.twig"),
+            Some(FileFormat::Html)
+        );
+        assert_eq!(
+            FileFormat::from_path("views/welcome.blade.php"),
+            Some(FileFormat::Php)
+        );
+
+        let associations = BTreeMap::from([
+            ("blade.php".to_string(), FileFormat::Html),
+            ("twig".to_string(), FileFormat::Html),
+        ]);
+        assert_eq!(
+            FileFormat::from_path_with("views/welcome.blade.php", &associations),
+            Some(FileFormat::Html)
+        );
+        assert_eq!(
+            FileFormat::from_path_with("templates/base.twig", &associations),
+            Some(FileFormat::Html)
+        );
+        assert_eq!(
+            FileFormat::from_path_with("index.php", &associations),
+            Some(FileFormat::Php)
+        );
     }
 
     #[test]
@@ -411,4 +501,108 @@ const count = ref(0);
         assert_eq!(matches.len(), 1);
         assert_eq!(matches[0].content, "flex p-4");
     }
+
+    #[test]
+    fn test_vue_static_and_bound_class_are_distinct() {
+        let config = create_test_config();
+        let extractor = ClassExtractor::new(
+            config.tailwind_functions.clone(),
+            config.tailwind_attributes.clone(),
+        );
+        let parser = FormatParser::new(extractor);
+
+        let content = r#"
+<template>
+  <div class="static p-4 z-10" :class="dynamic">One</div>
+  <div :class="{ 'z-10 p-4': isOpen }" class="mt-2 flex">Two</div>
+</template>
+"#;
+
+        let matches = parser.parse(content, FileFormat::Vue);
+        let class_strings: Vec<&str> = matches.iter().map(|m| m.content.as_str()).collect();
+
+        // The static attribute is matched on its own, the bare identifier in
+        // the binding is never treated as a class list
+        assert_eq!(
+            class_strings,
+            vec!["static p-4 z-10", "z-10 p-4", "mt-2 flex"]
+        );
+        for m in &matches {
+            assert_eq!(&content[m.start..m.end], m.content);
+        }
+    }
+
+    #[test]
+    fn test_verbose_diagnostics_report_skipped_candidates() {
+        let config = create_test_config();
+        let extractor = ClassExtractor::new(
+            config.tailwind_functions.clone(),
+            config.tailwind_attributes.clone(),
+        )
+        .with_diagnostics(true);
+        let parser = FormatParser::new(extractor);
+
+        let content = r#"<script>
+const x = 1;
+</script>
+
+<!-- class="z-10 p-4" -->
+<div class="z-10 p-4 {open ? 'a' : 'b'"></div>
+<div class={clsx(`p-${size}`)}></div>
+"#;
+
+        let matches = parser.parse(content, FileFormat::Svelte);
+        assert!(matches.is_empty());
+
+        // Positions are reported in the whole file, not the markup section
+        let comment = content.find("class=\"z-10").unwrap();
+        let unbalanced = content.find("z-10 p-4 {").unwrap();
+        let template = content.find("p-${").unwrap();
+        assert_eq!(
+            parser.extractor().diagnostics().events(),
+            vec![
+                Event::Skipped {
+                    offset: comment,
+                    reason: SkipReason::OutsideTag
+                },
+                Event::Skipped {
+                    offset: unbalanced,
+                    reason: SkipReason::UnbalancedInterpolation
+                },
+                Event::Skipped {
+                    offset: template,
+                    reason: SkipReason::DynamicContent
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_verbose_diagnostics_tagged_template_outside_tag() {
+        let config = create_test_config();
+        let extractor = ClassExtractor::new(
+            config.tailwind_functions.clone(),
+            config.tailwind_attributes.clone(),
+        )
+        .with_templates(vec!["tw".to_string()])
+        .with_diagnostics(true);
+        let parser = FormatParser::new(extractor);
+
+        // HTML markup is scanned for attributes and function calls alike
+        let content = "<p>{tw`z-10 p-4`}</p>\n<!-- class=\"b a\" -->\n";
+
+        let matches = parser.parse(content, FileFormat::Html);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].content, "z-10 p-4");
+
+        // A tagged template is an expression, so only the attribute in the
+        // comment is reported as outside of a tag
+        assert_eq!(
+            parser.extractor().diagnostics().events(),
+            vec![Event::Skipped {
+                offset: content.find("class").unwrap(),
+                reason: SkipReason::OutsideTag
+            }]
+        );
+    }
 }
@@ -0,0 +1,230 @@
+use std::path::Path;
+
+use crate::config::Configuration;
+use crate::sorter::matches_pattern;
+
+/// Test files and snapshot directories left alone with `skipTestFiles`,
+/// since their assertions compare exact class strings
+pub const TEST_FILE_PATTERNS: &[&str] = &["*.test.*", "*.spec.*", "__tests__/", "__snapshots__/"];
+
+/// Gitignore-style path patterns from the `ignore` option
+///
+/// The plugin can't read ignore files from disk, so the patterns are given
+/// in the configuration. Each pattern is matched against the segments of the
+/// formatted file's path relative to the project root, so directories above
+/// the project never match:
+///
+/// - `legacy` matches a file or directory named `legacy` at any depth
+/// - `legacy/` only matches directories
+/// - `packages/legacy` or `/legacy`, with a slash before the end, is
+///   anchored to the project root
+/// - `*` matches within a segment and `**` matches any number of segments
+/// - `!pattern` re-includes paths excluded by an earlier pattern
+/// - blank lines and lines starting with `#` are skipped
+///
+/// As in `.gitignore`, the last matching pattern wins.
+#[derive(Debug, Clone, Default)]
+pub struct IgnorePatterns {
+    rules: Vec<IgnoreRule>,
+}
+
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    negated: bool,
+    directory_only: bool,
+    /// Matched from the project root rather than at any depth
+    anchored: bool,
+    segments: Vec<String>,
+}
+
+impl IgnorePatterns {
+    pub fn new(patterns: &[String]) -> Self {
+        let rules = patterns
+            .iter()
+            .filter_map(|pattern| IgnoreRule::parse(pattern))
+            .collect();
+        Self { rules }
+    }
+
+    /// Patterns of the `ignore` option, after `TEST_FILE_PATTERNS` when
+    /// `skipTestFiles` is enabled, so `!pattern` can re-include a test file
+    pub fn from_config(config: &Configuration) -> Self {
+        let test_files = TEST_FILE_PATTERNS
+            .iter()
+            .filter(|_| config.skip_test_files)
+            .map(|pattern| pattern.to_string());
+        Self::new(
+            &test_files
+                .chain(config.ignore.iter().cloned())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Whether the file at `path`, relative to the project root, is excluded
+    /// from formatting
+    pub fn is_ignored(&self, path: &str) -> bool {
+        let segments: Vec<&str> = path
+            .split(['/', '\\'])
+            .filter(|segment| !segment.is_empty() && *segment != ".")
+            .collect();
+
+        let mut ignored = false;
+        for rule in &self.rules {
+            // Only a rule that would flip the result needs to be checked
+            if rule.negated == ignored && rule.matches(&segments) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
+
+impl IgnoreRule {
+    fn parse(pattern: &str) -> Option<Self> {
+        let pattern = pattern.trim();
+        if pattern.is_empty() || pattern.starts_with('#') {
+            return None;
+        }
+
+        let (negated, pattern) = match pattern.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        let directory_only = pattern.ends_with('/');
+        let anchored = pattern.trim_end_matches('/').contains('/');
+        let segments: Vec<String> = pattern
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        (!segments.is_empty()).then_some(Self {
+            negated,
+            directory_only,
+            anchored,
+            segments,
+        })
+    }
+
+    /// Whether the pattern matches a run of consecutive path segments that
+    /// ends at the file itself or at one of its directories, starting at the
+    /// project root for an anchored pattern
+    fn matches(&self, path: &[&str]) -> bool {
+        let starts = if self.anchored { 0..1 } else { 0..path.len() };
+        starts.any(|start| {
+            (start + 1..=path.len()).any(|end| {
+                let is_file = end == path.len();
+                !(is_file && self.directory_only)
+                    && segments_match(&self.segments, &path[start..end])
+            })
+        })
+    }
+}
+
+/// `path` relative to the project `root`, or unchanged when the root isn't
+/// known or the file lies outside of it
+pub fn project_path<'a>(path: &'a Path, root: Option<&Path>) -> &'a Path {
+    root.and_then(|root| path.strip_prefix(root).ok())
+        .unwrap_or(path)
+}
+
+/// Match pattern segments against path segments, with `**` matching any
+/// number of segments
+fn segments_match(pattern: &[String], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|skip| segments_match(rest, &path[skip..]))
+        }
+        Some((first, rest)) => path.split_first().is_some_and(|(segment, path_rest)| {
+            matches_pattern(first, segment) && segments_match(rest, path_rest)
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(patterns: &[&str]) -> IgnorePatterns {
+        IgnorePatterns::new(&patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_name_matches_at_any_depth() {
+        let ignore = patterns(&["legacy"]);
+        assert!(ignore.is_ignored("packages/legacy/index.html"));
+        assert!(ignore.is_ignored("src/legacy"));
+        assert!(!ignore.is_ignored("src/legacy-app/index.html"));
+    }
+
+    #[test]
+    fn test_directory_only_pattern() {
+        let ignore = patterns(&["generated/"]);
+        assert!(ignore.is_ignored("src/generated/App.tsx"));
+        assert!(!ignore.is_ignored("src/generated"));
+    }
+
+    #[test]
+    fn test_multi_segment_and_wildcard_patterns() {
+        let ignore = patterns(&["/packages/legacy-*", "apps/**/vendor", "*.min.html"]);
+        assert!(ignore.is_ignored("packages/legacy-admin/src/App.vue"));
+        assert!(!ignore.is_ignored("packages/admin/src/App.vue"));
+        assert!(ignore.is_ignored("apps/web/lib/vendor/App.jsx"));
+        assert!(ignore.is_ignored("apps/vendor/App.jsx"));
+        assert!(ignore.is_ignored("public\\page.min.html"));
+        assert!(!ignore.is_ignored("public/page.html"));
+    }
+
+    #[test]
+    fn test_patterns_with_slash_are_anchored() {
+        let ignore = patterns(&["src/legacy", "/vendor", "**/fixtures/"]);
+        assert!(ignore.is_ignored("src/legacy/App.tsx"));
+        assert!(!ignore.is_ignored("packages/x/src/legacy/App.tsx"));
+        assert!(ignore.is_ignored("vendor/App.tsx"));
+        assert!(!ignore.is_ignored("src/vendor/App.tsx"));
+        assert!(ignore.is_ignored("src/fixtures/page.html"));
+        assert!(ignore.is_ignored("fixtures/page.html"));
+    }
+
+    #[test]
+    fn test_directories_above_project_root_never_match() {
+        let root = Path::new("/home/u/build/app");
+        let path = project_path(Path::new("/home/u/build/app/src/test/App.vue"), Some(root));
+        assert_eq!(path, Path::new("src/test/App.vue"));
+
+        let path = path.to_string_lossy();
+        assert!(!patterns(&["build", "app"]).is_ignored(&path));
+        assert!(patterns(&["test/"]).is_ignored(&path));
+
+        // Files outside the root, or without a known root, keep their path
+        let outside = Path::new("/tmp/page.html");
+        assert_eq!(project_path(outside, Some(root)), outside);
+        assert_eq!(project_path(outside, None), outside);
+    }
+
+    #[test]
+    fn test_skip_test_files() {
+        let mut config = Configuration::default();
+        assert!(!IgnorePatterns::from_config(&config).is_ignored("src/Button.test.tsx"));
+
+        config.skip_test_files = true;
+        config.ignore = vec!["!e2e.spec.ts".to_string()];
+        let ignore = IgnorePatterns::from_config(&config);
+        assert!(ignore.is_ignored("src/Button.test.tsx"));
+        assert!(ignore.is_ignored("src/Card.spec.jsx"));
+        assert!(ignore.is_ignored("src/__tests__/Card.jsx"));
+        assert!(ignore.is_ignored("src/__snapshots__/Card.html"));
+        assert!(!ignore.is_ignored("src/e2e.spec.ts"));
+        assert!(!ignore.is_ignored("src/Button.tsx"));
+    }
+
+    #[test]
+    fn test_negation_and_comments() {
+        let ignore = patterns(&["# legacy code", "", "legacy/", "!legacy/keep/"]);
+        assert!(ignore.is_ignored("legacy/old/App.tsx"));
+        assert!(!ignore.is_ignored("legacy/keep/App.tsx"));
+        assert!(!ignore.is_ignored("src/App.tsx"));
+        assert!(!patterns(&[]).is_ignored("src/App.tsx"));
+    }
+}
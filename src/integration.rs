@@ -4,63 +4,61 @@
 /// compatibility with other plugins in the formatting pipeline.
 use dprint_core::plugins::{FormatResult, SyncHostFormatRequest};
 
-/// Plugin compatibility checks and coordination
+use crate::config::{Configuration, FallbackExtraction};
+
+/// Policy deciding which files this plugin formats
 ///
-/// Ensures this plugin works well with other dprint plugins.
-#[allow(dead_code)]
-pub struct PluginCompatibility;
+/// Derived from the resolved configuration, so it agrees with the file
+/// extensions claimed from dprint. A file is formatted when its extension
+/// is claimed. Otherwise it is left alone when its extension is deferred to
+/// another plugin, and formatted with fallback extraction only when
+/// `fallbackExtraction` isn't `off`.
+#[derive(Debug, Clone, Default)]
+pub struct PluginCompatibility {
+    /// Extensions of the enabled formats, as advertised to dprint
+    claimed: Vec<String>,
+    /// Extensions left to other plugins
+    deferred: Vec<String>,
+    /// Whether other files are formatted with fallback extraction
+    allow_fallback: bool,
+}
 
 impl PluginCompatibility {
-    /// Check if this plugin should format a given file
-    ///
-    /// Some files might be handled by other plugins first,
-    /// and we should respect their formatting decisions.
-    #[allow(dead_code)]
-    pub fn should_format(file_path: &str) -> bool {
-        // Check file extension
-        let extension = file_path.split('.').next_back().unwrap_or("");
-
-        match extension.to_lowercase().as_str() {
-            // Primary supported formats
-            "html" | "htm" | "jsx" | "tsx" | "vue" | "svelte" | "astro" => true,
-
-            // JSON/YAML should not be formatted by this plugin
-            "json" | "jsonc" | "yaml" | "yml" => false,
-
-            // TypeScript/JavaScript without JSX - might contain utility functions
-            // but are primarily handled by dprint-plugin-typescript
-            "ts" | "js" | "mjs" | "cjs" => {
-                // We can still format if there are HTML-like structures
-                // but we should be cautious
-                true
-            }
-
-            // Markdown might contain code blocks with classes
-            "md" | "mdx" => true,
-
-            // Unknown formats - allow fallback extraction
-            _ => true,
+    pub fn new(config: &Configuration) -> Self {
+        Self {
+            claimed: config.file_extensions(),
+            deferred: config.defer_extensions.clone(),
+            allow_fallback: config.fallback_extraction != FallbackExtraction::Off,
         }
     }
 
-    /// Check if the plugin should defer to another plugin
+    /// Check if this plugin should format a given file
+    pub fn should_format(&self, file_path: &str) -> bool {
+        let extension = extension(file_path);
+        self.claimed.contains(&extension)
+            || (self.allow_fallback && !self.deferred.contains(&extension))
+    }
+
+    /// Check if the plugin should defer a given file to another plugin
     ///
-    /// Some files are better handled by specialized plugins.
+    /// Claimed extensions are never deferred, so opting in with e.g.
+    /// `jsonClassProperties` takes JSON files over.
     #[allow(dead_code)]
-    pub fn should_defer(file_path: &str) -> bool {
-        let extension = file_path.split('.').next_back().unwrap_or("");
-
-        match extension.to_lowercase().as_str() {
-            // These should be handled by their respective plugins first
-            "json" | "jsonc" => true, // dprint-plugin-json
-            "toml" => true,           // dprint-plugin-toml
-            "yaml" | "yml" => true,   // Other YAML plugins
-
-            _ => false,
-        }
+    pub fn should_defer(&self, file_path: &str) -> bool {
+        let extension = extension(file_path);
+        !self.claimed.contains(&extension) && self.deferred.contains(&extension)
     }
 }
 
+/// Lowercase last extension of a path's file name, or an empty string
+fn extension(file_path: &str) -> String {
+    let file_name = file_path.rsplit(['/', '\\']).next().unwrap_or(file_path);
+    file_name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_lowercase())
+        .unwrap_or_default()
+}
+
 /// Range formatting support
 ///
 /// Handles partial file formatting when only a specific range
@@ -211,32 +209,36 @@ impl PreservationGuard {
 mod tests {
     use super::*;
 
+    fn default_policy() -> PluginCompatibility {
+        PluginCompatibility::new(&Configuration::default())
+    }
+
     #[test]
     fn test_should_format_supported_extensions() {
-        assert!(PluginCompatibility::should_format("index.html"));
-        assert!(PluginCompatibility::should_format("App.jsx"));
-        assert!(PluginCompatibility::should_format("App.tsx"));
-        assert!(PluginCompatibility::should_format("App.vue"));
-        assert!(PluginCompatibility::should_format("App.svelte"));
-        assert!(PluginCompatibility::should_format("page.astro"));
+        assert!(default_policy().should_format("index.html"));
+        assert!(default_policy().should_format("App.jsx"));
+        assert!(default_policy().should_format("App.tsx"));
+        assert!(default_policy().should_format("App.vue"));
+        assert!(default_policy().should_format("App.svelte"));
+        assert!(default_policy().should_format("page.astro"));
     }
 
     #[test]
     fn test_should_not_format_json() {
-        assert!(!PluginCompatibility::should_format("config.json"));
-        assert!(!PluginCompatibility::should_format("tsconfig.jsonc"));
-        assert!(!PluginCompatibility::should_format("config.yaml"));
-        assert!(!PluginCompatibility::should_format("config.yml"));
+        assert!(!default_policy().should_format("config.json"));
+        assert!(!default_policy().should_format("tsconfig.jsonc"));
+        assert!(!default_policy().should_format("config.yaml"));
+        assert!(!default_policy().should_format("config.yml"));
     }
 
     #[test]
     fn test_should_defer_to_other_plugins() {
-        assert!(PluginCompatibility::should_defer("config.json"));
-        assert!(PluginCompatibility::should_defer("Cargo.toml"));
-        assert!(PluginCompatibility::should_defer("config.yaml"));
+        assert!(default_policy().should_defer("config.json"));
+        assert!(default_policy().should_defer("Cargo.toml"));
+        assert!(default_policy().should_defer("config.yaml"));
 
-        assert!(!PluginCompatibility::should_defer("App.jsx"));
-        assert!(!PluginCompatibility::should_defer("index.html"));
+        assert!(!default_policy().should_defer("App.jsx"));
+        assert!(!default_policy().should_defer("index.html"));
     }
 
     #[test]
@@ -292,15 +294,43 @@ mod tests {
 
     #[test]
     fn test_should_format_markdown() {
-        assert!(PluginCompatibility::should_format("README.md"));
-        assert!(PluginCompatibility::should_format("component.mdx"));
+        assert!(default_policy().should_format("README.md"));
+        assert!(default_policy().should_format("component.mdx"));
     }
 
     #[test]
     fn test_should_format_typescript_javascript() {
         // These might contain utility functions like clsx()
-        assert!(PluginCompatibility::should_format("utils.ts"));
-        assert!(PluginCompatibility::should_format("helper.js"));
-        assert!(PluginCompatibility::should_format("module.mjs"));
+        assert!(default_policy().should_format("utils.ts"));
+        assert!(default_policy().should_format("helper.js"));
+        assert!(default_policy().should_format("module.mjs"));
+    }
+
+    #[test]
+    fn test_policy_follows_configuration() {
+        let config = Configuration {
+            fallback_extraction: FallbackExtraction::Off,
+            defer_extensions: vec!["md".to_string()],
+            json_class_properties: vec!["wrapperClasses".to_string()],
+            ..Configuration::default()
+        };
+        let policy = PluginCompatibility::new(&config);
+
+        // Claimed extensions win over the defer list
+        assert!(policy.should_format("src/App.tsx"));
+        assert!(policy.should_format("tokens.JSON"));
+        assert!(!policy.should_defer("tokens.json"));
+        // Unclaimed extensions are deferred or need fallback
+        assert!(policy.should_defer("README.md"));
+        assert!(!policy.should_format("README.md"));
+        assert!(!policy.should_defer("templates/base.twig"));
+        assert!(!policy.should_format("templates/base.twig"));
+        assert!(!policy.should_format("Cargo.toml"));
+    }
+
+    #[test]
+    fn test_extension_of_file_name() {
+        assert_eq!(extension("src/v1.2/App.Vue"), "vue");
+        assert_eq!(extension("my.dir/Dockerfile"), "");
     }
 }
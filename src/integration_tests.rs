@@ -3,9 +3,16 @@ use dprint_core::configuration::{ConfigKeyMap, GlobalConfiguration};
 use dprint_core::plugins::FormatConfigId;
 
 fn format_text(file_text: &str, file_extension: &str) -> Option<String> {
+    format_text_with_config(file_text, file_extension, ConfigKeyMap::new())
+}
+
+fn format_text_with_config(
+    file_text: &str,
+    file_extension: &str,
+    config_map: ConfigKeyMap,
+) -> Option<String> {
     let mut handler = TailwindCssPluginHandler::new();
 
-    let config_map = ConfigKeyMap::new();
     let global_config = GlobalConfiguration::default();
     let config_result = handler.resolve_config(config_map, &global_config);
 
@@ -42,7 +49,7 @@ fn test_format_html_file() {
     assert!(result.is_some());
 
     let formatted = result.unwrap();
-    assert!(formatted.contains(r#"class="mt-2 p-4 z-10 bg-white""#));
+    assert!(formatted.contains(r#"class="z-10 mt-2 p-4 bg-white""#));
     // Verify rest of HTML is unchanged
     assert!(formatted.contains("<!DOCTYPE html>"));
     assert!(formatted.contains("<title>Test</title>"));
@@ -63,7 +70,7 @@ fn test_format_htm_file() {
 fn test_format_jsx_file() {
     let input = r#"export function Button() {
     return (
-        <button className="z-10 p-4 bg-blue-500 text-white rounded-lg">
+        <button className="p-4 z-10 bg-blue-500 text-white rounded-lg">
             Click me
         </button>
     );
@@ -73,7 +80,7 @@ fn test_format_jsx_file() {
     assert!(result.is_some());
 
     let formatted = result.unwrap();
-    assert!(formatted.contains(r#"className="p-4 z-10 text-white bg-blue-500 rounded-lg""#));
+    assert!(formatted.contains(r#"className="z-10 p-4 text-white bg-blue-500 rounded-lg""#));
     assert!(formatted.contains("export function Button()"));
     assert!(formatted.contains("Click me"));
 }
@@ -187,6 +194,287 @@ const title = "My Page";
     }
 }
 
+#[test]
+fn test_format_marko_file() {
+    let input = r#"class {
+  onCreate() {
+    this.state = { open: false };
+  }
+}
+
+<div class="p-4 z-10 ${state.open ? "mt-2 flex" : ""}">
+  <button class="p-4 z-10" on-click("toggle")>${input.label}</button>
+</div>
+
+style {
+  .z-10 { color: red; }
+}"#;
+
+    let formatted = format_text(input, "marko").unwrap();
+    assert_eq!(
+        formatted,
+        input
+            .replace("p-4 z-10", "z-10 p-4")
+            .replace("mt-2 flex", "flex mt-2")
+    );
+}
+
+#[test]
+fn test_format_php_file() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let input = r#"<?php get_header(); ?>
+<main class="p-4 z-10 <?php echo is_front_page() ? "mt-2" : ""; ?>">
+  <?php while (have_posts()) : the_post(); ?>
+    <h2 class="<?= $size ?> p-4 z-10"><?php the_title(); ?></h2>
+  <?php endwhile; ?>
+</main>
+<?php get_footer(); ?>"#;
+
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert("formatPhp".to_string(), ConfigKeyValue::Bool(true));
+    let formatted = format_text_with_config(input, "php", config_map).unwrap();
+    assert_eq!(formatted, input.replace("p-4 z-10", "z-10 p-4"));
+}
+
+#[test]
+fn test_format_razor_class_builders() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let razor = r#"<button class="p-4 z-10 @Extra" @onclick="Toggle">Toggle</button>
+<div class="@Css">@ChildContent</div>
+
+@code {
+    string Css => new CssBuilder("p-4 z-10")
+        .AddClass("p-4 z-10", when: IsOpen)
+        .Build();
+}"#;
+    // The attribute with a Razor expression is left alone
+    let formatted = format_text(razor, "razor").unwrap();
+    assert_eq!(formatted, razor.replace(r#"("p-4 z-10""#, r#"("z-10 p-4""#));
+
+    let code = r#"public partial class Card
+{
+    string Css => CssBuilder.Default("p-4 z-10").AddClass("p-4 z-10", Wide).Build();
+}"#;
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert("formatCSharp".to_string(), ConfigKeyValue::Bool(true));
+    config_map.insert(
+        "classBuilders".to_string(),
+        ConfigKeyValue::Array(vec![
+            ConfigKeyValue::String("Default".to_string()),
+            ConfigKeyValue::String("AddClass".to_string()),
+        ]),
+    );
+    let formatted = format_text_with_config(code, "cs", config_map).unwrap();
+    assert_eq!(formatted, code.replace("p-4 z-10", "z-10 p-4"));
+}
+
+#[test]
+fn test_format_kotlin_compose_classes() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let input = r##"@Composable
+fun Card(title: String) {
+    val hint = "p-4 z-10"
+    Div({ classes("p-4 z-10", "shadow") }) {
+        Span({ classes("""p-4 z-10""") }) { Text("p-4 z-10") }
+        Span({ classes("p-4 z-10 ${Styles.extra}") }) { Text(title) }
+    }
+}"##;
+    let expected = input
+        .replace(r#"classes("p-4 z-10","#, r#"classes("z-10 p-4","#)
+        .replace(r#"classes("""p-4 z-10"#, r#"classes("""z-10 p-4"#);
+
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert("formatKotlin".to_string(), ConfigKeyValue::Bool(true));
+    let formatted = format_text_with_config(input, "kt", config_map).unwrap();
+    assert_eq!(formatted, expected);
+}
+
+#[test]
+fn test_format_python_class_names() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let input = r#"from fasthtml.common import *
+
+@rt("/")
+def get():
+    title = "p-4 z-10"
+    return Div(
+        H1(title, cls="p-4 z-10"),
+        P("p-4 z-10", cls=f"p-4 z-10 {size}"),
+        cls='p-4 z-10',
+    )
+
+class NameForm(forms.Form):
+    name = forms.CharField(widget=forms.TextInput(attrs={"class": "p-4 z-10"}))
+"#;
+    let expected = input
+        .replace(r#"cls="p-4 z-10""#, r#"cls="z-10 p-4""#)
+        .replace("cls='p-4 z-10'", "cls='z-10 p-4'")
+        .replace(r#""class": "p-4 z-10""#, r#""class": "z-10 p-4""#);
+
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert("formatPython".to_string(), ConfigKeyValue::Bool(true));
+    let formatted = format_text_with_config(input, "py", config_map).unwrap();
+    assert_eq!(formatted, expected);
+}
+
+#[test]
+fn test_format_notebook_cells() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let input = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {"tags": ["class=\"p-4 z-10\""]},
+   "source": [
+    "# Report\n",
+    "<div\n",
+    "  class=\"p-4 z-10\">Summary</div>\n",
+    "<p class=\"z-10\u0020p-4\"></p>"
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 1,
+   "metadata": {},
+   "outputs": [{"output_type": "display_data", "data": {"text/html": ["<b class=\"p-4 z-10\"></b>"]}}],
+   "source": ["html = '<b class=\"p-4 z-10\"></b>'"]
+  },
+  {
+   "cell_type": "code",
+   "metadata": {},
+   "outputs": [],
+   "source": "%%html\n<span class=\"p-4 z-10\">é</span>"
+  }
+ ],
+ "metadata": {"kernelspec": {"name": "python3"}},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}"##;
+    let expected = input
+        .replace(
+            r#"  class=\"p-4 z-10\">Summary"#,
+            r#"  class=\"z-10 p-4\">Summary"#,
+        )
+        .replace(
+            r#"<span class=\"p-4 z-10\">"#,
+            r#"<span class=\"z-10 p-4\">"#,
+        );
+
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert("formatNotebooks".to_string(), ConfigKeyValue::Bool(true));
+    let formatted = format_text_with_config(input, "ipynb", config_map).unwrap();
+    assert_eq!(formatted, expected);
+}
+
+#[test]
+fn test_format_xml_with_configured_attributes() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let input = r#"<?xml version="1.0"?>
+<?xml-stylesheet href="<p class='p-4 z-10'>"?>
+<xsl:stylesheet version="1.0" xmlns:xsl="http://www.w3.org/1999/XSL/Transform">
+  <xsl:template match="/">
+    <div class="p-4 z-10" id="b a">
+      <script><![CDATA[ el.innerHTML = '<p class="p-4 z-10">'; ]]></script>
+    </div>
+    <xsl:text><![CDATA[<p class="p-4 z-10">]]></xsl:text>
+  </xsl:template>
+</xsl:stylesheet>"#;
+
+    // No attributes are sorted in XML by default
+    assert_eq!(format_text(input, "xsl"), None);
+
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert("formatXml".to_string(), ConfigKeyValue::Bool(true));
+    config_map.insert(
+        "xml.tailwindAttributes".to_string(),
+        ConfigKeyValue::Array(vec![ConfigKeyValue::String("class".to_string())]),
+    );
+    let formatted = format_text_with_config(input, "xsl", config_map).unwrap();
+    assert_eq!(
+        formatted,
+        input.replace(r#"<div class="p-4 z-10""#, r#"<div class="z-10 p-4""#)
+    );
+}
+
+#[test]
+fn test_format_storybook_args() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let input = r#"import type { Meta, StoryObj } from "@storybook/react";
+import { Button } from "./Button";
+
+const meta: Meta<typeof Button> = {
+  component: Button,
+  args: { className: "p-4 z-10 flex" },
+};
+export default meta;
+
+export const Large: StoryObj<typeof Button> = {
+  args: { label: "Large button", className: "p-4 z-10 flex" },
+  render: (args) => <Button {...args} />,
+};"#;
+
+    // Object literals are left alone by default
+    assert_eq!(format_text(input, "tsx"), None);
+
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert(
+        "sortObjectProperties".to_string(),
+        ConfigKeyValue::Bool(true),
+    );
+    let formatted = format_text_with_config(input, "tsx", config_map).unwrap();
+    assert_eq!(formatted, input.replace("p-4 z-10 flex", "z-10 flex p-4"));
+}
+
+#[test]
+fn test_format_mjml_file() {
+    let input = r#"<mjml>
+  <mj-head>
+    <mj-style>.z-10 { z-index: 10; }</mj-style>
+  </mj-head>
+  <mj-body>
+    <mj-section css-class="p-4 z-10">
+      <mj-column>
+        <mj-raw><div class="mt-2 flex"></div></mj-raw>
+      </mj-column>
+    </mj-section>
+  </mj-body>
+</mjml>"#;
+
+    let formatted = format_text(input, "mjml").unwrap();
+    assert_eq!(
+        formatted,
+        input
+            .replace("css-class=\"p-4 z-10\"", "css-class=\"z-10 p-4\"")
+            .replace("mt-2 flex", "flex mt-2")
+    );
+}
+
+#[test]
+fn test_format_riot_file() {
+    let input = r#"<my-card>
+  <div class="p-4 z-10 { props.wide ? 'w-full mx-auto' : '' }">{ props.title }</div>
+
+  <script>
+    export default { css: "p-4 z-10" }
+  </script>
+</my-card>"#;
+
+    let formatted = format_text(input, "riot").unwrap();
+    assert_eq!(
+        formatted,
+        input
+            .replacen("p-4 z-10", "z-10 p-4", 1)
+            .replace("w-full mx-auto", "mx-auto w-full")
+    );
+}
+
 #[test]
 fn test_format_with_clsx_function() {
     let input = r#"import clsx from 'clsx';
@@ -198,7 +486,7 @@ const classes = clsx("z-10 hover:shadow-lg p-4 bg-white");
     assert!(result.is_some());
 
     let formatted = result.unwrap();
-    assert!(formatted.contains(r#"clsx("p-4 z-10 bg-white hover:shadow-lg")"#));
+    assert!(formatted.contains(r#"clsx("z-10 p-4 bg-white hover:shadow-lg")"#));
 }
 
 #[test]
@@ -217,12 +505,12 @@ fn test_format_preserves_comments() {
     assert!(formatted.contains("<!-- This is a comment -->"));
     assert!(formatted.contains("<!-- Another comment -->"));
     assert!(formatted.contains("<!-- Final comment -->"));
-    assert!(formatted.contains(r#"class="mt-2 p-4 z-10""#));
+    assert!(formatted.contains(r#"class="z-10 mt-2 p-4""#));
 }
 
 #[test]
 fn test_format_preserves_whitespace() {
-    let input = r#"<div class="z-10 p-4">
+    let input = r#"<div class="p-4 z-10">
 
     <span class="text-red-500">Text</span>
 
@@ -239,7 +527,7 @@ fn test_format_preserves_whitespace() {
 
 #[test]
 fn test_format_multiple_classes_in_file() {
-    let input = r#"<div class="z-10 p-4">
+    let input = r#"<div class="p-4 z-10">
     <div class="hover:bg-blue-500 bg-red-500">
         <div class="!font-bold text-gray-900">Text</div>
     </div>
@@ -249,7 +537,7 @@ fn test_format_multiple_classes_in_file() {
     assert!(result.is_some());
 
     let formatted = result.unwrap();
-    assert!(formatted.contains(r#"class="p-4 z-10""#));
+    assert!(formatted.contains(r#"class="z-10 p-4""#));
     assert!(formatted.contains(r#"class="bg-red-500 hover:bg-blue-500""#));
     assert!(formatted.contains(r#"class="text-gray-900 !font-bold""#));
 }
@@ -265,7 +553,7 @@ fn test_format_empty_class() {
 
 #[test]
 fn test_format_already_sorted() {
-    let input = r#"<div class="mt-2 p-4 z-10 bg-white">Already sorted</div>"#;
+    let input = r#"<div class="z-10 mt-2 p-4 bg-white">Already sorted</div>"#;
 
     let result = format_text(input, "html");
     // Already sorted, so no changes
@@ -322,11 +610,50 @@ fn test_format_malformed_utf8() {
 
     let result = handler.format(request, |_| Ok(None));
     assert!(result.is_err());
+
+    // The error locates the invalid bytes
+    let error = result.unwrap_err().downcast::<FormatError>().unwrap();
+    assert_eq!(error.stage, FormatStage::Decode);
+    assert_eq!(error.format, Some(FileFormat::Html));
+    assert_eq!(error.range, Some(0..1));
+    assert!(error
+        .to_string()
+        .starts_with("test.html (Html): decoding failed (bytes 0..1): "));
+}
+
+#[test]
+fn test_format_lossy_utf8() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let mut handler = TailwindCssPluginHandler::new();
+
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert("lossyUtf8".to_string(), ConfigKeyValue::Bool(true));
+    let global_config = GlobalConfiguration::default();
+    let config_result = handler.resolve_config(config_map, &global_config);
+
+    // A Latin-1 byte in text content and one inside a class list
+    let file_bytes = b"<p class=\"p-4 z-10\">caf\xe9</p><p class=\"z-10 \xe9 p-4\"></p>".to_vec();
+
+    let request = SyncFormatRequest {
+        file_path: std::path::Path::new("test.html"),
+        file_bytes,
+        range: None,
+        config: &config_result.config,
+        config_id: FormatConfigId::from_raw(0),
+        token: &dprint_core::plugins::NullCancellationToken,
+    };
+
+    let result = handler.format(request, |_| Ok(None)).unwrap();
+    assert_eq!(
+        result.as_deref(),
+        Some(&b"<p class=\"z-10 p-4\">caf\xe9</p><p class=\"z-10 \xe9 p-4\"></p>"[..])
+    );
 }
 
 #[test]
 fn test_format_mixed_quotes() {
-    let input = r#"<div class="z-10 p-4" data-class='hover:bg-blue-500 bg-red-500'>Test</div>"#;
+    let input = r#"<div class="p-4 z-10" data-class='hover:bg-blue-500 bg-red-500'>Test</div>"#;
 
     let result = format_text(input, "html");
     assert!(result.is_some());
@@ -352,3 +679,1250 @@ fn test_format_with_line_breaks_in_class() {
         assert!(!formatted.contains("class=\"z-10\n"));
     }
 }
+
+#[test]
+fn test_format_qwik_class_signal_array() {
+    let input = r#"export const App = component$(() => {
+  return <div class={[{ active: isActive.value }, sig.value, "z-10 p-4 mt-2"]}>Hi</div>;
+});"#;
+
+    let result = format_text(input, "tsx");
+    assert!(result.is_some());
+
+    let formatted = result.unwrap();
+    assert!(
+        formatted.contains(r#"class={[{ active: isActive.value }, sig.value, "z-10 mt-2 p-4"]}"#)
+    );
+}
+
+#[test]
+fn test_format_qwik_class_object() {
+    let input = r#"<div class={{ "p-4 z-10": isOpen.value, hidden: !isOpen.value }}>Hi</div>"#;
+
+    let result = format_text(input, "tsx");
+    assert!(result.is_some());
+
+    let formatted = result.unwrap();
+    assert!(formatted.contains(r#"class={{ "z-10 p-4": isOpen.value, hidden: !isOpen.value }}"#));
+}
+
+#[test]
+fn test_format_html_ignores_class_text_outside_tags() {
+    let input = r#"<p class="p-4 z-10">Write class="p-4 z-10" on the element.</p>
+<script>const sample = '<div class="p-4 z-10">';</script>"#;
+
+    let result = format_text(input, "html");
+    assert!(result.is_some());
+
+    let formatted = result.unwrap();
+    assert!(formatted.starts_with(r#"<p class="z-10 p-4">"#));
+    assert!(formatted.contains(r#"Write class="p-4 z-10" on the element."#));
+    assert!(formatted.contains(r#"'<div class="p-4 z-10">'"#));
+}
+
+#[test]
+fn test_format_html_srcdoc_and_inline_svg() {
+    let input = r#"<iframe srcdoc='<div class="p-4 z-10">'></iframe>
+<svg class="h-4 w-4 z-10"><path class="stroke-2 fill-none" d="M0 0"/></svg>"#;
+
+    let result = format_text(input, "html");
+    assert!(result.is_some());
+
+    let formatted = result.unwrap();
+    assert!(formatted.contains(r#"srcdoc='<div class="p-4 z-10">'"#));
+    assert!(formatted.contains(r#"<svg class="z-10 h-4 w-4">"#));
+    assert!(formatted.contains(r#"<path class="fill-none stroke-2" d="M0 0"/>"#));
+}
+
+#[test]
+fn test_format_html_entity_encoded_quotes() {
+    let input = r#"<div :class="{ &quot;p-4 z-10&quot;: open }" class="before:content-[&quot;x&quot;] p-4 z-10"></div>"#;
+
+    let result = format_text(input, "html");
+    assert!(result.is_some());
+
+    let formatted = result.unwrap();
+    assert!(formatted.contains(r#":class="{ &quot;z-10 p-4&quot;: open }""#));
+    assert!(formatted.contains("&quot;x&quot;]"));
+    assert_eq!(formatted.len(), input.len());
+}
+
+#[test]
+fn test_format_jsx_clsx_logical_operands() {
+    let input = r#"<div className={clsx("p-4 z-10", isActive && "z-10 bg-blue-500 p-2", error || "text-gray-500 mt-2", size === "lg p-4" && "text-lg")} />"#;
+
+    let result = format_text(input, "jsx");
+    assert!(result.is_some());
+
+    let formatted = result.unwrap();
+    assert!(formatted.contains(r#"clsx("z-10 p-4""#));
+    assert!(!formatted.contains(r#"isActive && "z-10 bg-blue-500 p-2""#));
+    assert!(formatted.contains(r#"error || "mt-2 text-gray-500""#));
+    // Comparison operands are not class lists
+    assert!(formatted.contains(r#"size === "lg p-4""#));
+}
+
+#[test]
+fn test_format_jsx_spread_and_expression_attributes() {
+    let input =
+        r#"<Card {...rest} class={isOpen} active={open}className="p-4 z-10" title="p-4 z-10" />"#;
+
+    let result = format_text(input, "jsx");
+    assert!(result.is_some());
+
+    let formatted = result.unwrap();
+    assert_eq!(
+        formatted,
+        r#"<Card {...rest} class={isOpen} active={open}className="z-10 p-4" title="p-4 z-10" />"#
+    );
+}
+
+#[test]
+fn test_format_svelte_interpolated_class() {
+    let input = r#"<div class="p-4 z-10 {active ? 'flex z-10' : ''} btn-{size} m-2">x</div>"#;
+
+    let result = format_text(input, "svelte");
+    assert!(result.is_some());
+
+    // Static parts and string literals are sorted on their own; the
+    // interpolations and the glued `btn-{size}` stay in place
+    assert_eq!(
+        result.unwrap(),
+        r#"<div class="z-10 p-4 {active ? 'z-10 flex' : ''} btn-{size} m-2">x</div>"#
+    );
+}
+
+#[test]
+fn test_format_html_strings_opt_in() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let input = r#"app.get("/", (req, res) => res.send('<div class="p-4 z-10">' + clsx("p-4 z-10") + "</div>"));"#;
+
+    // Off by default: only the function call is sorted
+    let formatted = format_text(input, "js").unwrap();
+    assert!(formatted.contains(r#"'<div class="p-4 z-10">'"#));
+    assert!(formatted.contains(r#"clsx("z-10 p-4")"#));
+
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert(
+        "extractFromHtmlStrings".to_string(),
+        ConfigKeyValue::Bool(true),
+    );
+    let formatted = format_text_with_config(input, "js", config_map).unwrap();
+    assert!(formatted.contains(r#"'<div class="z-10 p-4">'"#));
+    assert!(formatted.contains(r#"clsx("z-10 p-4")"#));
+}
+
+#[test]
+fn test_format_post_process_sorts_host_output() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let mut handler = TailwindCssPluginHandler::new();
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert("postProcess".to_string(), ConfigKeyValue::Bool(true));
+    let global_config = GlobalConfiguration::default();
+    let config_result = handler.resolve_config(config_map, &global_config);
+
+    let input = r#"<div   class="p-4 z-10">Test</div>"#;
+    let format = |host_output: Option<&str>| {
+        let request = SyncFormatRequest {
+            file_path: std::path::Path::new("test.html"),
+            file_bytes: input.as_bytes().to_vec(),
+            range: None,
+            config: &config_result.config,
+            config_id: FormatConfigId::from_raw(0),
+            token: &dprint_core::plugins::NullCancellationToken,
+        };
+        // Stands in for the primary markup formatter
+        TailwindCssPluginHandler::new()
+            .format(request, |host_request| {
+                assert_eq!(host_request.file_bytes, input.as_bytes());
+                Ok(host_output.map(|output| output.as_bytes().to_vec()))
+            })
+            .unwrap()
+            .map(|bytes| String::from_utf8(bytes).unwrap())
+    };
+
+    // Classes are sorted on the host's output
+    assert_eq!(
+        format(Some(r#"<div class="p-4 z-10">Test</div>"#)).as_deref(),
+        Some(r#"<div class="z-10 p-4">Test</div>"#)
+    );
+    // Host changes are kept even when the classes are already sorted
+    assert_eq!(
+        format(Some(r#"<div class="p-4">Test</div>"#)).as_deref(),
+        Some(r#"<div class="p-4">Test</div>"#)
+    );
+    // Without host changes the input is sorted directly
+    assert_eq!(
+        format(None).as_deref(),
+        Some(r#"<div   class="z-10 p-4">Test</div>"#)
+    );
+}
+
+#[test]
+fn test_format_with_safelist() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let input = r#"<div class="legacy-grid card-shadow p-4 flex">x</div>"#;
+
+    // Unknown classes sort last and alphabetically
+    let formatted = format_text(input, "html").unwrap();
+    assert!(formatted.contains(r#"class="flex p-4 card-shadow legacy-grid""#));
+
+    // Safelisted classes come before the remaining unknown ones
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert(
+        "safelist".to_string(),
+        ConfigKeyValue::Array(vec![ConfigKeyValue::String("legacy-*".to_string())]),
+    );
+    let formatted = format_text_with_config(input, "html", config_map).unwrap();
+    assert!(formatted.contains(r#"class="flex p-4 legacy-grid card-shadow""#));
+}
+
+#[test]
+fn test_format_css_source_inline() {
+    let input = r#"@import "tailwindcss";
+@source inline("z-10 p-4 mt-2");
+@source not inline("underline flex");
+@source inline("{hover:,}bg-red-{50,{100..900..100},950}");
+/* @source inline("p-4 z-10"); */
+.card {
+  @apply p-4 z-10;
+}
+"#;
+
+    let formatted = format_text(input, "css").unwrap();
+    assert_eq!(
+        formatted,
+        r#"@import "tailwindcss";
+@source inline("z-10 mt-2 p-4");
+@source not inline("flex underline");
+@source inline("{hover:,}bg-red-{50,{100..900..100},950}");
+/* @source inline("p-4 z-10"); */
+.card {
+  @apply p-4 z-10;
+}
+"#
+    );
+
+    // Without `@source inline(...)` lists there is nothing to sort
+    assert_eq!(format_text(".a { color: red; }", "css"), None);
+}
+
+#[test]
+fn test_format_respects_ignore_patterns() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let mut handler = TailwindCssPluginHandler::new();
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert(
+        "ignore".to_string(),
+        ConfigKeyValue::Array(vec![
+            ConfigKeyValue::String("packages/legacy/".to_string()),
+            ConfigKeyValue::String("!packages/legacy/shared/".to_string()),
+        ]),
+    );
+    let config = handler
+        .resolve_config(config_map, &GlobalConfiguration::default())
+        .config;
+
+    // Patterns are relative to the directory dprint runs in
+    let root = std::env::current_dir().unwrap();
+    let mut format_path = |path: &str| {
+        let path = root.join(path);
+        let request = SyncFormatRequest {
+            file_path: &path,
+            file_bytes: br#"<div class="p-4 z-10"></div>"#.to_vec(),
+            range: None,
+            config: &config,
+            config_id: FormatConfigId::from_raw(0),
+            token: &dprint_core::plugins::NullCancellationToken,
+        };
+        handler.format(request, |_| Ok(None)).unwrap()
+    };
+
+    assert!(format_path("packages/legacy/src/index.html").is_none());
+    assert!(format_path("packages/legacy/shared/index.html").is_some());
+    assert!(format_path("packages/app/index.html").is_some());
+    assert!(format_path("apps/web/packages/legacy/index.html").is_some());
+}
+
+#[test]
+fn test_format_leaves_duplicate_class_attributes() {
+    let input = r#"<div class="p-4 z-10" class="mt-2 flex"><span class="p-4 z-10">x</span></div>"#;
+
+    let formatted = format_text(input, "html").unwrap();
+    assert_eq!(
+        formatted,
+        r#"<div class="p-4 z-10" class="mt-2 flex"><span class="z-10 p-4">x</span></div>"#
+    );
+}
+
+#[test]
+fn test_format_element_factory_calls() {
+    let input = r#"const el = React.createElement("div", { className: "p-4 z-10" }, h("span", { "class": "mt-2 flex" }));"#;
+
+    let formatted = format_text(input, "jsx").unwrap();
+    assert_eq!(
+        formatted,
+        r#"const el = React.createElement("div", { className: "z-10 p-4" }, h("span", { "class": "flex mt-2" }));"#
+    );
+}
+
+#[test]
+fn test_format_vue_tsx_script_block() {
+    let input = r#"<template>
+  <Card class="p-4 z-10" />
+</template>
+
+<script setup lang="tsx">
+const Card = () => <div class="p-4 z-10">{h("span", { class: "p-4 z-10" })}</div>;
+</script>"#;
+
+    let formatted = format_text(input, "vue").unwrap();
+    assert_eq!(formatted, input.replace("p-4 z-10", "z-10 p-4"));
+}
+
+#[test]
+fn test_format_svelte_component_class_props() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let input = r#"<Button class="p-4 z-10" />
+<svelte:element this={tag} class="p-4 z-10">x</svelte:element>"#;
+
+    let formatted = format_text(input, "svelte").unwrap();
+    assert_eq!(formatted, input.replace("p-4 z-10", "z-10 p-4"));
+
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert(
+        "sortComponentClasses".to_string(),
+        ConfigKeyValue::Bool(false),
+    );
+    let formatted = format_text_with_config(input, "svelte", config_map).unwrap();
+    assert_eq!(
+        formatted,
+        r#"<Button class="p-4 z-10" />
+<svelte:element this={tag} class="z-10 p-4">x</svelte:element>"#
+    );
+}
+
+#[test]
+fn test_format_astro_component_props() {
+    let input = r#"---
+import Card from "../components/Card.astro";
+const { class: className } = Astro.props;
+---
+<Card class="p-4 z-10" className="mt-2 flex" />
+<Card
+  title="Pricing"
+  class="p-4 z-10"
+  class:list={["p-4 z-10", { "mt-2 flex": active }]}
+>
+  <p class={className}>x</p>
+</Card>"#;
+
+    let formatted = format_text(input, "astro").unwrap();
+    assert_eq!(
+        formatted,
+        input
+            .replace("p-4 z-10", "z-10 p-4")
+            .replace("mt-2 flex", "flex mt-2")
+    );
+}
+
+#[test]
+fn test_format_fallback_extraction_modes() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let input = r#"{{ macro("p-4 z-10") }} class="p-4 z-10"
+<div class="p-4 z-10">{{ clsx("p-4 z-10") }}</div>"#;
+    let format_with = |mode: &str| {
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "fallbackExtraction".to_string(),
+            ConfigKeyValue::String(mode.to_string()),
+        );
+        format_text_with_config(input, "twig", config_map)
+    };
+
+    assert_eq!(format_with("off"), None);
+    assert_eq!(
+        format_with("attributes").unwrap(),
+        r#"{{ macro("p-4 z-10") }} class="p-4 z-10"
+<div class="z-10 p-4">{{ clsx("p-4 z-10") }}</div>"#
+    );
+    assert_eq!(
+        format_with("full").unwrap(),
+        r#"{{ macro("p-4 z-10") }} class="z-10 p-4"
+<div class="z-10 p-4">{{ clsx("z-10 p-4") }}</div>"#
+    );
+}
+
+#[test]
+fn test_format_placeholder_position() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let input = r#"<div class="... p-4 z-10"></div>"#;
+    let format_with = |position: &str| {
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "placeholderPosition".to_string(),
+            ConfigKeyValue::String(position.to_string()),
+        );
+        format_text_with_config(input, "html", config_map)
+    };
+
+    assert_eq!(
+        format_text(input, "html").unwrap(),
+        r#"<div class="z-10 p-4 ..."></div>"#
+    );
+    assert_eq!(
+        format_with("start").unwrap(),
+        r#"<div class="... z-10 p-4"></div>"#
+    );
+    assert_eq!(
+        format_with("preserve").unwrap(),
+        r#"<div class="... z-10 p-4"></div>"#
+    );
+    assert_eq!(
+        format_text(r#"<div class="z-10 p-4 ..."></div>"#, "html"),
+        None
+    );
+}
+
+#[test]
+fn test_format_markdown_code_blocks() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let input = r#"# Components
+
+<div class="p-4 z-10">Prose</div>
+
+```html
+<a class="p-4 z-10">Link</a>
+```
+
+```rust
+fn  main() {}
+```
+
+- Item
+
+  ```rust
+  fn  indented() {}
+  ```
+"#;
+    let mut handler = TailwindCssPluginHandler::new();
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert("markdownCodeBlocks".to_string(), ConfigKeyValue::Bool(true));
+    let config = handler
+        .resolve_config(config_map, &GlobalConfiguration::default())
+        .config;
+
+    let mut host_files = Vec::new();
+    let request = SyncFormatRequest {
+        file_path: std::path::Path::new("docs/README.md"),
+        file_bytes: input.as_bytes().to_vec(),
+        range: None,
+        config: &config,
+        config_id: FormatConfigId::from_raw(0),
+        token: &dprint_core::plugins::NullCancellationToken,
+    };
+    let output = handler
+        .format(request, |host_request| {
+            host_files.push(host_request.file_path.to_string_lossy().to_string());
+            let text = std::str::from_utf8(host_request.file_bytes).unwrap();
+            Ok(Some(
+                text.replace("fn  ", "fn ").trim_end().as_bytes().to_vec(),
+            ))
+        })
+        .unwrap()
+        .unwrap();
+
+    // Only the unindented block in a language this plugin doesn't parse
+    // goes to the host
+    assert_eq!(host_files, vec!["file.rs"]);
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        r#"# Components
+
+<div class="z-10 p-4">Prose</div>
+
+```html
+<a class="z-10 p-4">Link</a>
+```
+
+```rust
+fn main() {}
+```
+
+- Item
+
+  ```rust
+  fn  indented() {}
+  ```
+"#
+    );
+
+    // Without the option, Markdown is scanned as a whole as before
+    assert_eq!(format_text("```rust\nfn  main() {}\n```\n", "md"), None);
+}
+
+#[test]
+fn test_format_important_affects_order() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let input = r#"<div class="!mt-2 p-4 z-10"></div>"#;
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert(
+        "importantAffectsOrder".to_string(),
+        ConfigKeyValue::Bool(false),
+    );
+
+    assert_eq!(
+        format_text(input, "html").unwrap(),
+        r#"<div class="z-10 p-4 !mt-2"></div>"#
+    );
+    assert_eq!(
+        format_text_with_config(input, "html", config_map).unwrap(),
+        r#"<div class="z-10 !mt-2 p-4"></div>"#
+    );
+}
+
+#[test]
+fn test_format_whitespace() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let input = "<div class=\"\n  p-4\u{a0}z-10\n  flex\n\"></div>";
+
+    // Classes are split on any whitespace and joined with single spaces
+    assert_eq!(
+        format_text(input, "html").unwrap(),
+        "<div class=\"z-10 flex p-4\"></div>"
+    );
+
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert(
+        "whitespace".to_string(),
+        ConfigKeyValue::String("preserve".to_string()),
+    );
+    assert_eq!(
+        format_text_with_config(input, "html", config_map).unwrap(),
+        "<div class=\"\n  z-10\u{a0}flex\n  p-4\n\"></div>"
+    );
+}
+
+#[test]
+fn test_format_quote_style() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let input = r#"<a class='p-4 z-10'></a><b class='z-10 p-4'></b><i class='z-10 [content:"x"]'></i>
+<u class="p-4 z-10">{x ? 'p-4 z-10' : ''}</u>"#;
+
+    // Quotes are kept as written by default
+    assert_eq!(
+        format_text(input, "svelte").unwrap(),
+        r#"<a class='z-10 p-4'></a><b class='z-10 p-4'></b><i class='z-10 [content:"x"]'></i>
+<u class="z-10 p-4">{x ? 'p-4 z-10' : ''}</u>"#
+    );
+
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert(
+        "quoteStyle".to_string(),
+        ConfigKeyValue::String("double".to_string()),
+    );
+    assert_eq!(
+        format_text_with_config(input, "svelte", config_map).unwrap(),
+        r#"<a class="z-10 p-4"></a><b class="z-10 p-4"></b><i class='z-10 [content:"x"]'></i>
+<u class="z-10 p-4">{x ? 'p-4 z-10' : ''}</u>"#
+    );
+}
+
+#[test]
+fn test_format_web_component_templates() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let input = r#"@customElement("my-card")
+export class MyCard extends LitElement {
+  static styles = css`:host { display: block; }`;
+
+  render(): TemplateResult {
+    return html`<div class="p-4 z-10 ${this.open ? "flex" : ""}">
+      ${this.items.map((item) => html`<span class="p-4 z-10">${item}</span>`)}
+    </div>`;
+  }
+}
+
+export class Plain extends HTMLElement {
+  render() {
+    return `<p class="p-4 z-10"></p>`;
+  }
+}"#;
+
+    // Templates are left alone unless web component support is on
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert("formatScripts".to_string(), ConfigKeyValue::Bool(true));
+    assert_eq!(format_text_with_config(input, "ts", config_map), None);
+
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert("webComponents".to_string(), ConfigKeyValue::Bool(true));
+    let formatted = format_text_with_config(input, "ts", config_map).unwrap();
+    assert_eq!(formatted, input.replace("p-4 z-10", "z-10 p-4"));
+}
+
+#[test]
+fn test_format_multiline_jsx_attributes() {
+    // Attribute layouts produced by prettier and dprint-plugin-typescript
+    // when a line gets too long
+    let input = r#"export const Card = ({ open }) => (
+  <div
+    className={
+      "p-4 z-10"
+    }
+  >
+    <p className={(
+      "p-4 z-10"
+    )} />
+    <span
+      className=
+        "p-4 z-10"
+    />
+    <b
+      className={clsx(
+        "p-4 z-10",
+        open && "p-4 z-10",
+      )}
+    />
+    <i className={open ? (
+      `p-4 z-10`
+    ) : "p-4 z-10"} />
+  </div>
+);"#;
+
+    let formatted = format_text(input, "tsx").unwrap();
+    assert_eq!(formatted, input.replace("p-4 z-10", "z-10 p-4"));
+    // The output round-trips unchanged
+    assert_eq!(format_text(&formatted, "tsx"), None);
+}
+
+#[test]
+fn test_byte_order_mark_is_kept() {
+    let input = "\u{feff}---\nconst html = '<b class=\"p-4 z-10\"></b>';\n---\n<div class=\"p-4 z-10\"></div>";
+
+    // The frontmatter is still found behind the byte order mark
+    let formatted = format_text(input, "astro").unwrap();
+    assert_eq!(
+        formatted,
+        "\u{feff}---\nconst html = '<b class=\"p-4 z-10\"></b>';\n---\n<div class=\"z-10 p-4\"></div>"
+    );
+    assert_eq!(format_text(&formatted, "astro"), None);
+}
+
+#[test]
+fn test_crlf_line_endings_are_kept() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    // Only class lists are rewritten and the sorted classes are joined with
+    // spaces, so every line ending outside them is copied as written. Each
+    // fixture must format the same with LF and CRLF line endings
+    let option = |key: &str, value: ConfigKeyValue| {
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(key.to_string(), value);
+        config_map
+    };
+    let fixtures = [
+        (
+            "---\nconst a = 1;\n---\n<div class=\"p-4 z-10\">\n</div>\n",
+            "astro",
+            ConfigKeyMap::new(),
+        ),
+        (
+            "import x from \"y\"\nclass {\n  onCreate() {}\n}\nstyle {\n  .a {}\n}\n<div class=\"p-4 z-10\"/>\n",
+            "marko",
+            ConfigKeyMap::new(),
+        ),
+        (
+            "<script>\n  let a = \"</script>\";\n</script>\n<div\n  class=\"p-4 z-10\"\n></div>\n",
+            "svelte",
+            ConfigKeyMap::new(),
+        ),
+        (
+            "<template>\n  <div class=\"p-4 z-10\"></div>\n</template>\n",
+            "vue",
+            ConfigKeyMap::new(),
+        ),
+        (
+            "<div class=\"\n  p-4\n  z-10\n\"></div>\n",
+            "html",
+            ConfigKeyMap::new(),
+        ),
+        (
+            "<div class=\"\n  p-4\n  z-10\n\"></div>\n",
+            "html",
+            option("whitespace", ConfigKeyValue::String("preserve".to_string())),
+        ),
+        (
+            "button:\n  classes: p-4 z-10\n  other: |\n    p-4 z-10\n",
+            "yaml",
+            option(
+                "jsonClassProperties",
+                ConfigKeyValue::Array(vec![ConfigKeyValue::String("classes".to_string())]),
+            ),
+        ),
+        (
+            "Div(cls=\"p-4 z-10\")\nx = {\n  \"class\": \"p-4 z-10\"\n}\n",
+            "py",
+            option("formatPython", ConfigKeyValue::Bool(true)),
+        ),
+    ];
+
+    for (input, extension, config_map) in fixtures {
+        let lf = format_text_with_config(input, extension, config_map.clone())
+            .unwrap_or_else(|| panic!("{extension} fixture is unchanged"));
+        let crlf = format_text_with_config(&input.replace('\n', "\r\n"), extension, config_map);
+        assert_eq!(crlf, Some(lf.replace('\n', "\r\n")), "{extension}");
+    }
+}
+
+#[test]
+fn test_format_custom_elements() {
+    let input = r#"<sl-button variant="primary" part="base label" class="p-4 z-10"></sl-button>
+<button is="fancy-button" exportparts="label:btn-label" class="mt-2 flex"></button>
+<x-foo.bar class="p-4 z-10"></x-foo.bar>
+<math-ω
+  data-x="1"
+  class="p-4 z-10"></math-ω>"#;
+
+    assert_eq!(
+        format_text(input, "html").unwrap(),
+        r#"<sl-button variant="primary" part="base label" class="z-10 p-4"></sl-button>
+<button is="fancy-button" exportparts="label:btn-label" class="flex mt-2"></button>
+<x-foo.bar class="z-10 p-4"></x-foo.bar>
+<math-ω
+  data-x="1"
+  class="z-10 p-4"></math-ω>"#
+    );
+}
+
+#[test]
+fn test_format_self_closing_and_void_elements() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    // `/>` right after the closing quote, with and without a space
+    let input = r#"<img class="w-8 h-8"/><br class="hidden block"/>text class="p-4 z-10"
+<input src="a.png" class="w-8 h-8" /><hr class='mt-2 flex'/><p class="p-4 z-10">x</p>"#;
+    assert_eq!(
+        format_text(input, "html").unwrap(),
+        r#"<img class="h-8 w-8"/><br class="block hidden"/>text class="p-4 z-10"
+<input src="a.png" class="h-8 w-8" /><hr class='flex mt-2'/><p class="z-10 p-4">x</p>"#
+    );
+
+    // Requoting keeps the `/>` in place
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert(
+        "quoteStyle".to_string(),
+        ConfigKeyValue::String("double".to_string()),
+    );
+    assert_eq!(
+        format_text_with_config(
+            r#"<img class='w-8 h-8'/><br class='hidden'/>"#,
+            "html",
+            config_map
+        )
+        .unwrap(),
+        r#"<img class="h-8 w-8"/><br class="hidden"/>"#
+    );
+
+    for (input, extension, expected) in [
+        (
+            r#"const a = <img className="w-8 h-8"/>;"#,
+            "jsx",
+            r#"const a = <img className="h-8 w-8"/>;"#,
+        ),
+        (
+            r#"const a = <img className={clsx("w-8 h-8")}/>;"#,
+            "tsx",
+            r#"const a = <img className={clsx("h-8 w-8")}/>;"#,
+        ),
+        (
+            r#"<template><img class="w-8 h-8"/><br class="hidden block"/></template>"#,
+            "vue",
+            r#"<template><img class="h-8 w-8"/><br class="block hidden"/></template>"#,
+        ),
+        (
+            r#"<img class="w-8 h-8"/><p class="p-4 z-10" />"#,
+            "svelte",
+            r#"<img class="h-8 w-8"/><p class="z-10 p-4" />"#,
+        ),
+        (
+            r#"<img class="w-8 h-8"/>"#,
+            "astro",
+            r#"<img class="h-8 w-8"/>"#,
+        ),
+    ] {
+        assert_eq!(
+            format_text(input, extension).as_deref(),
+            Some(expected),
+            "{extension}"
+        );
+    }
+}
+
+#[test]
+fn test_format_wrap_classes() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let config = || {
+        let mut wrap = ConfigKeyMap::new();
+        wrap.insert("maxLineLength".to_string(), ConfigKeyValue::Number(40));
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("wrapClasses".to_string(), ConfigKeyValue::Object(wrap));
+        config_map
+    };
+
+    let input = r#"<div>
+  <p class="text-lg font-bold p-4 mt-2 flex items-center justify-between">x</p>
+  <p class="p-4 flex">y</p>
+</div>"#;
+    let expected = r#"<div>
+  <p class="flex items-center
+    justify-between mt-2 p-4 text-lg
+    font-bold">x</p>
+  <p class="flex p-4">y</p>
+</div>"#;
+    assert_eq!(
+        format_text_with_config(input, "html", config()).as_deref(),
+        Some(expected)
+    );
+    assert_eq!(format_text_with_config(expected, "html", config()), None);
+
+    // Sorted lists wrapped another way are wrapped again
+    let input =
+        "<p class=\"flex\n  items-center justify-between mt-2 p-4 text-lg font-bold\">x</p>";
+    assert_eq!(
+        format_text_with_config(input, "html", config()).unwrap(),
+        "<p class=\"flex items-center\n  justify-between mt-2 p-4 text-lg\n  font-bold\">x</p>"
+    );
+
+    // String literals in code stay on one line
+    let input =
+        r#"const a = <p className={clsx("text-lg font-bold p-4 mt-2 flex items-center")} />;"#;
+    assert_eq!(
+        format_text_with_config(input, "jsx", config()).unwrap(),
+        r#"const a = <p className={clsx("flex items-center mt-2 p-4 text-lg font-bold")} />;"#
+    );
+}
+
+#[test]
+fn test_format_group_separator() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let config = |whitespace: &str| {
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "groupSeparator".to_string(),
+            ConfigKeyValue::String("|".to_string()),
+        );
+        config_map.insert(
+            "whitespace".to_string(),
+            ConfigKeyValue::String(whitespace.to_string()),
+        );
+        config_map
+    };
+
+    // Separators already there are dropped and put back between groups
+    let input = r#"<div class="text-red-500 p-4 | flex mt-2 | absolute">x</div>"#;
+    let expected = r#"<div class="absolute | flex | mt-2 p-4 | text-red-500">x</div>"#;
+    assert_eq!(
+        format_text_with_config(input, "html", config("normalize")).as_deref(),
+        Some(expected)
+    );
+    assert_eq!(
+        format_text_with_config(expected, "html", config("normalize")),
+        None
+    );
+
+    // Sorted lists get separators, and lists of one group stay as they are
+    let input = "const a = clsx(\"flex  p-4\", \"mt-2 p-4\");";
+    assert_eq!(
+        format_text_with_config(input, "js", config("preserve")).unwrap(),
+        "const a = clsx(\"flex | p-4\", \"mt-2 p-4\");"
+    );
+}
+
+#[test]
+fn test_format_sort_within_groups_only() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let config = || {
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "sortWithinGroupsOnly".to_string(),
+            ConfigKeyValue::Bool(true),
+        );
+        config_map
+    };
+
+    let input = "<div class=\"\n  text-red-500 font-bold\n  p-4  mt-2\n\n  hover:bg-blue-500 bg-white\n\">x</div>\n<p class=\"p-4 flex\">y</p>";
+    let expected = "<div class=\"\n  font-bold text-red-500\n  mt-2 p-4\n\n  bg-white hover:bg-blue-500\n\">x</div>\n<p class=\"flex p-4\">y</p>";
+    assert_eq!(
+        format_text_with_config(input, "html", config()).as_deref(),
+        Some(expected)
+    );
+    assert_eq!(format_text_with_config(expected, "html", config()), None);
+
+    // Without the option the lines are merged into one sorted list
+    assert_eq!(
+        format_text(input, "html").unwrap(),
+        "<div class=\"mt-2 p-4 font-bold text-red-500 bg-white hover:bg-blue-500\">x</div>\n<p class=\"flex p-4\">y</p>"
+    );
+
+    // Template literals keep their lines too
+    let input = "const a = tw`\n  p-4 flex\n  text-lg font-bold\n`;";
+    assert_eq!(
+        format_text_with_config(input, "js", config()).unwrap(),
+        "const a = tw`\n  flex p-4\n  text-lg font-bold\n`;"
+    );
+}
+
+#[test]
+fn test_format_custom_format() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let string = |value: &str| ConfigKeyValue::String(value.to_string());
+    let pair = |open: &str, close: &str| ConfigKeyValue::Array(vec![string(open), string(close)]);
+    let mut njk = ConfigKeyMap::new();
+    njk.insert(
+        "extensions".to_string(),
+        ConfigKeyValue::Array(vec![string("njk")]),
+    );
+    njk.insert(
+        "skipBlocks".to_string(),
+        ConfigKeyValue::Array(vec![pair("{%", "%}"), pair("{{", "}}"), pair("{#", "#}")]),
+    );
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert(
+        "customFormats".to_string(),
+        ConfigKeyValue::Array(vec![ConfigKeyValue::Object(njk)]),
+    );
+
+    let input = r#"{# <p class="p-4 flex"> #}
+{% for item in items %}
+<li class="p-4 flex {{ 'font-bold' if item.active }} mt-2 z-10">{{ item.title }}</li>
+{% endfor %}"#;
+    let expected = r#"{# <p class="p-4 flex"> #}
+{% for item in items %}
+<li class="flex p-4 {{ 'font-bold' if item.active }} z-10 mt-2">{{ item.title }}</li>
+{% endfor %}"#;
+    assert_eq!(
+        format_text_with_config(input, "njk", config_map).as_deref(),
+        Some(expected)
+    );
+
+    // Without the custom format, the file isn't recognized
+    assert_eq!(FileFormat::from_path("page.njk"), None);
+}
+
+#[test]
+fn test_format_exclude_elements() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let config = || {
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert(
+            "excludeElements".to_string(),
+            ConfigKeyValue::Array(vec![
+                ConfigKeyValue::String("pre".to_string()),
+                ConfigKeyValue::String("script".to_string()),
+                ConfigKeyValue::String("markdown-block".to_string()),
+            ]),
+        );
+        config_map
+    };
+
+    let input = r#"<pre class="p-4 flex"><span class="p-4 flex">x</span></pre>
+<markdown-block><div class="p-4 flex"></div></markdown-block>
+<script>const a = clsx("p-4 flex");</script>
+<div class="p-4 flex"></div>"#;
+    assert_eq!(
+        format_text_with_config(input, "html", config()).unwrap(),
+        r#"<pre class="flex p-4"><span class="p-4 flex">x</span></pre>
+<markdown-block><div class="p-4 flex"></div></markdown-block>
+<script>const a = clsx("p-4 flex");</script>
+<div class="flex p-4"></div>"#
+    );
+
+    // Without the option every list is sorted
+    assert_eq!(
+        format_text(input, "html").unwrap(),
+        r#"<pre class="flex p-4"><span class="flex p-4">x</span></pre>
+<markdown-block><div class="flex p-4"></div></markdown-block>
+<script>const a = clsx("flex p-4");</script>
+<div class="flex p-4"></div>"#
+    );
+
+    // Vue templates are markup as well
+    let input =
+        r#"<template><pre><b class="p-4 flex"></b></pre><i class="p-4 flex"></i></template>"#;
+    assert_eq!(
+        format_text_with_config(input, "vue", config()).unwrap(),
+        r#"<template><pre><b class="p-4 flex"></b></pre><i class="flex p-4"></i></template>"#
+    );
+}
+
+#[test]
+fn test_attributify() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let config = || {
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("attributify".to_string(), ConfigKeyValue::Bool(true));
+        config_map
+    };
+
+    let input = r#"<div p-4 flex id="a" hover:bg-red-500 m-2 items-center>
+  <input disabled p-4 m-2 hidden />
+  <Button p-4 flex>x</Button>
+  <span class="p-4 flex" text-sm>p-4 flex</span>
+</div>"#;
+    assert_eq!(
+        format_text_with_config(input, "html", config()).unwrap(),
+        r#"<div flex p-4 id="a" items-center m-2 hover:bg-red-500>
+  <input disabled hidden m-2 p-4 />
+  <Button flex p-4>x</Button>
+  <span class="flex p-4" text-sm>p-4 flex</span>
+</div>"#
+    );
+
+    // Without the option only class attributes are sorted
+    assert_eq!(
+        format_text(input, "html").unwrap(),
+        r#"<div p-4 flex id="a" hover:bg-red-500 m-2 items-center>
+  <input disabled p-4 m-2 hidden />
+  <Button p-4 flex>x</Button>
+  <span class="flex p-4" text-sm>p-4 flex</span>
+</div>"#
+    );
+
+    // Markup without any class attribute is still scanned
+    assert_eq!(
+        format_text_with_config("<template><p m-2 flex></p></template>", "vue", config()).unwrap(),
+        "<template><p flex m-2></p></template>"
+    );
+}
+
+#[test]
+fn test_canonicalize() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let config = || {
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("canonicalize".to_string(), ConfigKeyValue::Bool(true));
+        config_map
+    };
+
+    let input =
+        r#"<div class="text-[#fff] mt-[16px] hover:bg-[#000]/50 w-[17px] bg-[#3b82f6]"></div>"#;
+    assert_eq!(
+        format_text_with_config(input, "html", config()).unwrap(),
+        r#"<div class="mt-4 w-[17px] text-white bg-[#3b82f6] hover:bg-black/50"></div>"#
+    );
+
+    // Lists already in order are still rewritten
+    let input = r#"<div class="mt-[1rem] p-4"></div>"#;
+    assert_eq!(
+        format_text_with_config(input, "html", config()).unwrap(),
+        r#"<div class="mt-4 p-4"></div>"#
+    );
+    assert!(format_text(input, "html").is_none());
+}
+
+#[test]
+fn test_merge_shorthands() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let config = |whitespace: &str| {
+        let mut config_map = ConfigKeyMap::new();
+        config_map.insert("mergeShorthands".to_string(), ConfigKeyValue::Bool(true));
+        config_map.insert(
+            "whitespace".to_string(),
+            ConfigKeyValue::String(whitespace.to_string()),
+        );
+        config_map
+    };
+
+    let input = r#"<div class="mt-4 flex mb-4 px-2 py-2 hover:ml-1 mr-1"></div>"#;
+    assert_eq!(
+        format_text_with_config(input, "html", config("normalize")).unwrap(),
+        r#"<div class="flex mr-1 my-4 hover:ml-1 p-2"></div>"#
+    );
+
+    // Separators can't be kept for classes that were merged away
+    let input = "<div class=\"mt-4\n  mb-4 flex\"></div>";
+    assert_eq!(
+        format_text_with_config(input, "html", config("preserve")).unwrap(),
+        r#"<div class="flex my-4"></div>"#
+    );
+    assert!(format_text(r#"<div class="flex mb-4 mt-4"></div>"#, "html").is_none());
+}
+
+#[test]
+fn test_selftest_file_reports_config() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let mut handler = TailwindCssPluginHandler::new();
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert("enabled".to_string(), ConfigKeyValue::Bool(false));
+    config_map.insert(
+        "tailwindFunctions".to_string(),
+        ConfigKeyValue::Array(vec![ConfigKeyValue::String("cn".to_string())]),
+    );
+    let config_result = handler.resolve_config(config_map, &GlobalConfiguration::default());
+
+    let mut format = |file_name: &str, input: &str| {
+        let request = SyncFormatRequest {
+            file_path: std::path::Path::new(file_name),
+            file_bytes: input.as_bytes().to_vec(),
+            range: None,
+            config: &config_result.config,
+            config_id: FormatConfigId::from_raw(0),
+            token: &dprint_core::plugins::NullCancellationToken,
+        };
+        handler
+            .format(request, |_| Ok(None))
+            .unwrap()
+            .map(|bytes| String::from_utf8(bytes).unwrap())
+    };
+
+    let report = format("site/dprint-tailwindcss-selftest.html", "").unwrap();
+    assert_eq!(
+        report,
+        format!(
+            "<!--
+dprint-tailwindcss-selftest.html
+version: {}
+enabled: false
+functions: cn
+attributes: class
+templates: tw, css
+strategy: standalone
+fallbackExtraction: full
+-->
+",
+            env!("CARGO_PKG_VERSION")
+        )
+    );
+
+    // The report is stable, and other files are still left alone
+    assert!(format("dprint-tailwindcss-selftest.html", &report).is_none());
+    assert!(format("index.html", r#"<div class="p-4 flex"></div>"#).is_none());
+}
+
+#[test]
+fn test_jsx_string_concatenation() {
+    let input = r#"<div className={"p-4 z-10 " + extra + " flex mt-2"} />"#;
+    assert_eq!(
+        format_text(input, "jsx").unwrap(),
+        r#"<div className={"z-10 p-4 " + extra + " flex mt-2"} />"#
+    );
+
+    let input = r#"<div className={" mt-2 flex " + extra} />"#;
+    assert_eq!(
+        format_text(input, "tsx").unwrap(),
+        r#"<div className={" flex mt-2 " + extra} />"#
+    );
+
+    // Vue bindings and function arguments are concatenated the same way
+    let input = r#"<template><p :class="'mt-2 flex ' + extra"></p></template>"#;
+    assert_eq!(
+        format_text(input, "vue").unwrap(),
+        r#"<template><p :class="'flex mt-2 ' + extra"></p></template>"#
+    );
+    let input = r#"const a = clsx(extra + " mt-2 flex");"#;
+    assert_eq!(
+        format_text(input, "js").unwrap(),
+        r#"const a = clsx(extra + " flex mt-2");"#
+    );
+}
+
+#[test]
+fn test_default_functions_include_cn_and_tailwind_merge() {
+    let input = r#"const a = cn("p-4 z-10");
+const b = twMerge("p-4 z-10", twJoin("mt-2 flex"));
+const c = cx("p-4 z-10");"#;
+    assert_eq!(
+        format_text(input, "ts").unwrap(),
+        r#"const a = cn("z-10 p-4");
+const b = twMerge("z-10 p-4", twJoin("flex mt-2"));
+const c = cx("z-10 p-4");"#
+    );
+}
+
+#[test]
+fn test_vue_long_form_bindings() {
+    let input = r#"<template>
+  <div v-bind:class="'p-4 z-10'" class="p-4 z-10"></div>
+  <li v-for="item in items" v-bind:class.prop="[item, item.active && 'p-4 z-10']"></li>
+  <li v-for="(flex, i) in list" v-bind:class="flex + ' p-4 z-10'"></li>
+</template>"#;
+    // Only string literals are class lists; names such as the `v-for` item
+    // are dynamic and stay where they are
+    assert_eq!(
+        format_text(input, "vue").unwrap(),
+        r#"<template>
+  <div v-bind:class="'z-10 p-4'" class="z-10 p-4"></div>
+  <li v-for="item in items" v-bind:class.prop="[item, item.active && 'z-10 p-4']"></li>
+  <li v-for="(flex, i) in list" v-bind:class="flex + ' z-10 p-4'"></li>
+</template>"#
+    );
+}
+
+#[test]
+fn test_html_template_elements() {
+    use dprint_core::configuration::ConfigKeyValue;
+
+    let input = r#"<html>
+<head>
+  <template id="card"><div class="p-4 z-10"><slot class="mt-2 flex"></slot></div></template>
+</head>
+<body>
+  <template id="list">
+    <template><li class="p-4 z-10"></li></template>
+    <style>.x { color: red; }</style>
+    <script>const end = '</template>';</script>
+    <ul class="p-4 z-10"></ul>
+  </template>
+  <x-card><template shadowrootmode="open"><p class="p-4 z-10"></p></template></x-card>
+</body>
+</html>"#;
+    let expected = r#"<html>
+<head>
+  <template id="card"><div class="z-10 p-4"><slot class="flex mt-2"></slot></div></template>
+</head>
+<body>
+  <template id="list">
+    <template><li class="z-10 p-4"></li></template>
+    <style>.x { color: red; }</style>
+    <script>const end = '</template>';</script>
+    <ul class="z-10 p-4"></ul>
+  </template>
+  <x-card><template shadowrootmode="open"><p class="z-10 p-4"></p></template></x-card>
+</body>
+</html>"#;
+    assert_eq!(format_text(input, "html").as_deref(), Some(expected));
+
+    // Excluding script and style content leaves template content sorted
+    let mut config_map = ConfigKeyMap::new();
+    config_map.insert(
+        "excludeElements".to_string(),
+        ConfigKeyValue::Array(vec![
+            ConfigKeyValue::String("script".to_string()),
+            ConfigKeyValue::String("style".to_string()),
+        ]),
+    );
+    assert_eq!(
+        format_text_with_config(input, "html", config_map).as_deref(),
+        Some(expected)
+    );
+}
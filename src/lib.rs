@@ -1,7 +1,15 @@
+mod analyze;
+mod cache;
+mod canonical;
 mod config;
+mod diagnostics;
 mod extractor;
+mod ignore;
 mod integration;
+mod lossy;
 mod parser;
+mod scanner;
+mod shorthand;
 mod sorter;
 
 use dprint_core::configuration::{ConfigKeyMap, GlobalConfiguration};
@@ -12,19 +20,38 @@ use dprint_core::plugins::{
     SyncHostFormatRequest, SyncPluginHandler,
 };
 
-use config::Configuration;
-use extractor::ClassExtractor;
+pub use analyze::{analyze, analyze_with, Report};
+use cache::{CachedSorter, SortCache};
+use canonical::canonicalize_classes;
+pub use config::{
+    Configuration, FallbackExtraction, PlaceholderPosition, QuoteStyle, Whitespace, WrapClasses,
+};
+use diagnostics::{Diagnostics, Event, FormatError, FormatStage, SkipReason, UNSTABLE_MESSAGE};
+use extractor::{ClassExtractor, ClassMatch};
+use ignore::{project_path, IgnorePatterns};
 use integration::PluginCompatibility;
-use parser::{FileFormat, FormatParser};
-use sorter::sort_classes;
+use lossy::LossyText;
+pub use parser::FileFormat;
+use parser::{code_fences, fence_format, FormatParser};
+use scanner::element_contents;
+use shorthand::merge_shorthands;
+use sorter::{
+    placeholders_first, placeholders_in_place, same_class_order, with_separators_of,
+    without_group_separators, Safelist,
+};
 
 #[allow(dead_code)]
-struct TailwindCssPluginHandler;
+struct TailwindCssPluginHandler {
+    /// Sorted class lists, reused across files and requests
+    cache: SortCache,
+}
 
 impl TailwindCssPluginHandler {
     #[allow(dead_code)]
     const fn new() -> Self {
-        TailwindCssPluginHandler
+        TailwindCssPluginHandler {
+            cache: SortCache::new(),
+        }
     }
 }
 
@@ -57,16 +84,23 @@ impl SyncPluginHandler<Configuration> for TailwindCssPluginHandler {
 
     fn check_config_updates(
         &self,
-        _message: dprint_core::plugins::CheckConfigUpdatesMessage,
+        message: dprint_core::plugins::CheckConfigUpdatesMessage,
     ) -> anyhow::Result<Vec<dprint_core::plugins::ConfigChange>> {
-        Ok(Vec::new())
+        Ok(config::config_updates(&message.config))
     }
 
     fn format(
         &mut self,
         request: SyncFormatRequest<Configuration>,
-        _format_with_host: impl FnMut(SyncHostFormatRequest) -> FormatResult,
+        mut format_with_host: impl FnMut(SyncHostFormatRequest) -> FormatResult,
     ) -> FormatResult {
+        // The self-test file shows the configuration that reached the plugin,
+        // so it is answered even when the plugin is disabled
+        if request.file_path.file_name() == Some(std::ffi::OsStr::new(SELFTEST_FILE_NAME)) {
+            let report = selftest_report(request.config);
+            return Ok((request.file_bytes != report.as_bytes()).then(|| report.into_bytes()));
+        }
+
         // Check if plugin is enabled
         if !request.config.enabled {
             return Ok(None);
@@ -74,74 +108,640 @@ impl SyncPluginHandler<Configuration> for TailwindCssPluginHandler {
 
         // Check plugin compatibility - should we format this file?
         let file_path = request.file_path.to_string_lossy();
-        if !PluginCompatibility::should_format(&file_path) {
+        // Ignore patterns are compiled from the request's configuration, and
+        // relative to the directory dprint runs in so directories above the
+        // project never match
+        let project_root = std::env::current_dir().ok();
+        let project_path = project_path(request.file_path, project_root.as_deref());
+        if IgnorePatterns::from_config(request.config).is_ignored(&project_path.to_string_lossy()) {
             return Ok(None);
         }
-
-        // If we should defer to another plugin, return None
-        if PluginCompatibility::should_defer(&file_path) {
+        // Deferred and, without fallback, unclaimed files are left to other
+        // plugins
+        if !PluginCompatibility::new(request.config).should_format(&file_path) {
             return Ok(None);
         }
 
-        // Convert file bytes to string
-        let file_text = String::from_utf8(request.file_bytes.to_vec())
-            .map_err(|e| anyhow::anyhow!("Failed to parse file as UTF-8: {}", e))?;
+        // Errors name the file and format so reports can be diagnosed
+        let format = FileFormat::from_path_with(&file_path, &request.config.format_as);
 
-        // Determine file format from path
-        let format = FileFormat::from_path(&file_path);
+        // As a post-processor, let the primary formatter for this file run
+        // first and sort classes on its output
+        let host_output = if request.config.post_process {
+            format_with_host(SyncHostFormatRequest {
+                file_path: request.file_path,
+                file_bytes: &request.file_bytes,
+                range: None,
+                override_config: &ConfigKeyMap::new(),
+            })
+            .map_err(|error| {
+                FormatError::new(
+                    &file_path,
+                    format,
+                    FormatStage::HostFormat,
+                    format!("{error:#}"),
+                )
+            })?
+        } else {
+            None
+        };
+        let file_bytes = host_output.as_deref().unwrap_or(&request.file_bytes);
 
-        // Create extractor with configured function and attribute names
-        let extractor = ClassExtractor::new(
-            request.config.tailwind_functions.clone(),
-            request.config.tailwind_attributes.clone(),
-        );
+        // Validate UTF-8 in place; the file text is only ever borrowed
+        // unless `lossyUtf8` lets invalid bytes through
+        let decoded;
+        let (file_text, lossy) = match std::str::from_utf8(file_bytes) {
+            Ok(text) => (text, None),
+            Err(_) if request.config.lossy_utf8 => {
+                decoded = LossyText::decode(file_bytes);
+                (decoded.text.as_str(), Some(&decoded))
+            }
+            Err(e) => {
+                let invalid = e.valid_up_to()..e.valid_up_to() + e.error_len().unwrap_or(0);
+                let message = format!("Failed to parse file as UTF-8: {e}");
+                return Err(
+                    FormatError::new(&file_path, format, FormatStage::Decode, message)
+                        .at_bytes(invalid)
+                        .into(),
+                );
+            }
+        };
 
-        // Extract all class strings using format-aware parsing
-        let matches = if let Some(format) = format {
-            let parser = FormatParser::new(extractor);
-            parser.parse(&file_text, format)
-        } else {
-            // Fallback to basic extraction if format is unknown
-            let mut matches = extractor.extract_from_attributes(&file_text);
-            let function_matches = extractor.extract_from_functions(&file_text);
-            matches.extend(function_matches);
-            matches
+        // A UTF-8 byte order mark isn't content: parse the text behind it, so
+        // offsets and start-of-file checks such as Astro's frontmatter fence
+        // see the text as written, and put it back in front of the output
+        let (bom, file_text) = match file_text.strip_prefix(BOM) {
+            Some(text) => (BOM, text),
+            None => ("", file_text),
         };
 
-        // If no matches found, return unchanged
-        if matches.is_empty() {
-            return Ok(None);
+        // Markdown code blocks in other languages go to the host's plugins
+        let code_blocks = match format {
+            Some(FileFormat::Markdown) if lossy.is_none() => {
+                format_code_blocks(&file_path, format, file_text, &mut format_with_host)?
+            }
+            _ => None,
+        };
+        let file_text = code_blocks.as_deref().unwrap_or(file_text);
+
+        match sort_file_classes(request.config, &file_path, file_text, &mut self.cache) {
+            Some(sorted) => match lossy {
+                // Put the invalid bytes back, or keep the file as it was
+                Some(lossy) => Ok(lossy.encode(&format!("{bom}{sorted}")).or(host_output)),
+                None => Ok(Some([bom.as_bytes(), sorted.as_bytes()].concat())),
+            },
+            None if code_blocks.is_some() => {
+                Ok(Some([bom.as_bytes(), file_text.as_bytes()].concat()))
+            }
+            // Keep the primary formatter's changes even if no class moved
+            None => Ok(host_output),
         }
+    }
+}
+
+/// UTF-8 byte order mark some editors put at the start of a file
+const BOM: &str = "\u{feff}";
+
+/// Name of the file whose formatted text is `selftest_report`
+const SELFTEST_FILE_NAME: &str = "dprint-tailwindcss-selftest.html";
+
+/// HTML comment listing the configuration the plugin resolved, written over
+/// the content of `dprint-tailwindcss-selftest.html`
+///
+/// Lists show the names used for HTML files, and `strategy` tells whether
+/// classes are sorted on the file as written or on the output of the
+/// primary formatter. The report only depends on the configuration, so
+/// formatting the file again leaves it as it is.
+fn selftest_report(config: &Configuration) -> String {
+    let html = Some(FileFormat::Html);
+    let strategy = if config.post_process {
+        "post-process"
+    } else {
+        "standalone"
+    };
+    let fallback = match config.fallback_extraction {
+        FallbackExtraction::Off => "off",
+        FallbackExtraction::Attributes => "attributes",
+        FallbackExtraction::Full => "full",
+    };
+    let lines = [
+        format!("version: {}", env!("CARGO_PKG_VERSION")),
+        format!("enabled: {}", config.enabled),
+        format!("functions: {}", config.functions_for(html).join(", ")),
+        format!("attributes: {}", config.attributes_for(html).join(", ")),
+        format!("templates: {}", config.tailwind_templates.join(", ")),
+        format!("strategy: {strategy}"),
+        format!("fallbackExtraction: {fallback}"),
+    ];
+    format!("<!--\n{SELFTEST_FILE_NAME}\n{}\n-->\n", lines.join("\n"))
+}
 
-        // Sort and replace each class string
-        let mut result = file_text.clone();
-        let mut offset: i32 = 0;
+/// Format the fenced code blocks of a Markdown file that are tagged with a
+/// language this plugin doesn't parse with the host's plugins, returning
+/// `None` when no block changed
+///
+/// Blocks are sent as `file.<ext>`, as dprint-plugin-markdown does. Indented
+/// blocks, as in list items, are left alone since their lines carry the
+/// indentation of the list.
+fn format_code_blocks(
+    file_path: &str,
+    format: Option<FileFormat>,
+    file_text: &str,
+    format_with_host: &mut impl FnMut(SyncHostFormatRequest) -> FormatResult,
+) -> anyhow::Result<Option<String>> {
+    let mut result: Option<String> = None;
+    let mut last_end = 0;
 
-        for class_match in matches {
-            let sorted = sort_classes(&class_match.content);
+    for fence in code_fences(file_text) {
+        let Some(extension) = code_block_extension(fence.lang) else {
+            continue;
+        };
+        if fence.indented || fence.body.is_empty() || fence_format(fence.lang).is_some() {
+            continue;
+        }
+        let body = &file_text[fence.body.clone()];
+        let host_file = format!("file.{extension}");
+        let formatted = format_with_host(SyncHostFormatRequest {
+            file_path: std::path::Path::new(&host_file),
+            file_bytes: body.as_bytes(),
+            range: None,
+            override_config: &ConfigKeyMap::new(),
+        })
+        .map_err(|error| {
+            FormatError::new(
+                file_path,
+                format,
+                FormatStage::HostFormat,
+                format!("{error:#}"),
+            )
+            .at_bytes(fence.body.clone())
+        })?;
+        let Some(formatted) = formatted.and_then(|bytes| String::from_utf8(bytes).ok()) else {
+            continue;
+        };
+        if formatted == body {
+            continue;
+        }
 
-            // Only replace if sorting changed the content
-            if sorted != class_match.content {
-                let start = (class_match.start as i32 + offset) as usize;
-                let end = (class_match.end as i32 + offset) as usize;
+        // The closing fence must stay on a line of its own
+        let output = result.get_or_insert_with(|| String::with_capacity(file_text.len()));
+        output.push_str(&file_text[last_end..fence.body.start]);
+        output.push_str(&formatted);
+        if !formatted.is_empty() && !formatted.ends_with('\n') {
+            output.push('\n');
+        }
+        last_end = fence.body.end;
+    }
 
-                result.replace_range(start..end, &sorted);
+    let Some(mut output) = result else {
+        return Ok(None);
+    };
+    output.push_str(&file_text[last_end..]);
+    Ok(Some(output))
+}
 
-                // Update offset for next replacements
-                offset += sorted.len() as i32 - class_match.content.len() as i32;
+/// Extension to send a code block with its language tag as, such as `rs`
+/// for `rust`, or `None` for blocks without a usable tag
+fn code_block_extension(lang: &str) -> Option<String> {
+    let lang = lang.to_lowercase();
+    if lang.is_empty()
+        || !lang
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return None;
+    }
+    let extension = match lang.as_str() {
+        "rust" => "rs",
+        "shell" | "bash" | "zsh" => "sh",
+        "markdown" => "md",
+        "golang" => "go",
+        other => other,
+    };
+    Some(extension.to_string())
+}
+
+/// Sort the classes in a file, returning `None` when nothing changed
+fn sort_file_classes(
+    config: &Configuration,
+    file_path: &str,
+    file_text: &str,
+    cache: &mut SortCache,
+) -> Option<String> {
+    // Determine file format from path and configured associations
+    let format = FileFormat::from_path_with(file_path, &config.format_as);
+
+    let parser = file_parser(config, format);
+    let matches = extract_matches(config, &parser, format, file_text);
+
+    // Sort each class string and rebuild the file around the replacements
+    let mut sorter = cache.sorter(
+        Safelist::new(config.safelist.clone()),
+        config.sort_options(),
+    );
+    let diagnostics = parser.extractor().diagnostics();
+    let result = rewrite_matches(
+        file_text,
+        matches,
+        &mut sorter,
+        RewriteOptions::new(config),
+        diagnostics,
+    );
+
+    // A rewrite at a wrong offset would corrupt the file, and sorting the
+    // output again would then change it. Keep the original text instead
+    let result = result.filter(|sorted| {
+        let stable = !(config.verify_output || cfg!(debug_assertions))
+            || is_fixed_point(config, format, sorted, cache);
+        if !stable {
+            let error = FormatError::new(file_path, format, FormatStage::Verify, UNSTABLE_MESSAGE);
+            let error = match unstable_match(config, format, sorted, cache) {
+                Some((index, range)) => error.at_match(index, range),
+                None => error,
+            };
+            if !config.verbose {
+                diagnostics::warn(&error.to_string());
             }
+            diagnostics.record(Event::Unstable(error));
         }
+        stable
+    });
+
+    // Explain what was found and skipped when verbose logging is on
+    diagnostics.emit(file_path, file_text);
+
+    result
+}
+
+/// Whether sorting the classes of already sorted text leaves it unchanged
+fn is_fixed_point(
+    config: &Configuration,
+    format: Option<FileFormat>,
+    text: &str,
+    cache: &mut SortCache,
+) -> bool {
+    let parser = file_parser(config, format);
+    let matches = extract_matches(config, &parser, format, text);
+    let mut sorter = cache.sorter(
+        Safelist::new(config.safelist.clone()),
+        config.sort_options(),
+    );
+    rewrite_matches(
+        text,
+        matches,
+        &mut sorter,
+        RewriteOptions::new(config),
+        parser.extractor().diagnostics(),
+    )
+    .is_none()
+}
+
+/// Index and range of the first class list in already sorted text that
+/// sorting changes again, to point an unstable output report at
+fn unstable_match(
+    config: &Configuration,
+    format: Option<FileFormat>,
+    text: &str,
+    cache: &mut SortCache,
+) -> Option<(usize, std::ops::Range<usize>)> {
+    let parser = file_parser(config, format);
+    let matches = extract_matches(config, &parser, format, text);
+    let mut sorter = cache.sorter(
+        Safelist::new(config.safelist.clone()),
+        config.sort_options(),
+    );
+    matches
+        .into_iter()
+        .enumerate()
+        .find_map(|(index, class_match)| {
+            let range = class_match.start..class_match.end;
+            rewrite_matches(
+                text,
+                vec![class_match],
+                &mut sorter,
+                RewriteOptions::new(config),
+                &Diagnostics::default(),
+            )
+            .map(|_| (index, range))
+        })
+}
+
+/// Create the parser for files of the given format
+fn file_parser(config: &Configuration, format: Option<FileFormat>) -> FormatParser {
+    // Create extractor with configured function names and the attribute
+    // names for this format
+    let extractor = ClassExtractor::new(
+        config.functions_for(format),
+        config.attributes_for(format).to_vec(),
+    )
+    .with_templates(config.tailwind_templates.clone())
+    .with_factories(config.element_factories.clone())
+    .with_builders(config.class_builders.clone())
+    .with_component_classes(config.sort_component_classes)
+    .with_attributify(config.attributify)
+    .with_object_properties(config.sort_object_properties)
+    .with_data_properties(config.json_class_properties.clone())
+    .with_python_names(config.python_class_names.clone())
+    .with_diagnostics(config.verbose);
+    FormatParser::new(extractor).with_custom_formats(config.custom_formats.clone())
+}
+
+/// Extract the class lists of a file
+fn extract_matches(
+    config: &Configuration,
+    parser: &FormatParser,
+    format: Option<FileFormat>,
+    file_text: &str,
+) -> Vec<ClassMatch> {
+    let extractor = parser.extractor();
+    let diagnostics = extractor.diagnostics();
 
-        // Return the formatted text if changes were made
-        if result != file_text {
-            Ok(Some(result.into_bytes()))
+    // Bail out early if no configured attribute or function name appears.
+    // Stylesheets only have `@source inline(...)` lists
+    let has_candidates = match format {
+        Some(FileFormat::Css) => file_text.contains("@source"),
+        Some(FileFormat::Json | FileFormat::Yaml) => config
+            .json_class_properties
+            .iter()
+            .any(|name| file_text.contains(name.as_str())),
+        Some(FileFormat::Python) => config
+            .python_class_names
+            .iter()
+            .any(|name| file_text.contains(name.as_str())),
+        _ => extractor.has_candidates(file_text),
+    };
+    if !has_candidates {
+        diagnostics.record(Event::NoCandidates);
+        return Vec::new();
+    }
+
+    // Markup built in string literals of script code is opt-in
+    let mut html_string_matches =
+        if config.extract_from_html_strings && format.is_none_or(FileFormat::is_script) {
+            extractor.extract_from_html_strings(file_text)
         } else {
-            Ok(None)
+            Vec::new()
+        };
+    // So is web component markup in templates
+    if config.web_components && format.is_none_or(FileFormat::is_script) {
+        html_string_matches.extend(extractor.extract_from_markup_templates(file_text));
+    }
+
+    // Extract all class strings using format-aware parsing
+    let mut matches = if let Some(format) = format {
+        parser.parse(file_text, format)
+    } else {
+        // Fallback to basic extraction if format is unknown
+        match config.fallback_extraction {
+            FallbackExtraction::Off => Vec::new(),
+            FallbackExtraction::Attributes => extractor.extract_attributes_from_markup(file_text),
+            FallbackExtraction::Full => extractor.extract_all(file_text),
         }
+    };
+
+    // The same class list may be found by several of the passes
+    matches.extend(html_string_matches);
+    matches.sort_by_key(|m| (m.start, m.end));
+    matches.dedup_by(|a, b| a.start == b.start && a.end == b.end);
+    // The content of excluded elements stays as it is, while their own
+    // attributes are still sorted
+    if format.is_some_and(FileFormat::is_markup) && !config.exclude_elements.is_empty() {
+        let excluded = element_contents(file_text, &config.exclude_elements);
+        matches.retain(|m| {
+            let inside = excluded.iter().any(|range| range.contains(&m.start));
+            if inside {
+                diagnostics.skipped(m.start, SkipReason::ExcludedElement);
+            }
+            !inside
+        });
     }
+    // Invalid bytes decoded by `lossyUtf8` can only be put back if the class
+    // lists holding them stay as they are
+    if config.lossy_utf8 {
+        matches.retain(|m| {
+            let valid = !m.content.contains(char::REPLACEMENT_CHARACTER);
+            if !valid {
+                diagnostics.skipped(m.start, SkipReason::InvalidUtf8);
+            }
+            valid
+        });
+    }
+    diagnostics.record(Event::Found(matches.len()));
+
+    matches
 }
 
-// Generate the WASM plugin code
+/// Rebuild the file text with every class match replaced by its sorted form
+///
+/// Matches are walked in source order and the output is assembled from the
+/// unchanged slices between them, so each byte is copied exactly once. The
+/// output buffer is only allocated once the first match actually changes.
+/// Overlapping matches and matches that don't describe their own range of
+/// the file are skipped, so one bad match never fails or corrupts the rest
+/// of the file. `options` decide how each sorted list is written back.
+/// Returns `None` when nothing changed.
+fn rewrite_matches(
+    file_text: &str,
+    mut matches: Vec<ClassMatch>,
+    sorter: &mut CachedSorter,
+    options: RewriteOptions,
+    diagnostics: &Diagnostics,
+) -> Option<String> {
+    let RewriteOptions {
+        quote_style,
+        whitespace,
+        wrap,
+        group_separator,
+        sort_within_groups_only,
+        ..
+    } = options;
+    let newline = if file_text.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    matches.sort_by_key(|m| m.start);
+
+    let mut result: Option<String> = None;
+    let mut last_end = 0;
+
+    for class_match in matches {
+        if class_match.start < last_end {
+            diagnostics.skipped(class_match.start, SkipReason::Overlapping);
+            continue;
+        }
+        if !class_match.is_valid_in(file_text) {
+            diagnostics.skipped(class_match.start, SkipReason::InvalidRange);
+            continue;
+        }
+
+        // Only replace if sorting changed the order. Whitespace alone is not
+        // a change, so a list another formatter wrapped across lines is kept
+        // as is and chained formatting stays stable
+        let by_lines = sort_within_groups_only && class_match.content.contains('\n');
+        let sorted = if by_lines {
+            sort_lines(&class_match.content, sorter, &options)
+        } else {
+            sort_list(&class_match.content, sorter, &options)
+        };
+        let reordered = !same_class_order(&sorted, &class_match.content);
+        let requote = class_match
+            .quoted_range(file_text)
+            .filter(|range| range.start >= last_end)
+            .zip(quote_style.requote(class_match.quote, &class_match.content));
+        // Wrapping measures the line as rewritten so far, so a value after
+        // another wrapped value on the same line starts where that one ends
+        let wrapped = wrap
+            .filter(|_| class_match.quote.is_some() && !by_lines)
+            .and_then(|wrap| {
+                let pending = &file_text[last_end..class_match.start];
+                let line = match pending.rfind('\n') {
+                    Some(index) => pending[index + 1..].to_string(),
+                    None => {
+                        let done = result.as_deref().unwrap_or(&file_text[..last_end]);
+                        let line_start = done.rfind('\n').map_or(0, |index| index + 1);
+                        format!("{}{}", &done[line_start..], pending)
+                    }
+                };
+                wrap.wrap(&line, &sorted, newline)
+            });
+        let rewrapped = wrapped
+            .as_ref()
+            .is_some_and(|wrapped| *wrapped != class_match.content);
+        if !reordered && !rewrapped && requote.is_none() {
+            diagnostics.skipped(class_match.start, SkipReason::AlreadySorted);
+            continue;
+        }
+        diagnostics.record(Event::Sorted(class_match.start));
+
+        let sorted = match (wrapped, whitespace) {
+            (Some(wrapped), _) => wrapped,
+            // Merged shorthands leave fewer classes than separators
+            (None, Whitespace::Preserve)
+                if reordered
+                    && group_separator.is_none()
+                    && sorted.split_whitespace().count()
+                        == class_match.content.split_whitespace().count() =>
+            {
+                with_separators_of(&class_match.content, &sorted)
+            }
+            (None, _) => sorted,
+        };
+        let classes = if reordered || rewrapped {
+            &sorted
+        } else {
+            &class_match.content
+        };
+        let output = result.get_or_insert_with(|| String::with_capacity(file_text.len()));
+        match requote {
+            Some((range, quote)) => {
+                output.push_str(&file_text[last_end..range.start]);
+                output.push(quote);
+                output.push_str(classes);
+                output.push(quote);
+                last_end = range.end;
+            }
+            None => {
+                output.push_str(&file_text[last_end..class_match.start]);
+                output.push_str(classes);
+                last_end = class_match.end;
+            }
+        }
+    }
+
+    let mut output = result?;
+    output.push_str(&file_text[last_end..]);
+    Some(output)
+}
+
+/// Sort a class list, putting its placeholders and group separators where
+/// `options` say
+fn sort_list(classes: &str, sorter: &mut CachedSorter, options: &RewriteOptions) -> String {
+    let classes = match options.group_separator {
+        Some(separator) => without_group_separators(classes, separator),
+        None => classes.to_string(),
+    };
+    let classes = if options.canonicalize {
+        canonicalize_classes(&classes)
+    } else {
+        classes
+    };
+    let classes = if options.merge_shorthands {
+        merge_shorthands(&classes)
+    } else {
+        classes
+    };
+    let sorted = sorter.sort(&classes);
+    let sorted = match options.placeholders {
+        PlaceholderPosition::End => sorted,
+        PlaceholderPosition::Start => placeholders_first(&sorted),
+        PlaceholderPosition::Preserve => placeholders_in_place(&classes, &sorted),
+    };
+    match options.group_separator {
+        Some(separator) => sorter.separate_groups(&sorted, separator),
+        None => sorted,
+    }
+}
+
+/// Sort each line of a class list on its own, keeping the line breaks and
+/// the whitespace around each line's classes
+fn sort_lines(classes: &str, sorter: &mut CachedSorter, options: &RewriteOptions) -> String {
+    classes
+        .split('\n')
+        .map(|line| {
+            let start = line.len() - line.trim_start().len();
+            let end = line.trim_end().len();
+            if start >= end {
+                return line.to_string();
+            }
+            let sorted = sort_list(&line[start..end], sorter, options);
+            format!("{}{}{}", &line[..start], sorted, &line[end..])
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// How sorted class lists are written back into a file
+#[derive(Debug, Clone, Copy)]
+struct RewriteOptions<'a> {
+    /// Quotes put around quoted attribute values
+    quote_style: QuoteStyle,
+    /// With `preserve`, reordered lists keep their separators
+    whitespace: Whitespace,
+    /// Where placeholders such as `...` go
+    placeholders: PlaceholderPosition,
+    /// Wrapping of quoted attribute values that don't fit on their line
+    wrap: Option<WrapClasses>,
+    /// Token put between the groups of the Tailwind order, replacing the
+    /// separators there were, with the list's whitespace normalized
+    group_separator: Option<&'a str>,
+    /// Sort each line of a list spanning several lines on its own, merging
+    /// none
+    sort_within_groups_only: bool,
+    /// Replace arbitrary values matching a default theme value by the named
+    /// utility before sorting
+    canonicalize: bool,
+    /// Merge pairs such as `mt-4 mb-4` into `my-4` before sorting
+    merge_shorthands: bool,
+}
+
+impl<'a> RewriteOptions<'a> {
+    fn new(config: &'a Configuration) -> Self {
+        RewriteOptions {
+            quote_style: config.quote_style,
+            whitespace: config.whitespace,
+            placeholders: config.placeholder_position,
+            wrap: config.wrap_classes,
+            group_separator: config.group_separator.as_deref(),
+            sort_within_groups_only: config.sort_within_groups_only,
+            canonicalize: config.canonicalize,
+            merge_shorthands: config.merge_shorthands,
+        }
+    }
+}
+
+// Generate the WASM plugin code. The glue only uses plain exports and
+// imports, so it is the same for wasm32-unknown-unknown and wasm32-wasip1
 #[cfg(target_arch = "wasm32")]
 generate_plugin_code!(TailwindCssPluginHandler, TailwindCssPluginHandler::new());
 
@@ -149,6 +749,7 @@ generate_plugin_code!(TailwindCssPluginHandler, TailwindCssPluginHandler::new())
 mod tests {
     use super::*;
     use dprint_core::configuration::{ConfigKeyMap, GlobalConfiguration};
+    use sorter::SortOptions;
 
     #[test]
     fn test_plugin_info() {
@@ -180,7 +781,7 @@ mod tests {
         let result = handler.resolve_config(config_map, &global_config);
 
         assert!(result.config.enabled);
-        assert_eq!(result.config.tailwind_functions.len(), 5);
+        assert_eq!(result.config.tailwind_functions.len(), 9);
         assert_eq!(result.config.tailwind_attributes.len(), 2);
         assert!(result.diagnostics.is_empty());
 
@@ -236,6 +837,202 @@ mod tests {
         assert!(result.is_ok());
         assert!(result.unwrap().is_empty());
     }
+
+    #[test]
+    fn test_rewrite_matches_out_of_order() {
+        let text = r#"<a class="p-4 z-10"></a><b class="mt-2 flex"></b>"#;
+        let second = text.find("mt-2").unwrap();
+        let first = text.find("p-4").unwrap();
+        let matches = vec![
+            ClassMatch {
+                start: second,
+                end: second + "mt-2 flex".len(),
+                content: "mt-2 flex".to_string(),
+                quote: None,
+            },
+            ClassMatch {
+                start: first,
+                end: first + "p-4 z-10".len(),
+                content: "p-4 z-10".to_string(),
+                quote: None,
+            },
+        ];
+
+        let result = rewrite_matches(
+            text,
+            matches,
+            &mut SortCache::new().sorter(Safelist::default(), SortOptions::default()),
+            RewriteOptions::new(&Configuration::default()),
+            &Diagnostics::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            r#"<a class="z-10 p-4"></a><b class="flex mt-2"></b>"#
+        );
+    }
+
+    #[test]
+    fn test_rewrite_matches_unchanged() {
+        let text = r#"<a class="z-10 p-4"></a>"#;
+        let start = text.find("z-10").unwrap();
+        let matches = vec![ClassMatch {
+            start,
+            end: start + "z-10 p-4".len(),
+            content: "z-10 p-4".to_string(),
+            quote: None,
+        }];
+
+        assert_eq!(
+            rewrite_matches(
+                text,
+                matches,
+                &mut SortCache::new().sorter(Safelist::default(), SortOptions::default()),
+                RewriteOptions::new(&Configuration::default()),
+                &Diagnostics::default()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rewrite_matches_records_diagnostics() {
+        let text = r#"<a class="p-4 z-10"></a><b class="flex mt-2"></b>"#;
+        let first = text.find("p-4").unwrap();
+        let second = text.find("flex").unwrap();
+        let matches = vec![
+            ClassMatch {
+                start: first,
+                end: first + "p-4 z-10".len(),
+                content: "p-4 z-10".to_string(),
+                quote: None,
+            },
+            ClassMatch {
+                start: first + 4,
+                end: first + "p-4 z-10".len(),
+                content: "z-10".to_string(),
+                quote: None,
+            },
+            ClassMatch {
+                start: second,
+                end: second + "flex mt-2".len(),
+                content: "flex mt-2".to_string(),
+                quote: None,
+            },
+        ];
+
+        let diagnostics = Diagnostics::new(true);
+        rewrite_matches(
+            text,
+            matches,
+            &mut SortCache::new().sorter(Safelist::default(), SortOptions::default()),
+            RewriteOptions::new(&Configuration::default()),
+            &diagnostics,
+        )
+        .unwrap();
+        assert_eq!(
+            diagnostics.events(),
+            vec![
+                Event::Sorted(first),
+                Event::Skipped {
+                    offset: first + 4,
+                    reason: SkipReason::Overlapping
+                },
+                Event::Skipped {
+                    offset: second,
+                    reason: SkipReason::AlreadySorted
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_fixed_point() {
+        let config = Configuration::default();
+        let mut cache = SortCache::new();
+        let format = Some(FileFormat::Html);
+
+        assert!(is_fixed_point(
+            &config,
+            format,
+            r#"<div class="z-10 p-4"></div>"#,
+            &mut cache
+        ));
+        assert!(!is_fixed_point(
+            &config,
+            format,
+            r#"<div class="p-4 z-10"></div>"#,
+            &mut cache
+        ));
+    }
+
+    #[test]
+    fn test_unstable_match() {
+        let config = Configuration::default();
+        let mut cache = SortCache::new();
+        let format = Some(FileFormat::Html);
+        let text = r#"<a class="z-10 p-4"></a><b class="p-4 z-10"></b>"#;
+        let start = text.find("p-4 z-10").unwrap();
+
+        assert_eq!(
+            unstable_match(&config, format, text, &mut cache),
+            Some((1, start..start + 8))
+        );
+        assert_eq!(
+            unstable_match(&config, format, r#"<a class="z-10 p-4"></a>"#, &mut cache),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rewrite_matches_skips_invalid_matches() {
+        let text = r#"<a class="p-4 z-10"></a><b class="mt-2 flex"></b><i class="é"></i>"#;
+        let first = text.find("p-4").unwrap();
+        let second = text.find("mt-2").unwrap();
+        let accent = text.find('é').unwrap();
+        let matches = vec![
+            // Out of bounds
+            ClassMatch {
+                start: text.len() - 2,
+                end: text.len() + 8,
+                content: "p-4 z-10".to_string(),
+                quote: None,
+            },
+            // Not on a char boundary
+            ClassMatch {
+                start: accent + 1,
+                end: accent + 2,
+                content: "b a".to_string(),
+                quote: None,
+            },
+            // Content doesn't match the range
+            ClassMatch {
+                start: first,
+                end: first + "p-4 z-10".len(),
+                content: "p-4 z-10 m-1".to_string(),
+                quote: None,
+            },
+            ClassMatch {
+                start: second,
+                end: second + "mt-2 flex".len(),
+                content: "mt-2 flex".to_string(),
+                quote: None,
+            },
+        ];
+
+        let result = rewrite_matches(
+            text,
+            matches,
+            &mut SortCache::new().sorter(Safelist::default(), SortOptions::default()),
+            RewriteOptions::new(&Configuration::default()),
+            &Diagnostics::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            r#"<a class="p-4 z-10"></a><b class="flex mt-2"></b><i class="é"></i>"#
+        );
+    }
 }
 
 #[cfg(test)]
@@ -261,3 +1058,9 @@ mod performance_tests;
 
 #[cfg(test)]
 mod prettier_compat_tests;
+
+#[cfg(test)]
+mod property_tests;
+
+#[cfg(test)]
+mod fixture_tests;
@@ -0,0 +1,88 @@
+/// Text of a file that isn't valid UTF-8, for the `lossyUtf8` option
+///
+/// Every invalid byte sequence is decoded as U+FFFD. Class lists holding a
+/// U+FFFD are never rewritten and the text around class lists is copied as
+/// is, so the formatted text has the same U+FFFD characters in the same
+/// order, and each one that replaced invalid bytes can be turned back into
+/// those bytes.
+#[derive(Debug)]
+pub struct LossyText<'a> {
+    pub text: String,
+    /// For every U+FFFD of `text` in order, the invalid bytes it stands for,
+    /// or `None` for one that was already in the file
+    replacements: Vec<Option<&'a [u8]>>,
+}
+
+impl<'a> LossyText<'a> {
+    pub fn decode(bytes: &'a [u8]) -> Self {
+        let mut text = String::with_capacity(bytes.len());
+        let mut replacements = Vec::new();
+
+        for chunk in bytes.utf8_chunks() {
+            let valid = chunk.valid();
+            replacements.extend(valid.matches(char::REPLACEMENT_CHARACTER).map(|_| None));
+            text.push_str(valid);
+            if !chunk.invalid().is_empty() {
+                text.push(char::REPLACEMENT_CHARACTER);
+                replacements.push(Some(chunk.invalid()));
+            }
+        }
+
+        Self { text, replacements }
+    }
+
+    /// Encode the formatted text with the original invalid bytes put back
+    ///
+    /// Returns `None` if the formatted text doesn't hold the same number of
+    /// U+FFFD characters, since the bytes could then end up out of place.
+    pub fn encode(&self, formatted: &str) -> Option<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(formatted.len());
+        let mut replacements = self.replacements.iter();
+        let mut rest = formatted;
+
+        while let Some(index) = rest.find(char::REPLACEMENT_CHARACTER) {
+            bytes.extend_from_slice(&rest.as_bytes()[..index]);
+            let end = index + char::REPLACEMENT_CHARACTER.len_utf8();
+            match replacements.next()? {
+                Some(invalid) => bytes.extend_from_slice(invalid),
+                None => bytes.extend_from_slice(&rest.as_bytes()[index..end]),
+            }
+            rest = &rest[end..];
+        }
+        if replacements.next().is_some() {
+            return None;
+        }
+        bytes.extend_from_slice(rest.as_bytes());
+
+        Some(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_keeps_invalid_bytes() {
+        let bytes = b"<p class=\"z-10 p-4\">\xff\xfe caf\xc3\xa9 \xef\xbf\xbd \xe2\x82</p>";
+        let lossy = LossyText::decode(bytes);
+
+        assert_eq!(
+            lossy.text,
+            "<p class=\"z-10 p-4\">\u{fffd}\u{fffd} café \u{fffd} \u{fffd}</p>"
+        );
+        assert_eq!(lossy.encode(&lossy.text).as_deref(), Some(&bytes[..]));
+
+        let sorted = lossy.text.replace("z-10 p-4", "p-4 z-10");
+        let expected = b"<p class=\"p-4 z-10\">\xff\xfe caf\xc3\xa9 \xef\xbf\xbd \xe2\x82</p>";
+        assert_eq!(lossy.encode(&sorted).as_deref(), Some(&expected[..]));
+    }
+
+    #[test]
+    fn test_encode_rejects_lost_replacements() {
+        let lossy = LossyText::decode(b"a\xffb");
+
+        assert_eq!(lossy.encode("ab"), None);
+        assert_eq!(lossy.encode("a\u{fffd}b\u{fffd}"), None);
+    }
+}
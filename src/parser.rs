@@ -2,10 +2,16 @@
 ///
 /// This module provides format-aware parsing to extract TailwindCSS classes
 /// from various file types while preserving their original structure.
+use crate::diagnostics::SkipReason;
 use crate::extractor::{ClassExtractor, ClassMatch};
+use crate::scanner::{matching_delimiter, script_and_style_elements, tag_attribute};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ops::Range;
 
 /// File format types supported by the plugin
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 #[allow(dead_code)]
 pub enum FileFormat {
     Html,
@@ -14,64 +20,275 @@ pub enum FileFormat {
     Vue,
     Svelte,
     Astro,
+    /// Marko templates, with `${...}` placeholders and top-level
+    /// `class { ... }` and `style { ... }` blocks
+    Marko,
+    /// Riot components, with `{ ... }` expressions
+    Riot,
+    /// MJML email templates, where `css-class` holds classes as well
+    Mjml,
+    /// XML documents, such as XSLT stylesheets and standalone SVG, where only
+    /// explicitly configured attributes are class lists
+    Xml,
+    /// PHP files, where HTML is interleaved with `<?php ... ?>` code
+    Php,
+    /// Razor components and views (Blazor, ASP.NET), where `@` starts C#
+    Razor,
+    /// C# code, where only class builder and utility calls hold class lists
+    CSharp,
+    /// Kotlin code, such as Compose HTML's `classes(...)` calls
+    Kotlin,
+    /// Python code, where only the `pythonClassNames` keyword arguments and
+    /// dict entries are class lists
+    Python,
+    /// Jupyter notebooks, whose markdown and `%%html` cells hold markup
+    Notebook,
+    /// JavaScript modules, which may contain JSX
+    Js,
+    /// TypeScript modules, which can't contain JSX
+    Ts,
+    /// Stylesheets, where only Tailwind v4 `@source inline(...)` lists are
+    /// class lists
+    Css,
+    /// JSON data, where only the `jsonClassProperties` fields are class lists
+    Json,
+    /// YAML data, where only the `jsonClassProperties` keys are class lists
+    Yaml,
+    /// Markdown and MDX documents, whose fenced code blocks are parsed by
+    /// their language. Only used through `formatAs` or `markdownCodeBlocks`
+    Markdown,
+    /// Template language described by the `customFormats` entry at this
+    /// index, parsed like HTML around its opaque code blocks
+    Custom(usize),
 }
 
 impl FileFormat {
     /// Determine file format from file path
     #[allow(dead_code)]
     pub fn from_path(path: &str) -> Option<Self> {
-        let extension = path.split('.').next_back()?.to_lowercase();
-        match extension.as_str() {
-            "html" | "htm" => Some(FileFormat::Html),
-            "jsx" => Some(FileFormat::Jsx),
-            "tsx" => Some(FileFormat::Tsx),
-            "vue" => Some(FileFormat::Vue),
-            "svelte" => Some(FileFormat::Svelte),
-            "astro" => Some(FileFormat::Astro),
-            _ => None,
-        }
+        Self::from_path_with(path, &BTreeMap::new())
+    }
+
+    /// Determine file format from file path and extra extension associations
+    ///
+    /// Extensions are tried from the last one inwards, so `page.html.twig` is
+    /// HTML and `Button.stories.tsx` is TSX. Associations are checked first,
+    /// the longest first, and may name compound extensions such as
+    /// `blade.php`, which then wins over the built-in PHP format.
+    pub fn from_path_with(path: &str, associations: &BTreeMap<String, FileFormat>) -> Option<Self> {
+        let file_name = path.rsplit(['/', '\\']).next()?.to_lowercase();
+        let extensions: Vec<&str> = file_name.split('.').skip(1).collect();
+
+        (0..extensions.len())
+            .find_map(|i| associations.get(&extensions[i..].join(".")).copied())
+            .or_else(|| {
+                (0..extensions.len())
+                    .rev()
+                    .find_map(|i| Self::from_extension(extensions[i]))
+            })
+    }
+
+    /// Whether files of this format are script code rather than markup
+    pub fn is_script(self) -> bool {
+        matches!(
+            self,
+            FileFormat::Jsx | FileFormat::Tsx | FileFormat::Js | FileFormat::Ts
+        )
+    }
+
+    /// Whether files of this format are HTML-like markup, where
+    /// `excludeElements` applies
+    pub fn is_markup(self) -> bool {
+        matches!(
+            self,
+            FileFormat::Html
+                | FileFormat::Mjml
+                | FileFormat::Vue
+                | FileFormat::Svelte
+                | FileFormat::Astro
+                | FileFormat::Marko
+                | FileFormat::Riot
+                | FileFormat::Xml
+                | FileFormat::Php
+                | FileFormat::Razor
+                | FileFormat::Custom(_)
+        )
+    }
+
+    /// Determine file format from a lowercase format name, as given in
+    /// `formatAs`, which also names formats no extension maps to by default
+    pub fn from_name(name: &str) -> Option<Self> {
+        FORMAT_NAMES
+            .iter()
+            .find(|(format_name, _)| *format_name == name)
+            .map(|(_, format)| *format)
     }
+
+    /// Determine file format from a single lowercase extension or format name
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        // Markdown is only used when asked for
+        Self::from_name(extension).filter(|format| *format != FileFormat::Markdown)
+    }
+
+    /// Format names accepted by `from_name`, in table order
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        FORMAT_NAMES.iter().map(|(name, _)| *name)
+    }
+}
+
+/// Format names and the formats they stand for, shared by file extension
+/// detection and `formatAs`
+const FORMAT_NAMES: [(&str, FileFormat); 36] = [
+    ("markdown", FileFormat::Markdown),
+    ("md", FileFormat::Markdown),
+    ("mdx", FileFormat::Markdown),
+    ("html", FileFormat::Html),
+    ("htm", FileFormat::Html),
+    ("jsx", FileFormat::Jsx),
+    ("tsx", FileFormat::Tsx),
+    ("vue", FileFormat::Vue),
+    ("svelte", FileFormat::Svelte),
+    ("astro", FileFormat::Astro),
+    ("marko", FileFormat::Marko),
+    ("riot", FileFormat::Riot),
+    ("mjml", FileFormat::Mjml),
+    ("xml", FileFormat::Xml),
+    ("xsl", FileFormat::Xml),
+    ("xslt", FileFormat::Xml),
+    ("svg", FileFormat::Xml),
+    ("php", FileFormat::Php),
+    ("razor", FileFormat::Razor),
+    ("cshtml", FileFormat::Razor),
+    ("cs", FileFormat::CSharp),
+    ("kt", FileFormat::Kotlin),
+    ("kts", FileFormat::Kotlin),
+    ("py", FileFormat::Python),
+    ("ipynb", FileFormat::Notebook),
+    ("js", FileFormat::Js),
+    ("mjs", FileFormat::Js),
+    ("cjs", FileFormat::Js),
+    ("ts", FileFormat::Ts),
+    ("mts", FileFormat::Ts),
+    ("cts", FileFormat::Ts),
+    ("css", FileFormat::Css),
+    ("json", FileFormat::Json),
+    ("jsonc", FileFormat::Json),
+    ("yaml", FileFormat::Yaml),
+    ("yml", FileFormat::Yaml),
+];
+
+/// Template language onboarded through the `customFormats` option
+///
+/// Its files are parsed like HTML with the code between each pair of
+/// `skip_blocks` delimiters left alone, the way PHP code is.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomFormat {
+    /// Lowercase extensions without a leading dot, such as `njk`
+    pub extensions: Vec<String>,
+    /// Opening and closing delimiters of template code, such as `{%` and
+    /// `%}`
+    pub skip_blocks: Vec<(String, String)>,
+    /// Leave `<script>` and `<style>` elements alone
+    pub skip_script_and_style: bool,
 }
 
 /// Format parser that extracts classes based on file format
 #[allow(dead_code)]
 pub struct FormatParser {
     extractor: ClassExtractor,
+    custom_formats: Vec<CustomFormat>,
 }
 
 impl FormatParser {
     #[allow(dead_code)]
     pub fn new(extractor: ClassExtractor) -> Self {
-        Self { extractor }
+        Self {
+            extractor,
+            custom_formats: Vec::new(),
+        }
+    }
+
+    /// Set the formats `FileFormat::Custom` indices refer to
+    pub fn with_custom_formats(mut self, custom_formats: Vec<CustomFormat>) -> Self {
+        self.custom_formats = custom_formats;
+        self
+    }
+
+    /// The extractor used for parsing
+    pub fn extractor(&self) -> &ClassExtractor {
+        &self.extractor
+    }
+
+    /// Shift matches and diagnostics recorded since `mark` from positions
+    /// in a section to positions in the file
+    fn offset_section(&self, matches: &mut [ClassMatch], mark: usize, offset: usize) {
+        offset_matches(matches, offset);
+        self.extractor.diagnostics().offset_since(mark, offset);
     }
 
     /// Parse content based on file format
     #[allow(dead_code)]
     pub fn parse(&self, content: &str, format: FileFormat) -> Vec<ClassMatch> {
         match format {
-            FileFormat::Html => self.parse_html(content),
+            FileFormat::Html | FileFormat::Mjml => self.parse_html(content),
             FileFormat::Jsx | FileFormat::Tsx => self.parse_jsx(content),
             FileFormat::Vue => self.parse_vue(content),
             FileFormat::Svelte => self.parse_svelte(content),
             FileFormat::Astro => self.parse_astro(content),
+            FileFormat::Marko => self.parse_marko(content),
+            FileFormat::Riot => self.parse_riot(content),
+            FileFormat::Xml => self.extractor.extract_attributes_from_markup(content),
+            FileFormat::Php => self.parse_php(content),
+            FileFormat::Razor => self.parse_razor(content),
+            FileFormat::CSharp | FileFormat::Kotlin => {
+                self.extractor.extract_from_functions(content)
+            }
+            FileFormat::Js => self.parse_script(content, true),
+            FileFormat::Ts => self.parse_script(content, false),
+            FileFormat::Css => self.parse_css(content),
+            FileFormat::Json => self.extractor.extract_from_json(content),
+            FileFormat::Yaml => self.extractor.extract_from_yaml(content),
+            FileFormat::Python => self.extractor.extract_from_python(content),
+            FileFormat::Notebook => self.extractor.extract_from_notebook(content),
+            FileFormat::Markdown => self.parse_markdown(content),
+            FileFormat::Custom(index) => match self.custom_formats.get(index) {
+                Some(custom) => self.parse_custom(content, custom),
+                None => self.parse_html(content),
+            },
+        }
+    }
+
+    /// Parse files of a template language from `customFormats`
+    ///
+    /// The code between each pair of skip block delimiters, such as
+    /// `{% if %}` or `{{ extra }}`, is masked like PHP code, so the markup
+    /// around it is parsed like HTML and classes next to a block stay in
+    /// place. With `skip_script_and_style`, only the attributes outside of
+    /// `<script>` and `<style>` elements are parsed.
+    fn parse_custom(&self, content: &str, custom: &CustomFormat) -> Vec<ClassMatch> {
+        let masked = mask_blocks(content, &custom.skip_blocks);
+        if custom.skip_script_and_style {
+            let excluded = script_and_style_ranges(&masked);
+            self.parse_markup_sections(markup_sections(&masked, excluded))
+        } else {
+            self.extractor.extract_from_markup(&masked)
         }
     }
 
     /// Parse HTML files
     ///
     /// HTML files contain standard class attributes in tags.
-    /// We preserve all HTML structure, comments, and whitespace.
+    /// We preserve all HTML structure, comments, and whitespace, and only
+    /// rewrite attributes that sit inside an element tag. The content of
+    /// `<template>` elements, anywhere including `<head>` and nested
+    /// templates, is markup like the rest of the file.
     #[allow(dead_code)]
     fn parse_html(&self, content: &str) -> Vec<ClassMatch> {
-        // Use extractor to find class attributes
-        let mut matches = self.extractor.extract_from_attributes(content);
-
-        // HTML doesn't typically have function calls like clsx()
-        // but we check anyway in case of inline scripts
-        let function_matches = self.extractor.extract_from_functions(content);
-        matches.extend(function_matches);
-
-        matches
+        // Find class attributes inside element tags in a single scan. HTML
+        // doesn't typically have function calls like clsx() but we check
+        // anyway in case of inline scripts
+        self.extractor.extract_from_markup(content)
     }
 
     /// Parse JSX/TSX files
@@ -82,13 +299,79 @@ impl FormatParser {
     /// - Utility functions: className={clsx(...)}
     #[allow(dead_code)]
     fn parse_jsx(&self, content: &str) -> Vec<ClassMatch> {
-        // Extract from className and class attributes
-        let mut matches = self.extractor.extract_from_attributes(content);
+        // Extract from className and class attributes as well as utility
+        // functions (clsx, classnames, etc.) in a single scan
+        let mut matches = self.extractor.extract_all(content);
+        matches.extend(self.parse_object_properties(content));
+        matches
+    }
+
+    /// Parse object literal properties named like the attributes, such as
+    /// Storybook's `args: { className: "..." }`, when `sortObjectProperties`
+    /// is enabled
+    fn parse_object_properties(&self, content: &str) -> Vec<ClassMatch> {
+        if self.extractor.object_properties {
+            self.extractor.extract_from_object_properties(content)
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Parse plain JavaScript/TypeScript modules
+    ///
+    /// Many React codebases keep JSX in `.js` files. When JSX-like tags are
+    /// present the module is parsed like JSX; otherwise only utility
+    /// function calls are extracted, so string attributes in object literals
+    /// or markup inside strings are left alone unless `sortObjectProperties`
+    /// is enabled.
+    fn parse_script(&self, content: &str, allow_jsx: bool) -> Vec<ClassMatch> {
+        if allow_jsx && looks_like_jsx(content) {
+            self.parse_jsx(content)
+        } else {
+            let mut matches = self.extractor.extract_from_functions(content);
+            matches.extend(self.parse_object_properties(content));
+            matches
+        }
+    }
 
-        // Extract from utility functions (clsx, classnames, etc.)
-        let function_matches = self.extractor.extract_from_functions(content);
-        matches.extend(function_matches);
+    /// Parse stylesheets
+    ///
+    /// Tailwind v4 CSS can list classes to always generate in
+    /// `@source inline("...")`. Those lists are sorted; `@apply`, `@source`
+    /// paths and `@reference` imports are left alone.
+    fn parse_css(&self, content: &str) -> Vec<ClassMatch> {
+        self.extractor.extract_from_css(content)
+    }
 
+    /// Parse Markdown and MDX documents
+    ///
+    /// Fenced code blocks tagged with a language this plugin parses, such as
+    /// ```` ```html ```` or ```` ```jsx ````, are parsed as files of that
+    /// format. Other code blocks are left alone. The prose around them,
+    /// which may hold HTML or MDX components, is scanned like a file of no
+    /// known format.
+    fn parse_markdown(&self, content: &str) -> Vec<ClassMatch> {
+        let parse_section = |range: Range<usize>, format: Option<FileFormat>| {
+            let mark = self.extractor.diagnostics().mark();
+            let section = &content[range.clone()];
+            let mut matches = match format {
+                Some(format) => self.parse(section, format),
+                None => self.extractor.extract_all(section),
+            };
+            self.offset_section(&mut matches, mark, range.start);
+            matches
+        };
+
+        let mut matches = Vec::new();
+        let mut prose_start = 0;
+        for fence in code_fences(content) {
+            matches.extend(parse_section(prose_start..fence.range.start, None));
+            if let Some(format) = fence_format(fence.lang) {
+                matches.extend(parse_section(fence.body, Some(format)));
+            }
+            prose_start = fence.range.end;
+        }
+        matches.extend(parse_section(prose_start..content.len(), None));
         matches
     }
 
@@ -104,35 +387,49 @@ impl FormatParser {
     fn parse_vue(&self, content: &str) -> Vec<ClassMatch> {
         // Find the template section
         if let Some(template_section) = extract_vue_template(content) {
-            // Parse classes within the template section
-            let mut matches = self
-                .extractor
-                .extract_from_attributes(&template_section.content);
-
-            // Adjust match positions to account for template offset
-            for m in &mut matches {
-                m.start += template_section.start;
-                m.end += template_section.start;
-            }
-
-            // Also check for function calls in template (rare but possible)
-            let mut function_matches = self
-                .extractor
-                .extract_from_functions(&template_section.content);
-            for m in &mut function_matches {
-                m.start += template_section.start;
-                m.end += template_section.start;
+            // Parse classes within the template section, including function
+            // calls in the template (rare but possible)
+            let mut matches = if vue_template_lang(content).is_none_or(|lang| lang == "html") {
+                let mark = self.extractor.diagnostics().mark();
+                let mut matches = self.extractor.extract_from_markup(template_section.content);
+
+                // Adjust match positions to account for template offset
+                self.offset_section(&mut matches, mark, template_section.range.start);
+                matches
+            } else {
+                // Pug and other template languages have no HTML attributes
+                // to match, so the template is left alone
+                self.extractor
+                    .diagnostics()
+                    .skipped(template_section.range.start, SkipReason::TemplateLanguage);
+                Vec::new()
+            };
+
+            // Render functions live in the script blocks: `lang="jsx"` and
+            // `lang="tsx"` blocks are parsed like JSX, others only for
+            // utility and element factory calls such as `h("div", {...})`
+            for script in extract_vue_scripts(content) {
+                if script.section.range.start < template_section.range.end
+                    && template_section.range.start < script.section.range.end
+                {
+                    continue;
+                }
+                let mark = self.extractor.diagnostics().mark();
+                let mut script_matches = if script.jsx {
+                    self.parse_jsx(script.section.content)
+                } else {
+                    self.extractor
+                        .extract_from_functions(script.section.content)
+                };
+                self.offset_section(&mut script_matches, mark, script.section.range.start);
+                matches.extend(script_matches);
             }
-            matches.extend(function_matches);
 
             matches
         } else {
             // No template section found, parse entire file
             // This handles edge cases where template syntax is non-standard
-            let mut matches = self.extractor.extract_from_attributes(content);
-            let function_matches = self.extractor.extract_from_functions(content);
-            matches.extend(function_matches);
-            matches
+            self.extractor.extract_all(content)
         }
     }
 
@@ -148,19 +445,76 @@ impl FormatParser {
     fn parse_svelte(&self, content: &str) -> Vec<ClassMatch> {
         // Svelte markup is at the top level, but we need to avoid
         // parsing inside <script> and <style> tags
-        let sections = extract_svelte_markup_sections(content);
+        self.parse_markup_sections(extract_svelte_markup_sections(content))
+    }
 
+    /// Parse Marko templates
+    ///
+    /// Marko markup sits at the top level next to `<script>` and `<style>`
+    /// tags, top-level `class { ... }` and `style { ... }` blocks and
+    /// `static`, `import` and `export` lines, which are all skipped.
+    /// Placeholders such as `class="p-4 ${size}"` are left alone while the
+    /// static classes around them are sorted.
+    fn parse_marko(&self, content: &str) -> Vec<ClassMatch> {
+        self.parse_markup_sections(extract_marko_markup_sections(content))
+    }
+
+    /// Parse Riot components
+    ///
+    /// A Riot component is a custom tag wrapping its markup, `<script>` and
+    /// `<style>`. Only the markup is parsed, and `{ ... }` expressions are
+    /// handled like Svelte's.
+    fn parse_riot(&self, content: &str) -> Vec<ClassMatch> {
+        self.parse_markup_sections(extract_svelte_markup_sections(content))
+    }
+
+    /// Parse PHP files
+    ///
+    /// WordPress themes and other PHP templates interleave HTML with
+    /// `<?php ... ?>` and `<?= ... ?>` code, also inside attribute values as
+    /// in `class="p-4 <?php echo $extra; ?>"`. The code regions are opaque:
+    /// they are masked as `{...}` interpolations of the same length, so the
+    /// HTML around them is parsed like HTML with positions unchanged and the
+    /// classes next to a region stay in place.
+    fn parse_php(&self, content: &str) -> Vec<ClassMatch> {
+        self.extractor.extract_from_markup(&mask_php_code(content))
+    }
+
+    /// Parse Razor components and views
+    ///
+    /// Markup is parsed like HTML, and class builder calls such as
+    /// `.AddClass("...")` in `@code` blocks are found as well. In Razor an `@`
+    /// inside an attribute value starts a C# expression, as in
+    /// `class="p-4 @(IsActive ? "flex" : "")"`, so such values are left
+    /// alone.
+    fn parse_razor(&self, content: &str) -> Vec<ClassMatch> {
+        let diagnostics = self.extractor.diagnostics();
+        self.extractor
+            .extract_from_markup(content)
+            .into_iter()
+            .filter(|m| {
+                let razor_expression = m.quote.is_some() && m.content.contains('@');
+                if razor_expression {
+                    diagnostics.skipped(m.start, SkipReason::DynamicContent);
+                }
+                !razor_expression
+            })
+            .collect()
+    }
+
+    /// Extract attribute class lists from markup sections of a file
+    fn parse_markup_sections(&self, sections: Vec<ContentSection<'_>>) -> Vec<ClassMatch> {
         let mut all_matches = Vec::new();
 
         for section in sections {
             // Extract classes from this markup section
-            let mut matches = self.extractor.extract_from_attributes(&section.content);
+            let mark = self.extractor.diagnostics().mark();
+            let mut matches = self
+                .extractor
+                .extract_attributes_from_markup(section.content);
 
             // Adjust positions
-            for m in &mut matches {
-                m.start += section.start;
-                m.end += section.start;
-            }
+            self.offset_section(&mut matches, mark, section.range.start);
 
             all_matches.extend(matches);
         }
@@ -182,37 +536,194 @@ impl FormatParser {
 
         // Parse the markup section
         let markup = &content[markup_start..];
-        let mut matches = self.extractor.extract_from_attributes(markup);
+        // Attributes and utility functions are found in a single scan
+        let mark = self.extractor.diagnostics().mark();
+        let mut matches = self.extractor.extract_from_markup(markup);
 
         // Adjust positions to account for frontmatter
-        for m in &mut matches {
-            m.start += markup_start;
-            m.end += markup_start;
-        }
-
-        // Also check for utility functions
-        let mut function_matches = self.extractor.extract_from_functions(markup);
-        for m in &mut function_matches {
-            m.start += markup_start;
-            m.end += markup_start;
-        }
-        matches.extend(function_matches);
+        self.offset_section(&mut matches, mark, markup_start);
 
         matches
     }
 }
 
 /// Section of content with its position
+///
+/// The content borrows from the parsed file, so splitting a component into
+/// sections never copies it.
 #[derive(Debug)]
 #[allow(dead_code)]
-struct ContentSection {
+struct ContentSection<'a> {
+    range: Range<usize>,
+    content: &'a str,
+}
+
+impl<'a> ContentSection<'a> {
+    fn new(content: &'a str, range: Range<usize>) -> Self {
+        Self {
+            content: &content[range.clone()],
+            range,
+        }
+    }
+}
+
+/// Fenced code block of a Markdown document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeFence<'a> {
+    /// Whole block, from the opening fence to the end of the closing one
+    pub range: Range<usize>,
+    /// First word of the info string, such as `html` for ```` ```html title="a" ````
+    pub lang: &'a str,
+    /// Lines between the fences
+    pub body: Range<usize>,
+    /// Whether the opening fence is indented, as in a list item
+    pub indented: bool,
+}
+
+/// Code block whose closing fence hasn't been found yet
+struct OpenFence<'a> {
+    /// `` ` `` or `~`
+    fence_char: char,
+    /// Length of the opening fence, which the closing one must reach
+    run: usize,
+    /// Start of the opening fence line
     start: usize,
-    content: String,
+    /// Start of the line after the opening fence
+    body_start: usize,
+    lang: &'a str,
+    indented: bool,
+}
+
+impl<'a> OpenFence<'a> {
+    /// Block ending at `end`, with its body running up to `body_end`
+    fn close(self, body_end: usize, end: usize) -> CodeFence<'a> {
+        CodeFence {
+            range: self.start..end,
+            lang: self.lang,
+            body: self.body_start..body_end,
+            indented: self.indented,
+        }
+    }
+}
+
+/// Fenced code blocks of a Markdown document, in order
+///
+/// Fences are runs of at least three backticks or tildes, indented by at
+/// most three spaces. A block is closed by a run of the same character at
+/// least as long with nothing after it, or else runs to the end of the
+/// document, as in CommonMark.
+pub fn code_fences(content: &str) -> Vec<CodeFence<'_>> {
+    let mut fences = Vec::new();
+    let mut open: Option<OpenFence> = None;
+    let mut line_start = 0;
+
+    while line_start < content.len() {
+        let line_end = content[line_start..]
+            .find('\n')
+            .map_or(content.len(), |newline| line_start + newline + 1);
+        let line = content[line_start..line_end].trim_end_matches(['\n', '\r']);
+        let rest = line.trim_start_matches(' ');
+        let indent = line.len() - rest.len();
+        let fence_char = rest.chars().next().filter(|c| *c == '`' || *c == '~');
+
+        if let Some(fence_char) = fence_char.filter(|_| indent <= 3) {
+            let run = rest.len() - rest.trim_start_matches(fence_char).len();
+            let info = rest[run..].trim();
+            match open.take() {
+                Some(block)
+                    if fence_char == block.fence_char && run >= block.run && info.is_empty() =>
+                {
+                    fences.push(block.close(line_start, line_end));
+                }
+                None if run >= 3 && !(fence_char == '`' && info.contains('`')) => {
+                    let lang = info
+                        .split(|c: char| c.is_whitespace() || c == '{' || c == ',')
+                        .next()
+                        .unwrap_or("");
+                    open = Some(OpenFence {
+                        fence_char,
+                        run,
+                        start: line_start,
+                        body_start: line_end,
+                        lang,
+                        indented: indent > 0,
+                    });
+                }
+                block => open = block,
+            }
+        }
+        line_start = line_end;
+    }
+
+    if let Some(block) = open {
+        fences.push(block.close(content.len(), content.len()));
+    }
+    fences
+}
+
+/// Format of a fenced code block from its language tag, if this plugin
+/// parses that language
+pub fn fence_format(lang: &str) -> Option<FileFormat> {
+    let lang = lang.to_lowercase();
+    FileFormat::from_extension(match lang.as_str() {
+        "javascript" => "js",
+        "typescript" => "ts",
+        "python" => "py",
+        "csharp" => "cs",
+        "kotlin" => "kt",
+        other => other,
+    })
+}
+
+/// Heuristic check for JSX elements in a JavaScript module
+///
+/// Looks for a `<` that starts an element (`<div`, `<Button`, `<>`) in
+/// expression position, i.e. after `(`, `=`, `?`, `:`, `,`, `[`, `{`, `>`,
+/// `&&`, `||` or `return`, followed somewhere later by `/>` or `</`.
+/// Comparisons such as `a < b` and generic calls don't qualify.
+fn looks_like_jsx(content: &str) -> bool {
+    let bytes = content.as_bytes();
+    let mut pos = 0;
+
+    while let Some(offset) = bytes[pos..].iter().position(|&b| b == b'<') {
+        let open = pos + offset;
+        pos = open + 1;
+
+        let starts_element = bytes
+            .get(open + 1)
+            .is_some_and(|&b| b.is_ascii_alphabetic() || b == b'>');
+        if !starts_element {
+            continue;
+        }
+
+        let before = content[..open].trim_end();
+        let in_expression_position = before.is_empty()
+            || before.ends_with("return")
+            || before.ends_with("&&")
+            || before.ends_with("||")
+            || before.bytes().next_back().is_some_and(|b| {
+                matches!(b, b'(' | b'=' | b'?' | b':' | b',' | b'[' | b'{' | b'>')
+            });
+        if in_expression_position {
+            let rest = &content[open..];
+            return rest.contains("/>") || rest.contains("</");
+        }
+    }
+
+    false
+}
+
+/// Shift section-relative match positions into file positions
+fn offset_matches(matches: &mut [ClassMatch], offset: usize) {
+    for m in matches {
+        m.start += offset;
+        m.end += offset;
+    }
 }
 
 /// Extract the template section from a Vue file
 #[allow(dead_code)]
-fn extract_vue_template(content: &str) -> Option<ContentSection> {
+fn extract_vue_template(content: &str) -> Option<ContentSection<'_>> {
     // Find <template> opening tag
     let template_start_tag = content.find("<template")?;
     let template_content_start = content[template_start_tag..].find('>')? + template_start_tag + 1;
@@ -220,80 +731,213 @@ fn extract_vue_template(content: &str) -> Option<ContentSection> {
     // Find </template> closing tag
     let template_end = content.find("</template>")?;
 
-    Some(ContentSection {
-        start: template_content_start,
-        content: content[template_content_start..template_end].to_string(),
-    })
+    Some(ContentSection::new(
+        content,
+        template_content_start..template_end,
+    ))
+}
+
+/// Language of the template block of a Vue file, from its `lang` attribute
+fn vue_template_lang(content: &str) -> Option<&str> {
+    tag_attribute(content, content.find("<template")?, "lang").filter(|lang| !lang.is_empty())
+}
+
+/// Script block of a Vue file
+#[derive(Debug)]
+struct VueScript<'a> {
+    section: ContentSection<'a>,
+    /// Whether the block is `lang="jsx"` or `lang="tsx"`
+    jsx: bool,
+}
+
+/// Extract the contents of the `<script>` blocks of a Vue file
+fn extract_vue_scripts(content: &str) -> Vec<VueScript<'_>> {
+    script_and_style_elements(content)
+        .into_iter()
+        .filter(|element| element.name == "script")
+        .map(|element| {
+            let open_tag = &content[element.open_tag];
+            let jsx = ["jsx", "tsx"].iter().any(|lang| {
+                open_tag.contains(&format!("lang=\"{lang}\""))
+                    || open_tag.contains(&format!("lang='{lang}'"))
+            });
+            VueScript {
+                section: ContentSection::new(content, element.content),
+                jsx,
+            }
+        })
+        .collect()
 }
 
 /// Extract markup sections from Svelte file (excluding <script> and <style>)
 #[allow(dead_code)]
-fn extract_svelte_markup_sections(content: &str) -> Vec<ContentSection> {
-    let mut sections = Vec::new();
-    let mut current_pos = 0;
-
-    // Find all <script> and <style> tags
-    let mut excluded_ranges = Vec::new();
+fn extract_svelte_markup_sections(content: &str) -> Vec<ContentSection<'_>> {
+    markup_sections(content, script_and_style_ranges(content))
+}
 
-    // Find <script> tags
-    let mut search_pos = 0;
-    while let Some(script_start) = content[search_pos..].find("<script") {
-        let abs_start = search_pos + script_start;
-        if let Some(script_end) = content[abs_start..].find("</script>") {
-            let abs_end = abs_start + script_end + "</script>".len();
-            excluded_ranges.push((abs_start, abs_end));
-            search_pos = abs_end;
+/// Extract markup sections from a Marko file, excluding `<script>` and
+/// `<style>` tags and top-level code blocks and statements
+fn extract_marko_markup_sections(content: &str) -> Vec<ContentSection<'_>> {
+    let mut excluded_ranges = script_and_style_ranges(content);
+
+    let mut line_start = 0;
+    while line_start < content.len() {
+        let line_end = content[line_start..]
+            .find('\n')
+            .map_or(content.len(), |newline| line_start + newline + 1);
+        let line = &content[line_start..line_end];
+        let block = ["class", "style"].iter().any(|keyword| {
+            line.strip_prefix(keyword).is_some_and(|rest| {
+                // `style.scss { ... }` blocks name their language
+                let rest = rest.strip_prefix('.').map_or(rest, |language| {
+                    language.trim_start_matches(|c: char| c.is_ascii_alphanumeric())
+                });
+                rest.trim_start().starts_with('{')
+            })
+        });
+        let statement = ["static ", "import ", "export "]
+            .iter()
+            .any(|keyword| line.starts_with(keyword));
+
+        if block {
+            let open = line_start + line.find('{').unwrap_or_default();
+            let end = matching_delimiter(content.as_bytes(), open)
+                .map_or(content.len(), |close| close + 1);
+            let end = content[end..]
+                .find('\n')
+                .map_or(content.len(), |newline| end + newline + 1);
+            excluded_ranges.push((line_start, end));
+            line_start = end;
         } else {
-            break;
+            if statement {
+                excluded_ranges.push((line_start, line_end));
+            }
+            line_start = line_end;
         }
     }
 
-    // Find <style> tags
-    search_pos = 0;
-    while let Some(style_start) = content[search_pos..].find("<style") {
-        let abs_start = search_pos + style_start;
-        if let Some(style_end) = content[abs_start..].find("</style>") {
-            let abs_end = abs_start + style_end + "</style>".len();
-            excluded_ranges.push((abs_start, abs_end));
-            search_pos = abs_end;
-        } else {
-            break;
+    // A block may have been found inside a script or style tag
+    excluded_ranges.sort_by_key(|r| r.0);
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in excluded_ranges {
+        match merged.last_mut() {
+            Some(last) if start < last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
         }
     }
 
-    // Sort excluded ranges by start position
-    excluded_ranges.sort_by_key(|r| r.0);
+    markup_sections(content, merged)
+}
+
+/// Ranges of the `<script>` and `<style>` tags of a component
+fn script_and_style_ranges(content: &str) -> Vec<(usize, usize)> {
+    script_and_style_elements(content)
+        .into_iter()
+        .map(|element| (element.range.start, element.range.end))
+        .collect()
+}
+
+/// Sections of content between sorted, non-overlapping excluded ranges
+fn markup_sections(content: &str, excluded_ranges: Vec<(usize, usize)>) -> Vec<ContentSection<'_>> {
+    let mut sections = Vec::new();
+    let mut current_pos = 0;
 
     // Extract sections between excluded ranges
     for (start, end) in excluded_ranges {
         if current_pos < start {
-            sections.push(ContentSection {
-                start: current_pos,
-                content: content[current_pos..start].to_string(),
-            });
+            sections.push(ContentSection::new(content, current_pos..start));
         }
         current_pos = end;
     }
 
     // Add remaining content after last excluded range
     if current_pos < content.len() {
-        sections.push(ContentSection {
-            start: current_pos,
-            content: content[current_pos..].to_string(),
-        });
+        sections.push(ContentSection::new(content, current_pos..content.len()));
     }
 
     // If no excluded ranges found, return entire content
     if sections.is_empty() {
-        sections.push(ContentSection {
-            start: 0,
-            content: content.to_string(),
-        });
+        sections.push(ContentSection::new(content, 0..content.len()));
     }
 
     sections
 }
 
+/// Replace the `<?php ... ?>`, `<?= ... ?>` and `<? ... ?>` regions of a PHP
+/// file with `{` and `}` around spaces
+///
+/// A region without a closing `?>` runs to the end of the file, as PHP
+/// allows. `?>` inside PHP strings or comments isn't recognized, which is
+/// rare in templates. `<?xml` declarations are left alone.
+fn mask_php_code(content: &str) -> String {
+    let mut masked = content.as_bytes().to_vec();
+    let mut search_pos = 0;
+
+    while let Some(offset) = content[search_pos..].find("<?") {
+        let start = search_pos + offset;
+        let rest = &content[start + 2..];
+        let is_code = rest.starts_with("php")
+            || rest.starts_with('=')
+            || rest.starts_with(|c: char| c.is_ascii_whitespace());
+        if !is_code {
+            search_pos = start + 2;
+            continue;
+        }
+
+        let end = rest
+            .find("?>")
+            .map_or(content.len(), |close| start + 2 + close + 2);
+        mask_region(&mut masked, start..end);
+        search_pos = end;
+    }
+
+    // Only ASCII bytes replaced whole characters, so the text is still UTF-8
+    String::from_utf8(masked).unwrap_or_else(|_| content.to_string())
+}
+
+/// Replace the code between each pair of `blocks` delimiters, delimiters
+/// included, with `{` and `}` around spaces
+///
+/// The block opened first wins, and blocks don't nest. A block without its
+/// closing delimiter runs to the end of the file. Delimiters must not be
+/// empty.
+fn mask_blocks(content: &str, blocks: &[(String, String)]) -> String {
+    let mut masked = content.as_bytes().to_vec();
+    let mut search_pos = 0;
+
+    loop {
+        let next = blocks
+            .iter()
+            .filter_map(|(open, close)| {
+                let offset = content[search_pos..].find(open.as_str())?;
+                Some((search_pos + offset, open.len(), close))
+            })
+            .min_by_key(|(start, _, _)| *start);
+        let Some((start, open_len, close)) = next else {
+            break;
+        };
+
+        let body = start + open_len;
+        let end = content[body..]
+            .find(close.as_str())
+            .map_or(content.len(), |offset| body + offset + close.len());
+        mask_region(&mut masked, start..end);
+        search_pos = end;
+    }
+
+    // Only ASCII bytes replaced whole characters, so the text is still UTF-8
+    String::from_utf8(masked).unwrap_or_else(|_| content.to_string())
+}
+
+/// Mask a region of code as a `{...}` interpolation of the same length
+fn mask_region(masked: &mut [u8], region: Range<usize>) {
+    masked[region.start] = b'{';
+    masked[region.start + 1..region.end].fill(b' ');
+    if region.end - region.start > 1 {
+        masked[region.end - 1] = b'}';
+    }
+}
+
 /// Find the end position of Astro frontmatter section
 #[allow(dead_code)]
 fn find_astro_frontmatter_end(content: &str) -> Option<usize> {
@@ -318,6 +962,7 @@ fn find_astro_frontmatter_end(content: &str) -> Option<usize> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::diagnostics::Event;
 
     fn create_test_parser() -> FormatParser {
         let extractor = ClassExtractor::new(
@@ -339,7 +984,30 @@ mod tests {
             Some(FileFormat::Svelte)
         );
         assert_eq!(FileFormat::from_path("page.astro"), Some(FileFormat::Astro));
-        assert_eq!(FileFormat::from_path("styles.css"), None);
+        assert_eq!(FileFormat::from_path("card.marko"), Some(FileFormat::Marko));
+        assert_eq!(FileFormat::from_path("todo.riot"), Some(FileFormat::Riot));
+        assert_eq!(
+            FileFormat::from_path("welcome.mjml"),
+            Some(FileFormat::Mjml)
+        );
+        assert_eq!(FileFormat::from_path("page.xsl"), Some(FileFormat::Xml));
+        assert_eq!(FileFormat::from_path("icon.svg"), Some(FileFormat::Xml));
+        assert_eq!(FileFormat::from_path("single.php"), Some(FileFormat::Php));
+        assert_eq!(FileFormat::from_path("Nav.razor"), Some(FileFormat::Razor));
+        assert_eq!(
+            FileFormat::from_path("Views/Index.cshtml"),
+            Some(FileFormat::Razor)
+        );
+        assert_eq!(FileFormat::from_path("Nav.cs"), Some(FileFormat::CSharp));
+        assert_eq!(FileFormat::from_path("Main.kt"), Some(FileFormat::Kotlin));
+        assert_eq!(FileFormat::from_path("app.py"), Some(FileFormat::Python));
+        assert_eq!(
+            FileFormat::from_path("report.ipynb"),
+            Some(FileFormat::Notebook)
+        );
+        assert_eq!(FileFormat::from_path("styles.css"), Some(FileFormat::Css));
+        assert_eq!(FileFormat::from_path("data.json"), Some(FileFormat::Json));
+        assert_eq!(FileFormat::from_path("data.yml"), Some(FileFormat::Yaml));
     }
 
     #[test]
@@ -382,6 +1050,69 @@ export default {
         assert_eq!(matches[0].content, "flex p-4");
     }
 
+    #[test]
+    fn test_parse_vue_skips_pug_template() {
+        let parser = create_test_parser();
+        let content = r#"<template lang="pug">
+div.card(:class="open && 'z-10 p-4'")
+  p Some <b class="z-10 p-4">inline</b> text
+</template>
+
+<script>
+export default { computed: { wrapper: () => clsx("z-10 p-4") } }
+</script>
+"#;
+
+        let matches = parser.parse_vue(content);
+        assert_eq!(matches.len(), 1);
+        assert!(content[matches[0].start..].starts_with("z-10 p-4\")"));
+        assert_eq!(vue_template_lang(content), Some("pug"));
+        assert_eq!(
+            vue_template_lang("<template lang='html'></template>"),
+            Some("html")
+        );
+        assert_eq!(vue_template_lang("<template>\n</template>"), None);
+        assert_eq!(
+            vue_template_lang("<template\n  lang=pug>\n</template>"),
+            Some("pug")
+        );
+        assert_eq!(
+            vue_template_lang("<template data-lang=\"pug\">\n</template>"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_style_block_variants_are_excluded() {
+        let parser = create_test_parser();
+        let styles = r#"
+<style lang="scss" global>
+  div[class="z-10 p-4"] { @apply flex; }
+</style>
+<style
+  lang="postcss">
+  :global(a[class='z-10 p-4']) { color: red; }
+</style>
+<STYLE scoped>p[class="z-10 p-4"] {}</STYLE>
+"#;
+
+        let svelte = format!("<div class=\"flex p-4\"></div>{styles}");
+        let matches = parser.parse_svelte(&svelte);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].content, "flex p-4");
+
+        let vue = format!("<template><div class=\"flex p-4\"></div></template>{styles}");
+        let matches = parser.parse_vue(&vue);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].content, "flex p-4");
+
+        // Without a template, render functions are scanned but styles aren't
+        let vue = format!("<script>h('div', {{ class: clsx('flex p-4') }})</script>{styles}");
+        let matches = parser.parse_vue(&vue);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].content, "flex p-4");
+    }
+
     #[test]
     fn test_parse_svelte() {
         let parser = create_test_parser();
@@ -405,6 +1136,169 @@ export default {
         assert!(matches.iter().any(|m| m.content == "bg-blue-500"));
     }
 
+    #[test]
+    fn test_parse_marko() {
+        let parser = create_test_parser();
+        let content = r#"import format from "./format";
+static const size = "class=\"z-10\"";
+
+class {
+  onCreate() {
+    this.state = { open: this.input.count < 3 };
+  }
+}
+
+style {
+  .card { color: red; }
+}
+
+<div class="flex p-4 ${state.open ? "block" : ""}">
+  <span class="bg-blue-500">${input.label}</span>
+</div>
+<script>
+  const el = `<b class="m-2">`;
+</script>
+"#;
+
+        let matches = parser.parse_marko(content);
+        let contents: Vec<&str> = matches.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["flex p-4", "block", "bg-blue-500"]);
+        for m in &matches {
+            assert_eq!(&content[m.start..m.end], m.content);
+        }
+    }
+
+    #[test]
+    fn test_parse_riot() {
+        let parser = create_test_parser();
+        let content = r#"<todo-list>
+  <ul class="flex p-4 { state.done ? 'opacity-50' : '' }">
+    <li each={ item in state.items } class={ item.active ? 'font-bold' : '' }>{ item.label }</li>
+  </ul>
+
+  <script>
+    export default { onMounted() { this.root.innerHTML = '<i class="m-2"></i>' } }
+  </script>
+  <style>
+    :host { display: block; }
+  </style>
+</todo-list>
+"#;
+
+        let matches = parser.parse_riot(content);
+        let contents: Vec<&str> = matches.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["flex p-4", "opacity-50", "font-bold"]);
+    }
+
+    #[test]
+    fn test_parse_php() {
+        let parser = create_test_parser();
+        let content = r#"<?php get_header(); $cls = 'class="m-2"'; ?>
+<?xml version="1.0"?>
+<article <?php post_class(); ?> class="flex p-4 <?php echo $featured ? "ring-2" : ""; ?> mt-2">
+  <h2 class="<?= esc_attr($size) ?> font-bold text-xl"><?php the_title(); ?></h2>
+</article>
+<?php get_footer();
+echo '<div class="m-4">';"#;
+
+        let matches = parser.parse_php(content);
+        let contents: Vec<&str> = matches.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["flex p-4", "mt-2", "font-bold text-xl"]);
+        for m in &matches {
+            assert_eq!(&content[m.start..m.end], m.content);
+        }
+    }
+
+    #[test]
+    fn test_mask_php_code() {
+        let content = "<p class=\"<?= $a ?>\">é<?php\necho 1;";
+        let masked = mask_php_code(content);
+        assert_eq!(masked.len(), content.len());
+        assert_eq!(masked, "<p class=\"{       }\">é{           }");
+    }
+
+    #[test]
+    fn test_mask_blocks() {
+        let blocks = [
+            ("{%".to_string(), "%}".to_string()),
+            ("{{".to_string(), "}}".to_string()),
+        ];
+        let content = "<p class=\"{{ a }}\">{% if x %}é{{ \"%}\" }}{% end";
+        let masked = mask_blocks(content, &blocks);
+        assert_eq!(masked.len(), content.len());
+        assert_eq!(masked, "<p class=\"{     }\">{        }é{        }{    }");
+        assert_eq!(mask_blocks(content, &[]), content);
+    }
+
+    #[test]
+    fn test_parse_custom_format() {
+        let custom = CustomFormat {
+            extensions: vec!["njk".to_string()],
+            skip_blocks: vec![
+                ("{%".to_string(), "%}".to_string()),
+                ("{{".to_string(), "}}".to_string()),
+            ],
+            skip_script_and_style: false,
+        };
+        let parser = create_test_parser().with_custom_formats(vec![custom.clone()]);
+        let content = r#"{% set cls = "m-2" %}
+<div class="flex p-4 {{ extra }} mt-2">{{ title }}</div>
+<script>const a = clsx("z-10 p-4");</script>"#;
+
+        let matches = parser.parse(content, FileFormat::Custom(0));
+        let contents: Vec<&str> = matches.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["flex p-4", "mt-2", "z-10 p-4"]);
+        for m in &matches {
+            assert_eq!(&content[m.start..m.end], m.content);
+        }
+
+        // Script and style elements can be left alone
+        let parser = create_test_parser().with_custom_formats(vec![CustomFormat {
+            skip_script_and_style: true,
+            ..custom
+        }]);
+        let matches = parser.parse(content, FileFormat::Custom(0));
+        let contents: Vec<&str> = matches.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["flex p-4", "mt-2"]);
+    }
+
+    #[test]
+    fn test_parse_razor() {
+        let extractor = ClassExtractor::new(vec![], vec!["class".to_string()])
+            .with_builders(vec!["AddClass".to_string()])
+            .with_diagnostics(true);
+        let parser = FormatParser::new(extractor);
+        let content = r#"<nav class="flex p-4">
+  <a class="p-2 @(IsActive ? "font-bold" : "")" href="@Url">@Title</a>
+  <span class="@@container m-2"></span>
+</nav>
+
+@code {
+    string Css => new CssBuilder().AddClass("z-10 p-4").Build();
+}
+"#;
+
+        let matches = parser.parse_razor(content);
+        let contents: Vec<&str> = matches.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["flex p-4", "z-10 p-4"]);
+        let skipped = parser
+            .extractor()
+            .diagnostics()
+            .events()
+            .into_iter()
+            .filter(|event| {
+                matches!(
+                    event,
+                    Event::Skipped {
+                        reason: SkipReason::DynamicContent,
+                        ..
+                    }
+                )
+            })
+            .count();
+        assert_eq!(skipped, 2);
+    }
+
     #[test]
     fn test_parse_astro() {
         let parser = create_test_parser();
@@ -434,6 +1328,19 @@ const title = "Hello";
         assert!(section.content.contains("<div>Hello</div>"));
     }
 
+    #[test]
+    fn test_sections_borrow_file_ranges() {
+        let content = "<div class=\"a\"></div><script>x</script><div class=\"b\"></div>";
+
+        for section in extract_svelte_markup_sections(content) {
+            assert_eq!(&content[section.range.clone()], section.content);
+        }
+
+        let vue = "<template><div class=\"a\"></div></template>";
+        let section = extract_vue_template(vue).unwrap();
+        assert_eq!(&vue[section.range], "<div class=\"a\"></div>");
+    }
+
     #[test]
     fn test_find_astro_frontmatter_end() {
         let content = "---\nconst x = 1;\n---\n<div>Hi</div>";
@@ -482,6 +1389,47 @@ const title = "Hello";
         assert!(sections[2].content.contains("class=\"c\""));
     }
 
+    #[test]
+    fn test_svelte_markup_sections_skip_script_strings() {
+        let content = r#"<script lang="ts" context="module">
+  export const tag = "</script>";
+  const quote = /'/;
+</script>
+<script lang="ts" generics="T extends Record<string, unknown>">
+  // no </script> here either
+  let { items }: { items: T[] } = $props();
+</script>
+<div class="a"></div>
+<style>
+  p::before { content: "</style>"; }
+</style>
+<div class="b"></div>"#;
+
+        let sections = extract_svelte_markup_sections(content);
+        let markup: Vec<&str> = sections
+            .iter()
+            .map(|s| s.content.trim())
+            .filter(|s| !s.is_empty())
+            .collect();
+        assert_eq!(
+            markup,
+            [r#"<div class="a"></div>"#, r#"<div class="b"></div>"#]
+        );
+    }
+
+    #[test]
+    fn test_svelte_markup_sections_unclosed_script() {
+        let content = r#"<div class="a"></div>
+<scripts class="b"></scripts>
+<script>
+  const markup = '<div class="c">';"#;
+
+        let sections = extract_svelte_markup_sections(content);
+        assert_eq!(sections.len(), 1);
+        assert!(sections[0].content.contains("class=\"b\""));
+        assert!(!sections[0].content.contains("class=\"c\""));
+    }
+
     #[test]
     fn test_parse_preserves_positions() {
         let parser = create_test_parser();
@@ -497,6 +1445,75 @@ const title = "Hello";
         }
     }
 
+    #[test]
+    fn test_parse_vue_script_blocks() {
+        let content = r#"<template>
+  <Panel class="z-10 p-4" />
+</template>
+
+<script lang="tsx">
+const Panel = () => <div class="flex m-2">{clsx("b a")}</div>;
+</script>
+
+<script>
+export default { render: () => h("span", { class: "mt-2 block" }) };
+</script>
+"#;
+
+        let parser = FormatParser::new(
+            ClassExtractor::new(vec!["clsx".to_string()], vec!["class".to_string()])
+                .with_factories(vec!["h".to_string()]),
+        );
+        let matches = parser.parse_vue(content);
+        let class_strings: Vec<&str> = matches.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(
+            class_strings,
+            vec!["z-10 p-4", "flex m-2", "b a", "mt-2 block"]
+        );
+    }
+
+    #[test]
+    fn test_extract_vue_scripts() {
+        let vue = "<script setup lang='tsx'>a</script><template></template><script>b</script>";
+        let scripts = extract_vue_scripts(vue);
+
+        assert_eq!(scripts.len(), 2);
+        assert_eq!(scripts[0].section.content, "a");
+        assert!(scripts[0].jsx);
+        assert_eq!(scripts[1].section.content, "b");
+        assert!(!scripts[1].jsx);
+    }
+
+    #[test]
+    fn test_extract_vue_scripts_generic_attribute() {
+        let vue = r#"<script setup lang="tsx" generic="T extends Record<string, number>">
+const a = <div class="z-10 p-4" />;
+</script>"#;
+        let scripts = extract_vue_scripts(vue);
+
+        assert_eq!(scripts.len(), 1);
+        assert!(scripts[0].jsx);
+        assert!(scripts[0].section.content.starts_with("\nconst a"));
+    }
+
+    #[test]
+    fn test_extract_vue_scripts_closing_tag_in_string() {
+        let vue = r#"<template><div></div></template>
+<script>
+const tag = "</script>";
+const b = clsx("z-10 p-4");
+</script>"#;
+        let scripts = extract_vue_scripts(vue);
+
+        assert_eq!(scripts.len(), 1);
+        assert!(scripts[0].section.content.contains("clsx(\"z-10 p-4\")"));
+
+        let parser = create_test_parser();
+        let matches = parser.parse_vue(vue);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(&vue[matches[0].start..matches[0].end], "z-10 p-4");
+    }
+
     #[test]
     fn test_parse_vue_without_template() {
         let parser = create_test_parser();
@@ -507,4 +1524,57 @@ const title = "Hello";
         assert_eq!(matches.len(), 1);
         assert_eq!(matches[0].content, "flex p-4");
     }
+
+    #[test]
+    fn test_code_fences() {
+        let content = "# Title\n\n```html title=\"a\"\n<b></b>\n```\n\n  ~~~~rust\nfn main() {}\n```\n~~~~~\n\n````\nopen";
+        let fences = code_fences(content);
+
+        assert_eq!(fences.len(), 3);
+        assert_eq!(fences[0].lang, "html");
+        assert_eq!(&content[fences[0].body.clone()], "<b></b>\n");
+        assert!(content[fences[0].range.clone()].ends_with("```\n"));
+        assert!(!fences[0].indented);
+
+        // A shorter or different fence doesn't close the block
+        assert_eq!(fences[1].lang, "rust");
+        assert_eq!(&content[fences[1].body.clone()], "fn main() {}\n```\n");
+        assert!(fences[1].indented);
+
+        // An unclosed block runs to the end
+        assert_eq!(fences[2].lang, "");
+        assert_eq!(&content[fences[2].body.clone()], "open");
+        assert_eq!(fences[2].range.end, content.len());
+    }
+
+    #[test]
+    fn test_fence_format() {
+        assert_eq!(fence_format("HTML"), Some(FileFormat::Html));
+        assert_eq!(fence_format("jsx"), Some(FileFormat::Jsx));
+        assert_eq!(fence_format("typescript"), Some(FileFormat::Ts));
+        assert_eq!(fence_format("rust"), None);
+        assert_eq!(fence_format(""), None);
+    }
+
+    #[test]
+    fn test_parse_markdown() {
+        let parser = create_test_parser();
+        let content = r##"<div class="p-4 z-10">Prose</div>
+
+```jsx
+<Button className="mt-2 flex" />
+```
+
+```rust
+let html = r#"<a class="z-10 p-4">"#;
+```
+"##;
+        let matches = parser.parse(content, FileFormat::Markdown);
+
+        let found: Vec<&str> = matches.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(found, vec!["p-4 z-10", "mt-2 flex"]);
+        for m in &matches {
+            assert_eq!(&content[m.start..m.end], m.content);
+        }
+    }
 }
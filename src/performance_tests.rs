@@ -217,6 +217,19 @@ mod performance_tests {
         assert!(result.contains("bg-blue-500"));
     }
 
+    #[test]
+    fn test_many_distinct_classes() {
+        // Grouping duplicates must not rescan the sorted list per class
+        let classes: String = (0..20_000).map(|i| format!("mt-[{i}px] ")).collect();
+
+        let start = Instant::now();
+        let result = sort_classes(&classes);
+        let duration = start.elapsed();
+
+        assert!(duration.as_millis() < 500, "Took too long: {:?}", duration);
+        assert_eq!(result.split_whitespace().count(), 20_000);
+    }
+
     #[test]
     fn test_complex_variant_combinations() {
         // Test performance with many complex variant combinations
@@ -20,10 +20,9 @@ mod plugin_ecosystem_tests {
 
     fn create_test_config() -> Configuration {
         Configuration {
-            enabled: true,
-            tailwind_config: None,
             tailwind_functions: vec!["clsx".to_string(), "cn".to_string()],
             tailwind_attributes: vec!["class".to_string(), "className".to_string()],
+            ..Configuration::default()
         }
     }
 
@@ -84,6 +83,39 @@ class: z-10 p-4 mt-2
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_json_and_yaml_class_properties_opt_in() {
+        let mut handler = create_test_handler();
+        let config = Configuration {
+            json_class_properties: vec!["wrapperClasses".to_string()],
+            ..create_test_config()
+        };
+
+        let json_content = r#"{
+  "hero": { "wrapperClasses": "z-10 p-4 mt-2", "class": "p-4 z-10" },
+  "cards": { "wrapperClasses": ["p-4 z-10", "flex"] }
+}"#;
+        let result = format_file(&mut handler, "tokens.json", json_content, config.clone());
+        assert_eq!(
+            result.unwrap(),
+            r#"{
+  "hero": { "wrapperClasses": "z-10 mt-2 p-4", "class": "p-4 z-10" },
+  "cards": { "wrapperClasses": ["z-10 p-4", "flex"] }
+}"#
+        );
+
+        let yaml_content = "hero:\n  wrapperClasses: z-10 p-4 mt-2\n  class: p-4 z-10\n";
+        let result = format_file(&mut handler, "content.yml", yaml_content, config.clone());
+        assert_eq!(
+            result.unwrap(),
+            "hero:\n  wrapperClasses: z-10 mt-2 p-4\n  class: p-4 z-10\n"
+        );
+
+        // TOML is still left to its own plugin
+        assert!(PluginCompatibility::new(&config).should_defer("Cargo.toml"));
+        assert!(!PluginCompatibility::new(&config).should_defer("tokens.json"));
+    }
+
     #[test]
     fn test_html_files_formatted() {
         let mut handler = create_test_handler();
@@ -96,7 +128,7 @@ class: z-10 p-4 mt-2
         // HTML files should be formatted
         assert!(result.is_some());
         let formatted = result.unwrap();
-        assert!(formatted.contains("mt-2 p-4 z-10"));
+        assert!(formatted.contains("z-10 mt-2 p-4"));
     }
 
     #[test]
@@ -111,7 +143,7 @@ class: z-10 p-4 mt-2
         // JSX files should be formatted
         assert!(result.is_some());
         let formatted = result.unwrap();
-        assert!(formatted.contains("mt-2 p-4 z-10"));
+        assert!(formatted.contains("z-10 mt-2 p-4"));
     }
 
     #[test]
@@ -134,7 +166,7 @@ class: z-10 p-4 mt-2
         let html_result = format_file(
             &mut handler,
             "index.html",
-            r#"<div class="z-10 p-4">Test</div>"#,
+            r#"<div class="p-4 z-10">Test</div>"#,
             config,
         );
         assert!(html_result.is_some(), "Should format HTML files");
@@ -155,7 +187,7 @@ const classes = clsx("z-10 p-4 mt-2");
         // Should format utility functions in TS files
         assert!(result.is_some());
         let formatted = result.unwrap();
-        assert!(formatted.contains("mt-2 p-4 z-10"));
+        assert!(formatted.contains("z-10 mt-2 p-4"));
     }
 
     #[test]
@@ -173,7 +205,7 @@ const classes = clsx("z-10 p-4 mt-2");
         // Markdown with HTML should be formatted
         assert!(result.is_some());
         let formatted = result.unwrap();
-        assert!(formatted.contains("mt-2 p-4 z-10"));
+        assert!(formatted.contains("z-10 mt-2 p-4"));
     }
 
     #[test]
@@ -290,13 +322,13 @@ const classes = clsx("z-10 p-4 mt-2");
     #[test]
     fn test_plugin_compatibility_checks() {
         // Test the PluginCompatibility helper functions
-        assert!(PluginCompatibility::should_format("index.html"));
-        assert!(PluginCompatibility::should_format("App.jsx"));
-        assert!(!PluginCompatibility::should_format("config.json"));
+        assert!(PluginCompatibility::new(&Configuration::default()).should_format("index.html"));
+        assert!(PluginCompatibility::new(&Configuration::default()).should_format("App.jsx"));
+        assert!(!PluginCompatibility::new(&Configuration::default()).should_format("config.json"));
 
-        assert!(PluginCompatibility::should_defer("config.json"));
-        assert!(PluginCompatibility::should_defer("Cargo.toml"));
-        assert!(!PluginCompatibility::should_defer("index.html"));
+        assert!(PluginCompatibility::new(&Configuration::default()).should_defer("config.json"));
+        assert!(PluginCompatibility::new(&Configuration::default()).should_defer("Cargo.toml"));
+        assert!(!PluginCompatibility::new(&Configuration::default()).should_defer("index.html"));
     }
 
     #[test]
@@ -312,7 +344,7 @@ const classes = clsx("z-10 p-4 mt-2");
         // Should still attempt formatting with fallback
         assert!(result.is_some());
         let formatted = result.unwrap();
-        assert!(formatted.contains("mt-2 p-4 z-10"));
+        assert!(formatted.contains("z-10 mt-2 p-4"));
     }
 
     #[test]
@@ -337,7 +369,7 @@ const x = 1;
         let formatted = result.unwrap();
 
         // Only class in div should be sorted, script/style untouched
-        assert!(formatted.contains("mt-2 p-4 z-10"));
+        assert!(formatted.contains("z-10 mt-2 p-4"));
         assert!(formatted.contains("const x = 1;"));
         assert!(formatted.contains(".test { color: red; }"));
     }
@@ -385,4 +417,85 @@ const className = "z-10 p-4 mt-2"; // Should NOT be sorted
         // Should return None (no changes needed) since it's already formatted
         assert!(result2.is_none());
     }
+
+    /// Simulate a markup formatter wrapping every class attribute so each
+    /// class sits on its own line, as markup_fmt does for long attributes
+    fn reflow_class_attributes(content: &str) -> String {
+        let mut result = String::with_capacity(content.len() * 2);
+        let mut rest = content;
+        while let Some(start) = rest.find("class=\"") {
+            let value_start = start + "class=\"".len();
+            let value_end = value_start + rest[value_start..].find('"').unwrap();
+            result.push_str(&rest[..value_start]);
+            let classes: Vec<&str> = rest[value_start..value_end].split_whitespace().collect();
+            result.push_str("\n    ");
+            result.push_str(&classes.join("\n    "));
+            result.push_str("\n  ");
+            rest = &rest[value_end..];
+        }
+        result.push_str(rest);
+        result
+    }
+
+    #[test]
+    fn test_reflowed_sorted_classes_left_unchanged() {
+        let mut handler = create_test_handler();
+        let config = create_test_config();
+
+        let content = r#"<div class="z-10 flex items-center mt-2 p-4">Content</div>"#;
+        let reflowed = reflow_class_attributes(content);
+        assert!(reflowed.contains("class=\"\n    z-10\n    flex"));
+
+        // Another formatter's line breaks are not a reason to rewrite
+        let result = format_file(&mut handler, "index.html", &reflowed, config);
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_chained_reflow_round_trip_is_stable() {
+        let mut handler = create_test_handler();
+        let config = create_test_config();
+
+        let content = r#"<div class="z-10 p-4 mt-2 flex">
+  <span class="text-sm font-bold hover:underline">Content</span>
+</div>"#;
+
+        // Sort, let the markup formatter re-wrap, then sort again
+        let sorted = format_file(&mut handler, "index.html", content, config.clone()).unwrap();
+        let reflowed = reflow_class_attributes(&sorted);
+        let result = format_file(&mut handler, "index.html", &reflowed, config.clone());
+        assert!(result.is_none());
+
+        // Reflowing and sorting in the other order converges on the same text
+        let reflowed_first = reflow_class_attributes(content);
+        let sorted_after =
+            format_file(&mut handler, "index.html", &reflowed_first, config).unwrap();
+        assert_eq!(reflow_class_attributes(&sorted_after), reflowed);
+    }
+
+    #[test]
+    fn test_reflowed_unsorted_classes_sorted_identically() {
+        let mut handler = create_test_handler();
+        let config = create_test_config();
+
+        let content = r#"<div class="z-10 p-4 mt-2">Content</div>"#;
+        let reflowed = reflow_class_attributes(content);
+
+        let from_single_line =
+            format_file(&mut handler, "index.html", content, config.clone()).unwrap();
+        let from_reflowed = format_file(&mut handler, "index.html", &reflowed, config).unwrap();
+        assert_eq!(from_single_line, from_reflowed);
+        assert!(from_reflowed.contains(r#"class="z-10 mt-2 p-4""#));
+    }
+
+    #[test]
+    fn test_reflowed_jsx_class_name_left_unchanged() {
+        let mut handler = create_test_handler();
+        let config = create_test_config();
+
+        let content = "export const App = () => (\n  <div\n    className=\"flex\n      mt-2\n      p-4\"\n  />\n);";
+
+        let result = format_file(&mut handler, "App.tsx", content, config);
+        assert!(result.is_none());
+    }
 }
@@ -283,14 +283,14 @@ mod prettier_compat {
     fn test_tw_tagged_template_extraction() {
         let extractor = ClassExtractor::new(vec!["tw".to_string()], vec!["class".to_string()]);
 
-        // Note: Template literals (backticks) are not currently supported
-        // by the basic regex extraction. This would require more sophisticated parsing.
+        // Like prettier's tailwindFunctions, function names also match
+        // tagged templates
         let code = r#"tw`sm:p-0 p-0`"#;
         let matches = extractor.extract_all(code);
 
-        // Current behavior: template literals are not extracted
-        // This is a known limitation that could be addressed in future versions
-        assert_eq!(matches.len(), 0);
+        assert_eq!(matches.len(), 1);
+        let sorted = sort_classes(&matches[0].content);
+        assert_eq!(sorted, "p-0 sm:p-0");
     }
 
     #[test]
@@ -0,0 +1,263 @@
+//! Property tests for extraction, rewriting and sorting
+//!
+//! Random documents are assembled from fixed text and class lists whose
+//! positions are known, so every run can check that text outside of class
+//! lists comes out byte for byte, that class lists only have their classes
+//! reordered and that formatting twice changes nothing.
+
+use crate::config::Configuration;
+use crate::sorter::sort_classes;
+use crate::TailwindCssPluginHandler;
+use dprint_core::plugins::{
+    FormatConfigId, NullCancellationToken, SyncFormatRequest, SyncPluginHandler,
+};
+use proptest::prelude::*;
+
+/// Piece of a generated document
+#[derive(Debug, Clone)]
+enum Segment {
+    /// Text that must come out unchanged
+    Text(String),
+    /// Class list, followed by a `Text` starting with its closing quote
+    Classes(String),
+}
+
+const CLASSES: &[&str] = &[
+    "flex",
+    "block",
+    "hidden",
+    "grid",
+    "absolute",
+    "relative",
+    "z-10",
+    "p-4",
+    "px-2",
+    "mt-2",
+    "-mb-4",
+    "w-full",
+    "h-4",
+    "gap-2",
+    "items-center",
+    "justify-between",
+    "text-lg",
+    "text-red-500",
+    "font-bold",
+    "bg-white",
+    "bg-blue-500",
+    "rounded",
+    "border",
+    "shadow",
+    "opacity-50",
+    "transition",
+    "w-[10px]",
+    "bg-[#fff]",
+    "grid-cols-[1fr_2fr]",
+    "[mask-type:luminance]",
+    "group",
+    "peer",
+    "custom",
+    "btn-primary",
+    "...",
+    "*",
+];
+
+const VARIANTS: &[&str] = &[
+    "",
+    "",
+    "",
+    "hover:",
+    "focus:",
+    "md:",
+    "dark:",
+    "lg:hover:",
+    "group-hover:",
+    "max-sm:",
+    "[&>*]:",
+];
+
+const SEPARATORS: &[&str] = &[" ", " ", " ", "  ", "\n", "\t", "\n  "];
+
+fn class() -> impl Strategy<Value = String> {
+    (
+        any::<bool>(),
+        prop::sample::select(VARIANTS),
+        prop::sample::select(CLASSES),
+    )
+        .prop_map(|(important, variant, class)| {
+            let important = if important { "!" } else { "" };
+            format!("{important}{variant}{class}")
+        })
+}
+
+/// Class list with irregular whitespace, possibly empty
+fn class_list() -> impl Strategy<Value = String> {
+    prop::collection::vec(
+        (prop::sample::select(SEPARATORS), class(), any::<bool>()),
+        0..10,
+    )
+    .prop_map(|parts| {
+        let mut list = String::new();
+        for (separator, class, leading) in parts {
+            if !list.is_empty() || leading {
+                list.push_str(separator);
+            }
+            list.push_str(&class);
+        }
+        list
+    })
+}
+
+/// Text between elements, without markup or quotes
+fn filler() -> impl Strategy<Value = String> {
+    prop::collection::vec(
+        prop::sample::select(vec![
+            "a", "z", "0", " ", "\n", ".", ",", "-", "!", "é", "日", "text", "class",
+        ]),
+        0..6,
+    )
+    .prop_map(|parts| parts.concat())
+}
+
+fn html_element() -> impl Strategy<Value = Vec<Segment>> {
+    (
+        prop::sample::select(vec!["div", "p", "span", "my-button"]),
+        prop::sample::select(vec!['"', '\'']),
+        class_list(),
+        filler(),
+    )
+        .prop_map(|(tag, quote, classes, content)| {
+            vec![
+                Segment::Text(format!("<{tag} class={quote}")),
+                Segment::Classes(classes),
+                Segment::Text(format!("{quote}>{content}</{tag}>")),
+            ]
+        })
+}
+
+fn jsx_element() -> impl Strategy<Value = Vec<Segment>> {
+    (0..3usize, class_list(), filler()).prop_map(|(kind, classes, content)| match kind {
+        0 => vec![
+            Segment::Text("<div className=\"".to_string()),
+            Segment::Classes(classes),
+            Segment::Text(format!("\">{content}</div>")),
+        ],
+        1 => vec![
+            Segment::Text("<div className={clsx(\"".to_string()),
+            Segment::Classes(classes),
+            Segment::Text(format!("\")}}>{content}</div>")),
+        ],
+        _ => vec![
+            Segment::Text("const styles = clsx('".to_string()),
+            Segment::Classes(classes),
+            Segment::Text("');\n".to_string()),
+        ],
+    })
+}
+
+fn document(element: impl Strategy<Value = Vec<Segment>>) -> impl Strategy<Value = Vec<Segment>> {
+    prop::collection::vec((filler(), element), 0..6).prop_map(|parts| {
+        parts
+            .into_iter()
+            .flat_map(|(text, element)| std::iter::once(Segment::Text(text)).chain(element))
+            .collect()
+    })
+}
+
+fn render(segments: &[Segment]) -> String {
+    segments
+        .iter()
+        .map(|segment| match segment {
+            Segment::Text(text) | Segment::Classes(text) => text.as_str(),
+        })
+        .collect()
+}
+
+/// Format a file with the default configuration, returning the input if
+/// nothing changed
+fn format(text: &str, file_name: &str) -> String {
+    let mut handler = TailwindCssPluginHandler::new();
+    let config = Configuration::default();
+    let request = SyncFormatRequest {
+        file_path: std::path::Path::new(file_name),
+        file_bytes: text.as_bytes().to_vec(),
+        range: None,
+        config: &config,
+        config_id: FormatConfigId::from_raw(0),
+        token: &NullCancellationToken,
+    };
+    match handler.format(request, |_| Ok(None)).unwrap() {
+        Some(bytes) => String::from_utf8(bytes).unwrap(),
+        None => text.to_string(),
+    }
+}
+
+fn sorted_tokens(classes: &str) -> Vec<&str> {
+    let mut tokens: Vec<&str> = classes.split_whitespace().collect();
+    tokens.sort_unstable();
+    tokens
+}
+
+fn without_whitespace(text: &str) -> usize {
+    text.chars().filter(|c| !c.is_whitespace()).count()
+}
+
+/// Walk the output along the segments of the input: text must come out
+/// unchanged and class lists as a permutation of their classes
+fn check_segments(segments: &[Segment], output: &str) -> Result<(), TestCaseError> {
+    let mut rest = output;
+    for (index, segment) in segments.iter().enumerate() {
+        match segment {
+            Segment::Text(text) => {
+                prop_assert!(
+                    rest.starts_with(text.as_str()),
+                    "text {text:?} changed, output continues with {rest:?}"
+                );
+                rest = &rest[text.len()..];
+            }
+            Segment::Classes(classes) => {
+                let quote = match &segments[index + 1] {
+                    Segment::Text(text) => text.chars().next().unwrap(),
+                    Segment::Classes(_) => unreachable!(),
+                };
+                let end = rest.find(quote).unwrap_or(rest.len());
+                prop_assert_eq!(sorted_tokens(&rest[..end]), sorted_tokens(classes));
+                rest = &rest[end..];
+            }
+        }
+    }
+    prop_assert_eq!(rest, "");
+    Ok(())
+}
+
+fn check_format(segments: &[Segment], file_name: &str) -> Result<(), TestCaseError> {
+    let input = render(segments);
+    let output = format(&input, file_name);
+
+    check_segments(segments, &output)?;
+    prop_assert_eq!(without_whitespace(&output), without_whitespace(&input));
+    prop_assert_eq!(format(&output, file_name), output);
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn sorting_is_an_idempotent_permutation(classes in class_list()) {
+        let sorted = sort_classes(&classes);
+
+        prop_assert_eq!(sorted_tokens(&sorted), sorted_tokens(&classes));
+        prop_assert!(!sorted.contains("  ") && sorted.trim() == sorted);
+        prop_assert_eq!(sort_classes(&sorted), sorted.clone());
+    }
+
+    #[test]
+    fn html_documents_only_reorder_classes(segments in document(html_element())) {
+        check_format(&segments, "page.html")?;
+    }
+
+    #[test]
+    fn jsx_documents_only_reorder_classes(segments in document(jsx_element())) {
+        check_format(&segments, "page.jsx")?;
+    }
+}
@@ -0,0 +1,1848 @@
+/// Linear-time scanner for class candidates
+///
+/// This module replaces the regex-based candidate search with a hand-written
+/// scanner. Every delimiter search remembers its last answer, so inputs with
+/// long runs of unterminated quotes, braces or parentheses are still scanned
+/// in linear time, keeping worst-case behavior predictable inside the WASM
+/// sandbox.
+use std::ops::Range;
+
+mod css;
+mod data;
+mod python;
+
+pub use css::source_inline_arguments;
+pub use data::{json_array_elements, json_object_members, json_string, yaml_property_values};
+pub use python::python_class_values;
+
+/// Kind of candidate found by the scanner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CandidateKind {
+    /// Quoted attribute value: class="..." or class='...'
+    AttributeValue,
+    /// Braced attribute expression: className={...}
+    AttributeExpression,
+    /// Bound attribute expression: :class="..." (Vue, Alpine.js)
+    AttributeBinding,
+    /// Function call arguments: clsx(...)
+    FunctionArguments,
+    /// Element factory call arguments: createElement(...), h(...)
+    FactoryArguments,
+    /// Class builder call arguments: .AddClass(...), new CssBuilder(...)
+    BuilderArguments,
+    /// Tagged template contents: tw`...`
+    TaggedTemplate,
+}
+
+/// A candidate region found by the scanner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub kind: CandidateKind,
+    /// Byte position where the attribute or function name starts
+    pub name_start: usize,
+    /// Byte range of the value, expression or arguments (without delimiters)
+    pub range: Range<usize>,
+}
+
+/// Scanner for configured attribute and function names
+pub struct CandidateScanner {
+    /// Attribute names, longest first
+    attribute_names: Vec<String>,
+    /// Function names, longest first
+    function_names: Vec<String>,
+    /// Tagged template names, longest first
+    template_names: Vec<String>,
+    /// Element factory names, longest first
+    factory_names: Vec<String>,
+    /// Class builder method and constructor names, longest first
+    builder_names: Vec<String>,
+    /// Bytes that can start any configured name
+    first_bytes: [bool; 256],
+}
+
+impl CandidateScanner {
+    pub fn new(
+        function_names: &[String],
+        template_names: &[String],
+        attribute_names: &[String],
+    ) -> Self {
+        let attribute_names = longest_first(attribute_names);
+        let function_names = longest_first(function_names);
+        let template_names = longest_first(template_names);
+
+        let mut first_bytes = [false; 256];
+        for name in attribute_names
+            .iter()
+            .chain(function_names.iter())
+            .chain(template_names.iter())
+        {
+            first_bytes[name.as_bytes()[0] as usize] = true;
+        }
+
+        Self {
+            attribute_names,
+            function_names,
+            template_names,
+            factory_names: Vec::new(),
+            builder_names: Vec::new(),
+            first_bytes,
+        }
+    }
+
+    /// Also find calls to element factories such as `createElement` or `h`
+    pub fn with_factories(mut self, factory_names: &[String]) -> Self {
+        self.factory_names = longest_first(factory_names);
+        for name in &self.factory_names {
+            self.first_bytes[name.as_bytes()[0] as usize] = true;
+        }
+        self
+    }
+
+    /// Also find class builder calls such as Blazor's `.AddClass(...)`
+    ///
+    /// Builder names only match as a method call in a chain or as a
+    /// constructor call, as in `new CssBuilder(...)`.
+    pub fn with_builders(mut self, builder_names: &[String]) -> Self {
+        self.builder_names = longest_first(builder_names);
+        for name in &self.builder_names {
+            self.first_bytes[name.as_bytes()[0] as usize] = true;
+        }
+        self
+    }
+
+    /// Find all non-overlapping candidates in source order
+    ///
+    /// At each position attribute names are tried before function names,
+    /// function names before factory, builder and template names, and
+    /// longer names before shorter ones, so `className` wins over `class`.
+    /// Like prettier's `tailwindFunctions`, function names match both calls
+    /// and tagged templates; template names only match tagged templates.
+    pub fn scan(&self, content: &str) -> Vec<Candidate> {
+        let bytes = content.as_bytes();
+        let mut double_quotes = NextDelimiter::new(b"\"");
+        let mut single_quotes = NextDelimiter::new(b"'");
+        let mut braces = ClosingDelimiter::new(b"}");
+        let mut parens = ClosingDelimiter::new(b")");
+        let mut backticks = ClosingDelimiter::new(b"`");
+
+        let mut candidates = Vec::new();
+        let mut pos = 0;
+
+        while pos < bytes.len() {
+            if !self.first_bytes[bytes[pos] as usize] {
+                pos += 1;
+                continue;
+            }
+
+            let before = pos.checked_sub(1).map(|i| bytes[i]);
+
+            let attribute_names: &[String] = if is_attribute_boundary(before) {
+                &self.attribute_names
+            } else {
+                &[]
+            };
+            let (function_names, factory_names, template_names): (&[String], &[String], &[String]) =
+                if before.is_some_and(is_identifier_byte) {
+                    (&[], &[], &[])
+                } else {
+                    (
+                        &self.function_names,
+                        &self.factory_names,
+                        &self.template_names,
+                    )
+                };
+
+            let found = attribute_names
+                .iter()
+                .filter(|name| bytes[pos..].starts_with(name.as_bytes()))
+                .find_map(|name| {
+                    let mut after = pos + name.len();
+                    // `:class`, or `v-bind:class` in long form, binds an
+                    // expression and is a distinct attribute from a static
+                    // `class` on the same element. Bindings may carry Vue
+                    // modifiers, as in `v-bind:class.prop`
+                    let kind = if before == Some(b':') {
+                        after = skip_binding_modifiers(bytes, after);
+                        CandidateKind::AttributeBinding
+                    } else {
+                        CandidateKind::AttributeValue
+                    };
+                    attribute_value(bytes, after, &mut double_quotes, &mut single_quotes)
+                        .map(|found| (kind, found))
+                        .or_else(|| {
+                            attribute_expression(bytes, after, &mut braces)
+                                .map(|found| (CandidateKind::AttributeExpression, found))
+                        })
+                })
+                .or_else(|| {
+                    function_names
+                        .iter()
+                        .filter(|name| bytes[pos..].starts_with(name.as_bytes()))
+                        .find_map(|name| {
+                            let after = pos + name.len();
+                            function_arguments(bytes, after, &mut parens)
+                                .map(|found| (CandidateKind::FunctionArguments, found))
+                                .or_else(|| {
+                                    tagged_template(bytes, after, &mut backticks)
+                                        .map(|found| (CandidateKind::TaggedTemplate, found))
+                                })
+                        })
+                })
+                .or_else(|| {
+                    factory_names
+                        .iter()
+                        .filter(|name| bytes[pos..].starts_with(name.as_bytes()))
+                        .find_map(|name| function_arguments(bytes, pos + name.len(), &mut parens))
+                        .map(|found| (CandidateKind::FactoryArguments, found))
+                })
+                .or_else(|| {
+                    if self.builder_names.is_empty() || !is_builder_call(bytes, pos) {
+                        return None;
+                    }
+                    self.builder_names
+                        .iter()
+                        .filter(|name| bytes[pos..].starts_with(name.as_bytes()))
+                        .find_map(|name| function_arguments(bytes, pos + name.len(), &mut parens))
+                        .map(|found| (CandidateKind::BuilderArguments, found))
+                })
+                .or_else(|| {
+                    template_names
+                        .iter()
+                        .filter(|name| bytes[pos..].starts_with(name.as_bytes()))
+                        .find_map(|name| tagged_template(bytes, pos + name.len(), &mut backticks))
+                        .map(|found| (CandidateKind::TaggedTemplate, found))
+                });
+
+            match found {
+                Some((kind, (range, end))) => {
+                    candidates.push(Candidate {
+                        kind,
+                        name_start: pos,
+                        range,
+                    });
+                    pos = end;
+                }
+                None => pos += 1,
+            }
+        }
+
+        candidates
+    }
+}
+
+/// Find every quoted string literal in an expression or argument list
+///
+/// A literal opened by `"`, `'` or `` ` `` ends at the same unescaped quote,
+/// and template literals end after any `${...}` holes. Literals compared
+/// with `==`, `===`, `!=` or `!==` are not class lists and are skipped, so in
+/// `variant === "primary" && "p-4 z-10"` only the `&&` operand is returned.
+/// Returned ranges exclude the quotes.
+pub fn string_literals(content: &str) -> Vec<Range<usize>> {
+    let bytes = content.as_bytes();
+    let mut literals = Vec::new();
+    let mut pos = 0;
+
+    while let Some(open) = find_byte(bytes, pos, b"\"'`") {
+        let close = match bytes[open] {
+            b'`' => closing_template(bytes, open + 1),
+            quote => closing_quote(bytes, open + 1, quote),
+        };
+        let Some(close) = close else {
+            break;
+        };
+        if !is_comparison_operand(bytes, open, close + 1) {
+            literals.push(open + 1..close);
+        }
+        pos = close + 1;
+    }
+
+    literals
+}
+
+/// Find the values of object literal properties named in `names`, such as
+/// `className` in `createElement("div", { className: "p-4" })`
+///
+/// Keys may be quoted, as in `{ "class": "p-4" }`. Returned ranges cover the
+/// whole value expression up to the next `,` or closing bracket.
+pub fn object_property_values(content: &str, names: &[String]) -> Vec<Range<usize>> {
+    let bytes = content.as_bytes();
+    let mut values = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            // Property keys follow `{` or `,`
+            b'{' | b',' => {
+                let key = skip_whitespace(bytes, pos + 1);
+                if let Some(value_start) = property_key(bytes, key, names) {
+                    let value_end = expression_end(bytes, value_start);
+                    values.push(value_start..value_end);
+                    pos = value_end;
+                    continue;
+                }
+            }
+            quote @ (b'"' | b'\'') => {
+                pos = closing_quote(bytes, pos + 1, quote).unwrap_or(bytes.len());
+            }
+            b'`' => pos = closing_template(bytes, pos + 1).unwrap_or(bytes.len()),
+            _ => {}
+        }
+        pos += 1;
+    }
+
+    values
+}
+
+/// Position after the `:` of a `name:` or `"name":` key at `pos`
+fn property_key(bytes: &[u8], pos: usize, names: &[String]) -> Option<usize> {
+    let quote = bytes.get(pos).copied().filter(|&b| b == b'"' || b == b'\'');
+    let start = pos + usize::from(quote.is_some());
+
+    names.iter().find_map(|name| {
+        let mut end = start + name.len();
+        if bytes.get(start..end)? != name.as_bytes() {
+            return None;
+        }
+        match quote {
+            Some(quote) if bytes.get(end) == Some(&quote) => end += 1,
+            Some(_) => return None,
+            None if bytes.get(end).is_some_and(|&b| is_identifier_byte(b)) => return None,
+            None => {}
+        }
+        let colon = skip_whitespace(bytes, end);
+        (bytes.get(colon) == Some(&b':')).then_some(colon + 1)
+    })
+}
+
+/// End of the expression starting at `pos`: the next `,` or unmatched
+/// closing bracket outside of nested brackets and literals
+fn expression_end(bytes: &[u8], mut pos: usize) -> usize {
+    let mut depth = 0usize;
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' if depth == 0 => return pos,
+            b')' | b']' | b'}' => depth -= 1,
+            b',' if depth == 0 => return pos,
+            quote @ (b'"' | b'\'') => match closing_quote(bytes, pos + 1, quote) {
+                Some(close) => pos = close,
+                None => return bytes.len(),
+            },
+            b'`' => match closing_template(bytes, pos + 1) {
+                Some(close) => pos = close,
+                None => return bytes.len(),
+            },
+            _ => {}
+        }
+        pos += 1;
+    }
+
+    bytes.len()
+}
+
+/// Find the template literals holding web component markup in script code
+///
+/// These are `html` tagged templates, as used by Lit and FAST, also with a
+/// type argument as in ``html<MyElement>`...` ``, and template literals
+/// returned from `render()` methods, as in Stencil-style or vanilla custom
+/// elements. Comments and other literals are skipped. Returned ranges
+/// exclude the backticks.
+pub fn markup_templates(content: &str) -> Vec<Range<usize>> {
+    let bytes = content.as_bytes();
+    let mut templates = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'/' if bytes.get(pos + 1) == Some(&b'/') => {
+                pos = find_byte(bytes, pos, b"\n").unwrap_or(bytes.len());
+            }
+            b'/' if bytes.get(pos + 1) == Some(&b'*') => {
+                pos = find_subslice(bytes, pos + 2, b"*/").map_or(bytes.len(), |end| end + 1);
+            }
+            quote @ (b'"' | b'\'') => {
+                pos = closing_quote(bytes, pos + 1, quote).unwrap_or(bytes.len());
+            }
+            b'`' => pos = closing_template(bytes, pos + 1).unwrap_or(bytes.len()),
+            b if is_identifier_byte(b) && (pos == 0 || !is_identifier_byte(bytes[pos - 1])) => {
+                if let Some(open) = html_template_open(bytes, pos) {
+                    let close = closing_template(bytes, open + 1).unwrap_or(bytes.len());
+                    templates.push(open + 1..close);
+                    pos = close;
+                } else if let Some(body) = render_body(bytes, pos) {
+                    // The body is scanned on for `html` templates; returned
+                    // plain templates are skipped there as literals
+                    templates.extend(returned_templates(bytes, body));
+                }
+            }
+            _ => {}
+        }
+        pos += 1;
+    }
+
+    templates.sort_by_key(|template| template.start);
+    templates
+}
+
+/// Position of the backtick of an `html` tagged template at `pos`
+fn html_template_open(bytes: &[u8], pos: usize) -> Option<usize> {
+    let mut pos = skip_whitespace(bytes, identifier_at(bytes, pos, b"html")?);
+    if bytes.get(pos) == Some(&b'<') {
+        pos = skip_whitespace(bytes, find_byte(bytes, pos, b">")? + 1);
+    }
+    (bytes.get(pos) == Some(&b'`')).then_some(pos)
+}
+
+/// Body of a `render()` method at `pos`, from `{` to the matching `}`
+///
+/// A TypeScript return type such as `render(): TemplateResult` may sit
+/// between the parameters and the body.
+fn render_body(bytes: &[u8], pos: usize) -> Option<Range<usize>> {
+    let open = skip_whitespace(bytes, identifier_at(bytes, pos, b"render")?);
+    if bytes.get(open) != Some(&b'(') {
+        return None;
+    }
+    let mut pos = skip_whitespace(bytes, matching_delimiter(bytes, open)? + 1);
+    if bytes.get(pos) == Some(&b':') {
+        pos = find_byte(bytes, pos, b"{;")?;
+    }
+    if bytes.get(pos) != Some(&b'{') {
+        return None;
+    }
+    Some(pos..matching_delimiter(bytes, pos)?)
+}
+
+/// Template literals directly returned within `body`, as in
+/// ``return `...` `` or ``return (`...`)``
+fn returned_templates(bytes: &[u8], body: Range<usize>) -> Vec<Range<usize>> {
+    let mut templates = Vec::new();
+    let mut pos = body.start;
+
+    while pos < body.end {
+        match bytes[pos] {
+            quote @ (b'"' | b'\'') => {
+                pos = closing_quote(bytes, pos + 1, quote).unwrap_or(body.end);
+            }
+            b'`' => pos = closing_template(bytes, pos + 1).unwrap_or(body.end),
+            b'r' if pos == 0 || !is_identifier_byte(bytes[pos - 1]) => {
+                if let Some(end) = identifier_at(bytes, pos, b"return") {
+                    let mut open = skip_whitespace(bytes, end);
+                    if bytes.get(open) == Some(&b'(') {
+                        open = skip_whitespace(bytes, open + 1);
+                    }
+                    if bytes.get(open) == Some(&b'`') {
+                        let close = closing_template(bytes, open + 1).unwrap_or(body.end);
+                        templates.push(open + 1..close);
+                        pos = close;
+                    }
+                }
+            }
+            _ => {}
+        }
+        pos += 1;
+    }
+
+    templates
+}
+
+/// Position after `word` when it is a whole identifier at `pos`
+fn identifier_at(bytes: &[u8], pos: usize, word: &[u8]) -> Option<usize> {
+    let end = pos + word.len();
+    let continues = bytes.get(end).is_some_and(|&b| is_identifier_byte(b));
+    (bytes.get(pos..end)? == word && !continues).then_some(end)
+}
+
+/// Find every quoted string literal in an expression inside an attribute value
+///
+/// Like `string_literals`, but quotes written as character references such
+/// as `&quot;` or `&#39;` count as quotes too, since that is how an
+/// expression embeds the quote type that delimits the attribute. Returned
+/// ranges exclude the encoded quotes, so the references are preserved.
+pub fn markup_string_literals(content: &str) -> Vec<Range<usize>> {
+    let bytes = content.as_bytes();
+    let mut literals = Vec::new();
+    let mut pos = 0;
+
+    while let Some((open, open_len)) = next_quote(bytes, pos) {
+        let start = open + open_len;
+        let Some((close, close_len)) = next_quote(bytes, start) else {
+            break;
+        };
+        if !is_comparison_operand(bytes, open, close + close_len) {
+            literals.push(start..close);
+        }
+        pos = close + close_len;
+    }
+
+    literals
+}
+
+/// Parts of an attribute value with `{...}` interpolations
+pub struct InterpolatedValue {
+    /// Static class lists between the interpolations
+    pub statics: Vec<Range<usize>>,
+    /// Interpolated expressions, without braces
+    pub expressions: Vec<Range<usize>>,
+}
+
+/// Split an attribute value with `{...}` interpolations into its parts
+///
+/// For example Svelte's `class="p-4 {active ? 'a' : 'b'} z-10"` has the
+/// static parts `p-4` and `z-10` and one expression.
+/// Tokens glued to an interpolation, like `btn-` in `btn-{size}`, are
+/// dynamic class names and belong to neither. Returns `None` when the
+/// braces don't balance.
+pub fn interpolation_segments(value: &str) -> Option<InterpolatedValue> {
+    let bytes = value.as_bytes();
+    let mut statics = Vec::new();
+    let mut expressions = Vec::new();
+    let mut segment_start = 0;
+
+    while let Some(open) = find_byte(bytes, segment_start, b"{") {
+        let close = matching_delimiter(bytes, open)?;
+        statics.extend(static_segment(bytes, segment_start..open));
+        expressions.push(open + 1..close);
+        segment_start = close + 1;
+    }
+    statics.extend(static_segment(bytes, segment_start..bytes.len()));
+
+    Some(InterpolatedValue {
+        statics,
+        expressions,
+    })
+}
+
+/// Trim a static segment between interpolations to its whole class tokens
+fn static_segment(bytes: &[u8], range: Range<usize>) -> Option<Range<usize>> {
+    let mut start = range.start;
+    let mut end = range.end;
+
+    // Drop a token continuing the previous interpolation
+    if start > 0 {
+        while start < end && !bytes[start].is_ascii_whitespace() {
+            start += 1;
+        }
+    }
+    // Drop a token continuing into the next interpolation
+    if end < bytes.len() {
+        while end > start && !bytes[end - 1].is_ascii_whitespace() {
+            end -= 1;
+        }
+    }
+
+    while start < end && bytes[start].is_ascii_whitespace() {
+        start += 1;
+    }
+    while end > start && bytes[end - 1].is_ascii_whitespace() {
+        end -= 1;
+    }
+
+    (start < end).then_some(start..end)
+}
+
+/// Character references that decode to a quote
+const QUOTE_REFERENCES: &[&[u8]] = &[
+    b"&quot;", b"&#34;", b"&#x22;", b"&apos;", b"&#39;", b"&#x27;",
+];
+
+/// Find the next literal or encoded quote at or after `from`, returning its
+/// position and length
+fn next_quote(bytes: &[u8], from: usize) -> Option<(usize, usize)> {
+    let mut pos = from;
+
+    while let Some(found) = find_byte(bytes, pos, b"\"'`&") {
+        if bytes[found] != b'&' {
+            return Some((found, 1));
+        }
+        let reference = QUOTE_REFERENCES.iter().find(|reference| {
+            bytes
+                .get(found..found + reference.len())
+                .is_some_and(|slice| slice.eq_ignore_ascii_case(reference))
+        });
+        if let Some(reference) = reference {
+            return Some((found, reference.len()));
+        }
+        pos = found + 1;
+    }
+
+    None
+}
+
+/// Whether the literal spanning `open..end` (quotes included) is an operand
+/// of an equality comparison rather than a class list
+fn is_comparison_operand(bytes: &[u8], open: usize, end: usize) -> bool {
+    let before = bytes[..open].trim_ascii_end();
+    let after = bytes[end.min(bytes.len())..].trim_ascii_start();
+
+    before.ends_with(b"==")
+        || before.ends_with(b"!=")
+        || after.starts_with(b"==")
+        || after.starts_with(b"!=")
+}
+
+/// Elements whose content is raw text rather than markup
+///
+/// `<template>` isn't one: its content is inert but parsed as markup, and
+/// web components keep their shadow DOM markup there.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+/// Byte ranges of element start tags in HTML-like markup
+///
+/// Built in a single forward pass that skips comments, CDATA sections,
+/// processing instructions and the content of raw text elements, and
+/// honors quoted attribute values and `{...}` expressions so a `>` inside
+/// them doesn't end the tag. Used to check that an attribute match really
+/// sits inside a tag rather than in text content or inside another
+/// attribute's value, such as the markup embedded in `srcdoc`.
+pub struct TagRanges {
+    /// Sorted, non-overlapping tag ranges from `<` up to and including `>`
+    ranges: Vec<Range<usize>>,
+    /// Sorted attribute values and expressions inside those tags
+    values: Vec<Range<usize>>,
+}
+
+impl TagRanges {
+    pub fn new(content: &str) -> Self {
+        let bytes = content.as_bytes();
+        let mut ranges = Vec::new();
+        let mut values = Vec::new();
+        let mut braces_balanced = true;
+        let mut pos = 0;
+
+        while let Some(open) = find_byte(bytes, pos, b"<") {
+            // Skip comments entirely
+            if bytes[open..].starts_with(b"<!--") {
+                pos = find_subslice(bytes, open + 4, b"-->").map_or(bytes.len(), |end| end + 3);
+                continue;
+            }
+            // So are CDATA sections and processing instructions such as
+            // `<?xml-stylesheet ...?>`, whose content isn't markup
+            if bytes[open..].starts_with(b"<![CDATA[") {
+                pos = find_subslice(bytes, open + 9, b"]]>").map_or(bytes.len(), |end| end + 3);
+                continue;
+            }
+            if bytes[open..].starts_with(b"<?") {
+                pos = find_subslice(bytes, open + 2, b"?>").map_or(bytes.len(), |end| end + 2);
+                continue;
+            }
+
+            // Only `<name` starts a tag; closing tags carry no attributes
+            if !bytes.get(open + 1).is_some_and(|b| b.is_ascii_alphabetic()) {
+                pos = open + 1;
+                continue;
+            }
+
+            let mut name_end = open + 1;
+            while bytes.get(name_end).is_some_and(|&b| {
+                b.is_ascii_alphanumeric() || matches!(b, b'-' | b':' | b'_' | b'.')
+            }) {
+                name_end += 1;
+            }
+
+            let end = tag_end(bytes, name_end, &mut values, &mut braces_balanced);
+            ranges.push(open..end);
+            pos = end;
+
+            let name = &content[open + 1..name_end];
+            if RAW_TEXT_ELEMENTS
+                .iter()
+                .any(|raw| name.eq_ignore_ascii_case(raw))
+            {
+                pos = find_closing_tag(bytes, pos, name).unwrap_or(bytes.len());
+            }
+        }
+
+        Self { ranges, values }
+    }
+
+    /// Whether an attribute name starting at `pos` belongs to an element tag
+    ///
+    /// The position must lie inside a tag but outside any attribute value or
+    /// expression, so `<iframe srcdoc='<p class="...">'>` and inline SVG
+    /// `<path class="..."/>` are told apart.
+    pub fn is_attribute_start(&self, pos: usize) -> bool {
+        self.contains(pos) && !within(&self.values, pos)
+    }
+
+    /// Whether the byte position lies inside an element tag
+    fn contains(&self, pos: usize) -> bool {
+        within(&self.ranges, pos)
+    }
+
+    /// Index of the element tag containing the byte position
+    pub fn tag_at(&self, pos: usize) -> Option<usize> {
+        let index = self.ranges.partition_point(|r| r.end <= pos);
+        self.ranges
+            .get(index)
+            .is_some_and(|r| r.contains(&pos))
+            .then_some(index)
+    }
+
+    /// Name of the element tag containing the byte position, such as `div`,
+    /// `Button` or `svelte:element`
+    pub fn tag_name<'a>(&self, content: &'a str, pos: usize) -> Option<&'a str> {
+        let tag = &content[self.ranges[self.tag_at(pos)?].start + 1..];
+        let name_end = tag
+            .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | ':' | '_' | '.')))
+            .unwrap_or(tag.len());
+        Some(&tag[..name_end])
+    }
+
+    /// Runs of valueless attribute names, as in UnoCSS's attributify mode
+    /// `<div flex p-4 items-center>`
+    ///
+    /// A run holds the names between two attributes with a value or
+    /// expression, or a line break, so only neighbours that can be reordered
+    /// without moving anything else end up together.
+    pub fn bare_attribute_runs(&self, content: &str) -> Vec<Vec<Range<usize>>> {
+        let bytes = content.as_bytes();
+        let mut runs = Vec::new();
+
+        for tag in &self.ranges {
+            let mut pos = tag.start + 1;
+            while pos < tag.end && !is_attribute_break(bytes[pos]) {
+                pos += 1;
+            }
+
+            let mut run = Vec::new();
+            while pos < tag.end {
+                match bytes[pos] {
+                    b'\n' | b'\r' => {
+                        runs.push(std::mem::take(&mut run));
+                        pos += 1;
+                    }
+                    b if b.is_ascii_whitespace() => pos += 1,
+                    b'>' => break,
+                    b'/' if bytes.get(pos + 1) == Some(&b'>') => break,
+                    b'"' | b'\'' | b'{' => {
+                        runs.push(std::mem::take(&mut run));
+                        pos = skip_attribute_value(bytes, pos, tag.end);
+                    }
+                    _ => {
+                        let start = pos;
+                        while pos < tag.end
+                            && !is_attribute_break(bytes[pos])
+                            && !(bytes[pos] == b'/' && bytes.get(pos + 1) == Some(&b'>'))
+                        {
+                            pos += 1;
+                        }
+                        let mut next = pos;
+                        while matches!(bytes.get(next), Some(b' ' | b'\t')) {
+                            next += 1;
+                        }
+                        if next < tag.end && bytes[next] == b'=' {
+                            runs.push(std::mem::take(&mut run));
+                            next += 1;
+                            while matches!(bytes.get(next), Some(b' ' | b'\t')) {
+                                next += 1;
+                            }
+                            pos = skip_attribute_value(bytes, next, tag.end);
+                        } else if pos > start {
+                            run.push(start..pos);
+                        } else {
+                            pos += 1;
+                        }
+                    }
+                }
+            }
+            runs.push(run);
+        }
+
+        runs.retain(|run| !run.is_empty());
+        runs
+    }
+}
+
+/// Value of the attribute `name` (ASCII case-insensitive) of the start tag
+/// whose `<` is at `open`, without its quotes
+///
+/// Attributes are told apart as in `TagRanges::bare_attribute_runs`, so any
+/// whitespace separates them and `data-lang` isn't `lang`. Unquoted values,
+/// as in `lang=pug`, run up to whitespace or `>`, and an attribute without
+/// a value has an empty one.
+pub fn tag_attribute<'a>(content: &'a str, open: usize, name: &str) -> Option<&'a str> {
+    let bytes = content.as_bytes();
+    let end = tag_end(bytes, open + 1, &mut Vec::new(), &mut true);
+    let mut pos = open + 1;
+    while pos < end && !is_attribute_break(bytes[pos]) {
+        pos += 1;
+    }
+
+    while pos < end {
+        match bytes[pos] {
+            b if b.is_ascii_whitespace() => pos += 1,
+            b'>' => break,
+            b'/' if bytes.get(pos + 1) == Some(&b'>') => break,
+            b'"' | b'\'' | b'{' => pos = skip_attribute_value(bytes, pos, end),
+            _ => {
+                let start = pos;
+                while pos < end
+                    && !is_attribute_break(bytes[pos])
+                    && !(bytes[pos] == b'/' && bytes.get(pos + 1) == Some(&b'>'))
+                {
+                    pos += 1;
+                }
+                let matched =
+                    pos > start && bytes[start..pos].eq_ignore_ascii_case(name.as_bytes());
+                let next = skip_whitespace(bytes, pos);
+                if next < end && bytes[next] == b'=' {
+                    let value = skip_whitespace(bytes, next + 1);
+                    let value_end = skip_attribute_value(bytes, value, end);
+                    if matched {
+                        return Some(match bytes.get(value) {
+                            Some(&quote @ (b'"' | b'\'')) => {
+                                let close = find_byte(bytes, value + 1, &[quote])?;
+                                &content[value + 1..close]
+                            }
+                            _ => &content[value..value_end],
+                        });
+                    }
+                    pos = value_end;
+                } else if matched {
+                    return Some("");
+                } else if pos == start {
+                    pos += 1;
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Whether the byte ends an attribute name
+fn is_attribute_break(byte: u8) -> bool {
+    byte.is_ascii_whitespace() || matches!(byte, b'=' | b'>' | b'"' | b'\'' | b'{')
+}
+
+/// Position after the attribute value or expression starting at `pos`:
+/// a quoted string, a `{...}` expression or an unquoted word
+fn skip_attribute_value(bytes: &[u8], pos: usize, end: usize) -> usize {
+    let close = match bytes.get(pos) {
+        Some(&quote @ (b'"' | b'\'')) => find_byte(bytes, pos + 1, &[quote]),
+        Some(b'{') => matching_delimiter(bytes, pos),
+        _ => {
+            let mut pos = pos;
+            while pos < end && !bytes[pos].is_ascii_whitespace() && bytes[pos] != b'>' {
+                pos += 1;
+            }
+            return pos;
+        }
+    };
+    close.map_or(end, |close| (close + 1).min(end))
+}
+
+/// Whether `pos` lies in one of the sorted, non-overlapping `ranges`
+fn within(ranges: &[Range<usize>], pos: usize) -> bool {
+    let index = ranges.partition_point(|r| r.end <= pos);
+    ranges.get(index).is_some_and(|r| r.contains(&pos))
+}
+
+/// Find the end of a start tag whose name ends at `pos`
+///
+/// Returns the position after the closing `>`, the position of a stray `<`
+/// for malformed markup, or the end of input. Quoted values and `{...}`
+/// expressions passed over are recorded in `values`.
+fn tag_end(
+    bytes: &[u8],
+    mut pos: usize,
+    values: &mut Vec<Range<usize>>,
+    braces_balanced: &mut bool,
+) -> usize {
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'>' => return pos + 1,
+            b'<' => return pos,
+            quote @ (b'"' | b'\'') => match find_byte(bytes, pos + 1, &[quote]) {
+                Some(close) => {
+                    values.push(pos + 1..close);
+                    pos = close;
+                }
+                None => {
+                    values.push(pos + 1..bytes.len());
+                    return bytes.len();
+                }
+            },
+            b'{' if *braces_balanced => match matching_delimiter(bytes, pos) {
+                Some(close) => {
+                    values.push(pos + 1..close);
+                    pos = close;
+                }
+                None => *braces_balanced = false,
+            },
+            _ => {}
+        }
+        pos += 1;
+    }
+    bytes.len()
+}
+
+/// `<script>` or `<style>` element of a component
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawTextElement {
+    /// `script` or `style`
+    pub name: &'static str,
+    /// From the `<` of the opening tag to after the closing tag
+    pub range: Range<usize>,
+    /// From the `<` of the opening tag to after its `>`
+    pub open_tag: Range<usize>,
+    /// Between the opening and the closing tag
+    pub content: Range<usize>,
+}
+
+/// The `<script>` and `<style>` elements of a component
+///
+/// Found in a single forward pass that skips comments. The opening tag is
+/// read up to the first `>` outside its quoted attribute values, so
+/// `<script lang="ts" generics="T extends Record<string, unknown>">` is read
+/// whole, and a name such as `<scripts>` doesn't count. The closing tag is
+/// looked for outside the string literals and comments of the element's
+/// content; if none is found that way it's the first `</script>` or
+/// `</style>`, and an element that isn't closed runs to the end of the file.
+pub fn script_and_style_elements(content: &str) -> Vec<RawTextElement> {
+    let bytes = content.as_bytes();
+    let mut elements = Vec::new();
+    let mut pos = 0;
+
+    while let Some(open) = find_subslice(bytes, pos, b"<") {
+        if bytes[open..].starts_with(b"<!--") {
+            pos = find_subslice(bytes, open + 4, b"-->").map_or(bytes.len(), |end| end + 3);
+            continue;
+        }
+        let Some(name) = ["script", "style"]
+            .into_iter()
+            .find(|name| tag_name_at(bytes, open + 1, name))
+        else {
+            pos = open + 1;
+            continue;
+        };
+
+        let content_start = tag_end(bytes, open + 1 + name.len(), &mut Vec::new(), &mut true);
+        let close = raw_text_closing_tag(bytes, content_start, name).or_else(|| {
+            find_closing_tag(bytes, content_start, name)
+                .filter(|&close| tag_name_at(bytes, close + 2, name))
+        });
+        let end = close.map_or(bytes.len(), |close| {
+            find_byte(bytes, close, b">").map_or(bytes.len(), |gt| gt + 1)
+        });
+        elements.push(RawTextElement {
+            name,
+            range: open..end,
+            open_tag: open..content_start,
+            content: content_start..close.unwrap_or(bytes.len()),
+        });
+        pos = end;
+    }
+
+    elements
+}
+
+/// Byte ranges of the content of the elements named in `names`, from after
+/// the opening tag to the start of the closing tag
+///
+/// Names are compared ASCII case-insensitively and comments are skipped.
+/// Elements of the same name nested in one another count toward finding
+/// the closing tag, self-closing tags such as `<markdown-block />` have no
+/// content, and an element that isn't closed runs to the end of the file.
+/// `<script>` and `<style>` content is read as raw text, so markup in their
+/// strings doesn't count.
+pub fn element_contents(content: &str, names: &[String]) -> Vec<Range<usize>> {
+    let bytes = content.as_bytes();
+    let mut contents = Vec::new();
+    let mut pos = 0;
+
+    while let Some(open) = find_byte(bytes, pos, b"<") {
+        if bytes[open..].starts_with(b"<!--") {
+            pos = find_subslice(bytes, open + 4, b"-->").map_or(bytes.len(), |end| end + 3);
+            continue;
+        }
+        let Some(name) = names
+            .iter()
+            .find(|name| !name.is_empty() && tag_name_at(bytes, open + 1, name))
+        else {
+            pos = open + 1;
+            continue;
+        };
+
+        let content_start = tag_end(bytes, open + 1 + name.len(), &mut Vec::new(), &mut true);
+        if bytes[..content_start].ends_with(b"/>") {
+            pos = content_start;
+            continue;
+        }
+        let raw_text = RAW_TEXT_ELEMENTS
+            .iter()
+            .any(|raw| name.eq_ignore_ascii_case(raw));
+        let end = if raw_text {
+            raw_text_closing_tag(bytes, content_start, name)
+                .or_else(|| find_closing_tag(bytes, content_start, name))
+        } else {
+            nested_closing_tag(bytes, content_start, name)
+        }
+        .unwrap_or(bytes.len());
+        contents.push(content_start..end);
+        pos = end;
+    }
+
+    contents
+}
+
+/// Find the start of the `</name>` closing an element whose content starts
+/// at `pos`, passing over elements of the same name opened inside it
+fn nested_closing_tag(bytes: &[u8], mut pos: usize, name: &str) -> Option<usize> {
+    let mut depth = 0;
+
+    while let Some(open) = find_byte(bytes, pos, b"<") {
+        if bytes[open..].starts_with(b"<!--") {
+            pos = find_subslice(bytes, open + 4, b"-->")? + 3;
+            continue;
+        }
+        if bytes.get(open + 1) == Some(&b'/') && tag_name_at(bytes, open + 2, name) {
+            if depth == 0 {
+                return Some(open);
+            }
+            depth -= 1;
+        } else if tag_name_at(bytes, open + 1, name) {
+            let end = tag_end(bytes, open + 1 + name.len(), &mut Vec::new(), &mut true);
+            if !bytes[..end].ends_with(b"/>") {
+                depth += 1;
+            }
+            pos = end;
+            continue;
+        }
+        pos = open + 1;
+    }
+
+    None
+}
+
+/// Whether the tag name `name` (ASCII case-insensitive) starts at `pos` and
+/// isn't the prefix of a longer name
+fn tag_name_at(bytes: &[u8], pos: usize, name: &str) -> bool {
+    bytes
+        .get(pos..pos + name.len())
+        .is_some_and(|candidate| candidate.eq_ignore_ascii_case(name.as_bytes()))
+        && bytes
+            .get(pos + name.len())
+            .is_none_or(|&b| b.is_ascii_whitespace() || b == b'>' || b == b'/')
+}
+
+/// Find the start of the `</name>` closing a `<script>` or `<style>`
+/// element, skipping string literals and comments in its content
+///
+/// Quoted strings end at the end of their line, so a stray quote, as in a
+/// regular expression literal, is passed over rather than hiding the rest of
+/// the element. `//` comments only count in scripts, where CSS could hold
+/// them in `url(//...)`.
+fn raw_text_closing_tag(bytes: &[u8], mut pos: usize, name: &str) -> Option<usize> {
+    let script = name == "script";
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'<' if bytes.get(pos + 1) == Some(&b'/') && tag_name_at(bytes, pos + 2, name) => {
+                return Some(pos);
+            }
+            quote @ (b'"' | b'\'') => {
+                if let Some(close) = closing_quote(bytes, pos + 1, quote)
+                    .filter(|&close| find_byte(&bytes[..close], pos + 1, b"\n").is_none())
+                {
+                    pos = close;
+                }
+            }
+            b'`' if script => pos = closing_template(bytes, pos + 1)?,
+            b'/' if bytes.get(pos + 1) == Some(&b'*') => {
+                pos = find_subslice(bytes, pos + 2, b"*/")? + 1;
+            }
+            b'/' if script && bytes.get(pos + 1) == Some(&b'/') => {
+                pos = find_byte(bytes, pos, b"\n").unwrap_or(bytes.len());
+            }
+            _ => {}
+        }
+        pos += 1;
+    }
+
+    None
+}
+
+/// Find the start of `</name` (ASCII case-insensitive) at or after `from`
+fn find_closing_tag(bytes: &[u8], from: usize, name: &str) -> Option<usize> {
+    let mut pos = from;
+    while let Some(open) = find_subslice(bytes, pos, b"</") {
+        let candidate = bytes.get(open + 2..open + 2 + name.len())?;
+        if candidate.eq_ignore_ascii_case(name.as_bytes()) {
+            return Some(open);
+        }
+        pos = open + 2;
+    }
+    None
+}
+
+fn find_subslice(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    bytes
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|offset| from + offset)
+}
+
+/// Match `="..."` or `='...'` directly after an attribute name
+///
+/// The quoted value may start on the next line, as in `className=` followed
+/// by a wrapped string, but the `=` must follow the name so that
+/// assignments such as `let className = "..."` aren't matched. The value
+/// only ends at the quote it was opened with, so it may contain the other
+/// quote type, as in `class='content-["x"]'` or a bound
+/// `:class="{ 'p-4': open }"` expression. `${...}` placeholders, as in Marko
+/// or `html` templates, may also contain the opening quote.
+fn attribute_value(
+    bytes: &[u8],
+    after_name: usize,
+    double_quotes: &mut NextDelimiter,
+    single_quotes: &mut NextDelimiter,
+) -> Option<(Range<usize>, usize)> {
+    if bytes.get(after_name) != Some(&b'=') {
+        return None;
+    }
+
+    let open = skip_whitespace(bytes, after_name + 1);
+    let start = open + 1;
+    let quotes = match bytes.get(open) {
+        Some(b'"') => double_quotes,
+        Some(b'\'') => single_quotes,
+        _ => return None,
+    };
+    let mut close = quotes.find(bytes, start)?;
+
+    let mut pos = start;
+    while let Some(hole) = find_byte(&bytes[..close], pos, b"$") {
+        pos = hole + 1;
+        if bytes.get(pos) != Some(&b'{') {
+            continue;
+        }
+        // An unbalanced placeholder leaves the value to end at the first quote
+        let Some(end) = matching_delimiter(bytes, pos) else {
+            break;
+        };
+        if end > close {
+            close = quotes.find(bytes, end + 1)?;
+        }
+        pos = end + 1;
+    }
+
+    Some((start..close, close + 1))
+}
+
+/// Position after the `.prop` or `.camel` style modifiers following the
+/// name of a bound attribute
+fn skip_binding_modifiers(bytes: &[u8], mut pos: usize) -> usize {
+    while bytes.get(pos) == Some(&b'.') && bytes.get(pos + 1).is_some_and(u8::is_ascii_alphabetic) {
+        pos += 2;
+        while bytes.get(pos).is_some_and(u8::is_ascii_alphanumeric) {
+            pos += 1;
+        }
+    }
+    pos
+}
+
+/// Match `\s*=\s*{...}` after an attribute name
+fn attribute_expression(
+    bytes: &[u8],
+    after_name: usize,
+    braces: &mut ClosingDelimiter,
+) -> Option<(Range<usize>, usize)> {
+    let mut pos = skip_whitespace(bytes, after_name);
+    if bytes.get(pos) != Some(&b'=') {
+        return None;
+    }
+    pos = skip_whitespace(bytes, pos + 1);
+    if bytes.get(pos) != Some(&b'{') {
+        return None;
+    }
+
+    let start = pos + 1;
+    let close = braces.find(bytes, pos)?;
+    if close == start {
+        return None;
+    }
+
+    Some((start..close, close + 1))
+}
+
+/// Whether the name at `pos` is called as a method in a chain, as in
+/// `.AddClass(`, or as a constructor, as in `new CssBuilder(`
+fn is_builder_call(bytes: &[u8], pos: usize) -> bool {
+    let before = &bytes[..pos];
+    if before.last() == Some(&b'.') {
+        return true;
+    }
+    let trimmed = before.trim_ascii_end();
+    trimmed.len() < before.len()
+        && trimmed.ends_with(b"new")
+        && !trimmed[..trimmed.len() - 3]
+            .last()
+            .is_some_and(|&b| is_identifier_byte(b))
+}
+
+/// Match `\s*(...)` after a function name
+fn function_arguments(
+    bytes: &[u8],
+    after_name: usize,
+    parens: &mut ClosingDelimiter,
+) -> Option<(Range<usize>, usize)> {
+    let pos = skip_whitespace(bytes, after_name);
+    if bytes.get(pos) != Some(&b'(') {
+        return None;
+    }
+
+    let start = pos + 1;
+    let close = parens.find(bytes, pos)?;
+    if close == start {
+        return None;
+    }
+
+    Some((start..close, close + 1))
+}
+
+/// Match `\s*`...`` after a template tag name
+fn tagged_template(
+    bytes: &[u8],
+    after_name: usize,
+    backticks: &mut ClosingDelimiter,
+) -> Option<(Range<usize>, usize)> {
+    let pos = skip_whitespace(bytes, after_name);
+    if bytes.get(pos) != Some(&b'`') {
+        return None;
+    }
+
+    let start = pos + 1;
+    let close = backticks.find(bytes, pos)?;
+    if close == start {
+        return None;
+    }
+
+    Some((start..close, close + 1))
+}
+
+/// Memoized search for the next occurrence of any delimiter byte
+///
+/// If the last search started at `searched_from` and found `found`, any later
+/// search starting in `searched_from..=found` has the same answer, which keeps
+/// repeated searches over one long tail linear overall.
+struct NextDelimiter {
+    targets: &'static [u8],
+    searched_from: usize,
+    found: Option<usize>,
+}
+
+impl NextDelimiter {
+    fn new(targets: &'static [u8]) -> Self {
+        Self {
+            targets,
+            searched_from: usize::MAX,
+            found: None,
+        }
+    }
+
+    fn find(&mut self, bytes: &[u8], from: usize) -> Option<usize> {
+        if from >= self.searched_from {
+            match self.found {
+                Some(found) if from <= found => return Some(found),
+                None => return None,
+                _ => {}
+            }
+        }
+
+        self.searched_from = from;
+        self.found = find_byte(bytes, from, self.targets);
+        self.found
+    }
+}
+
+/// Finds the brace closing an attribute expression, the parenthesis
+/// closing a call's arguments or the backtick closing a tagged template
+///
+/// Nested objects and arrays such as Qwik's `class={[{ a: sig.value }, "p-4"]}`
+/// and nested calls such as `compose(base, cva("p-4"))` are matched as a
+/// whole, skipping over string and template literals. Once a balanced search
+/// runs off the end of the input, later searches fall back to the first
+/// closing delimiter so unterminated input is still scanned in linear time.
+struct ClosingDelimiter {
+    first: NextDelimiter,
+    unbalanced: bool,
+}
+
+impl ClosingDelimiter {
+    fn new(close: &'static [u8]) -> Self {
+        Self {
+            first: NextDelimiter::new(close),
+            unbalanced: false,
+        }
+    }
+
+    /// Find the delimiter closing the `{`, `(` or `` ` `` at `open`
+    fn find(&mut self, bytes: &[u8], open: usize) -> Option<usize> {
+        if !self.unbalanced {
+            match matching_delimiter(bytes, open) {
+                Some(close) => return Some(close),
+                None => self.unbalanced = true,
+            }
+        }
+        self.first.find(bytes, open + 1)
+    }
+}
+
+/// Find the delimiter matching the `{`, `(` or `` ` `` at `open`, honoring
+/// nesting and literals
+pub fn matching_delimiter(bytes: &[u8], open: usize) -> Option<usize> {
+    let (opening, closing) = match bytes[open] {
+        b'`' => return closing_template(bytes, open + 1),
+        b'(' => (b'(', b')'),
+        _ => (b'{', b'}'),
+    };
+    let mut depth = 0usize;
+    let mut pos = open;
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b if b == opening => depth += 1,
+            b if b == closing => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(pos);
+                }
+            }
+            quote @ (b'"' | b'\'') => pos = closing_quote(bytes, pos + 1, quote)?,
+            b'`' => pos = closing_template(bytes, pos + 1)?,
+            _ => {}
+        }
+        pos += 1;
+    }
+
+    None
+}
+
+/// Find the quote ending a string literal, skipping escaped characters
+fn closing_quote(bytes: &[u8], mut pos: usize, quote: u8) -> Option<usize> {
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' => pos += 1,
+            b if b == quote => return Some(pos),
+            _ => {}
+        }
+        pos += 1;
+    }
+    None
+}
+
+/// Find the backtick ending a template literal, skipping `${...}` holes
+fn closing_template(bytes: &[u8], mut pos: usize) -> Option<usize> {
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' => pos += 1,
+            b'`' => return Some(pos),
+            b'$' if bytes.get(pos + 1) == Some(&b'{') => pos = matching_delimiter(bytes, pos + 1)?,
+            _ => {}
+        }
+        pos += 1;
+    }
+    None
+}
+
+fn find_byte(bytes: &[u8], from: usize, targets: &[u8]) -> Option<usize> {
+    bytes
+        .get(from..)?
+        .iter()
+        .position(|b| targets.contains(b))
+        .map(|offset| from + offset)
+}
+
+/// Attribute names must start a new attribute: after whitespace (including
+/// the space following the tag name), after the `:` of a binding, directly
+/// after an expression or spread attribute such as `{...rest}`, or at the
+/// start of the input. This rejects suffixes such as `data-class=` and text
+/// like `title='class="..."'` inside other attribute values.
+fn is_attribute_boundary(before: Option<u8>) -> bool {
+    match before {
+        None => true,
+        Some(b) => b.is_ascii_whitespace() || b == b':' || b == b'}',
+    }
+}
+
+/// Bytes that continue a JS identifier, so `mytw(` is not a call to `tw`
+fn is_identifier_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$'
+}
+
+fn skip_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+    while bytes.get(pos).is_some_and(|b| b.is_ascii_whitespace()) {
+        pos += 1;
+    }
+    pos
+}
+
+fn longest_first(names: &[String]) -> Vec<String> {
+    let mut names: Vec<String> = names.iter().filter(|n| !n.is_empty()).cloned().collect();
+    names.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    names.dedup();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_object_property_values() {
+        let names = vec!["class".to_string(), "className".to_string()];
+        let args = r#""div", { id: "x", className: open ? "z-10 p-4" : "m-2", 'class': "a b", style: { classList: "no" } }, h("span", {class:"c d"})"#;
+        let values: Vec<&str> = object_property_values(args, &names)
+            .into_iter()
+            .map(|range| &args[range])
+            .collect();
+
+        assert_eq!(
+            values,
+            vec![r#" open ? "z-10 p-4" : "m-2""#, r#" "a b""#, r#""c d""#]
+        );
+    }
+
+    #[test]
+    fn test_scan_factory_arguments() {
+        let content = r#"React.createElement("div", { className: "p-4" }); mycreateElement("x");"#;
+        let candidates = scanner()
+            .with_factories(&["createElement".to_string()])
+            .scan(content);
+
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].kind, CandidateKind::FactoryArguments);
+        assert_eq!(
+            &content[candidates[0].range.clone()],
+            r#""div", { className: "p-4" }"#
+        );
+    }
+
+    #[test]
+    fn test_scan_builder_arguments() {
+        let content = r#"var a = new CssBuilder("p-4")
+    .AddClass("z-10", when: open);
+AddClass("no"); renewCssBuilder("no"); x.AddClassName("no");"#;
+        let candidates = scanner()
+            .with_builders(&["CssBuilder".to_string(), "AddClass".to_string()])
+            .scan(content);
+
+        let arguments: Vec<&str> = candidates
+            .iter()
+            .map(|candidate| {
+                assert_eq!(candidate.kind, CandidateKind::BuilderArguments);
+                &content[candidate.range.clone()]
+            })
+            .collect();
+        assert_eq!(arguments, [r#""p-4""#, r#""z-10", when: open"#]);
+    }
+
+    #[test]
+    fn test_markup_templates() {
+        let code = r#"// html`<p class="no">`
+const a = html`<p class="a"></p>`;
+const b = html<Card>`<p class="b"></p>`;
+const c = "html`no`";
+class X {
+  render(): TemplateResult {
+    const d = `<p class="no"></p>`;
+    return `<p class="e">${this.f}</p>`;
+  }
+  renderItem() { return `<p class="no"></p>`; }
+}"#;
+        let templates: Vec<&str> = markup_templates(code)
+            .into_iter()
+            .map(|range| &code[range])
+            .collect();
+
+        assert_eq!(
+            templates,
+            vec![
+                r#"<p class="a"></p>"#,
+                r#"<p class="b"></p>"#,
+                r#"<p class="e">${this.f}</p>"#
+            ]
+        );
+    }
+
+    fn scanner() -> CandidateScanner {
+        CandidateScanner::new(
+            &["clsx".to_string()],
+            &["tw".to_string()],
+            &["class".to_string(), "className".to_string()],
+        )
+    }
+
+    #[test]
+    fn test_scan_attribute_value() {
+        let content = r#"<div class="p-4 flex">"#;
+        let candidates = scanner().scan(content);
+
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].kind, CandidateKind::AttributeValue);
+        assert_eq!(&content[candidates[0].range.clone()], "p-4 flex");
+    }
+
+    #[test]
+    fn test_scan_attribute_value_on_next_line() {
+        let content = "<div\n  className=\n    \"p-4 flex\"\n/>; let className = \"b a\";";
+        let candidates = scanner().scan(content);
+
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(&content[candidates[0].range.clone()], "p-4 flex");
+    }
+
+    #[test]
+    fn test_scan_attribute_expression_and_function() {
+        let content = r#"<div className = {"p-4"} /> clsx("flex")"#;
+        let candidates = scanner().scan(content);
+
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].kind, CandidateKind::AttributeExpression);
+        assert_eq!(&content[candidates[0].range.clone()], r#""p-4""#);
+        assert_eq!(candidates[1].kind, CandidateKind::FunctionArguments);
+        assert_eq!(&content[candidates[1].range.clone()], r#""flex""#);
+    }
+
+    #[test]
+    fn test_scan_unterminated_is_linear() {
+        let content = "clsx( className={ ".repeat(50_000);
+        let start = std::time::Instant::now();
+        let candidates = scanner().scan(&content);
+
+        assert!(candidates.is_empty());
+        assert!(start.elapsed().as_secs() < 2);
+    }
+
+    #[test]
+    fn test_scan_nested_expression() {
+        let content = r#"<div class={[{ active: sig.value }, "p-4 flex"]} />"#;
+        let candidates = scanner().scan(content);
+
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(
+            &content[candidates[0].range.clone()],
+            r#"[{ active: sig.value }, "p-4 flex"]"#
+        );
+    }
+
+    #[test]
+    fn test_scan_expression_ignores_braces_in_literals() {
+        let content = r#"<div class={["p-4 }", `m-${size}`, "flex"]} />"#;
+        let candidates = scanner().scan(content);
+
+        assert_eq!(candidates.len(), 1);
+        assert!(content[candidates[0].range.clone()].ends_with(r#""flex"]"#));
+    }
+
+    #[test]
+    fn test_scan_unbalanced_expression_falls_back() {
+        let content = r#"<div class={"p-4"} /> <div class={{ broken "#;
+        let candidates = scanner().scan(content);
+
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(&content[candidates[0].range.clone()], r#""p-4""#);
+    }
+
+    #[test]
+    fn test_scan_long_form_binding_with_modifiers() {
+        let content = r#"<div v-bind:class="'p-4'" v-bind:class.prop="['m-2']" :class.camel="'z-10'" class.x="a">"#;
+        let candidates = scanner().scan(content);
+
+        let found: Vec<(CandidateKind, &str)> = candidates
+            .iter()
+            .map(|c| (c.kind, &content[c.range.clone()]))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (CandidateKind::AttributeBinding, "'p-4'"),
+                (CandidateKind::AttributeBinding, "['m-2']"),
+                (CandidateKind::AttributeBinding, "'z-10'"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_bound_attribute_is_distinct() {
+        let content = r#"<div class="z-10 p-4" :class="{ 'mt-2 flex': open }">"#;
+        let candidates = scanner().scan(content);
+
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].kind, CandidateKind::AttributeValue);
+        assert_eq!(&content[candidates[0].range.clone()], "z-10 p-4");
+        assert_eq!(candidates[1].kind, CandidateKind::AttributeBinding);
+        assert_eq!(
+            &content[candidates[1].range.clone()],
+            "{ 'mt-2 flex': open }"
+        );
+    }
+
+    #[test]
+    fn test_scan_rejects_attribute_suffixes() {
+        let content = r#"<div data-class="z-10 p-4" my-class="a" title='class="b"' class="c">"#;
+        let candidates = scanner().scan(content);
+
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(&content[candidates[0].range.clone()], "c");
+    }
+
+    #[test]
+    fn test_scan_rejects_function_suffixes() {
+        let content = r#"myclsx("a") utils.clsx("b") clsx("c")"#;
+        let candidates = scanner().scan(content);
+
+        let args: Vec<&str> = candidates
+            .iter()
+            .map(|c| &content[c.range.clone()])
+            .collect();
+        assert_eq!(args, vec![r#""b""#, r#""c""#]);
+    }
+
+    #[test]
+    fn test_scan_nested_call_arguments() {
+        let content = r#"clsx(compose(base, cva("p-4 z-10")), isOpen() && "flex")"#;
+        let candidates = scanner().scan(content);
+
+        assert_eq!(candidates.len(), 1);
+        assert!(content[candidates[0].range.clone()].ends_with(r#""flex""#));
+    }
+
+    #[test]
+    fn test_scan_tagged_templates() {
+        let content = "tw`z-10 p-4` mytw`a` clsx `flex` tw(`b`) tw`m-${size}`";
+        let candidates = scanner().scan(content);
+
+        let found: Vec<(CandidateKind, &str)> = candidates
+            .iter()
+            .map(|c| (c.kind, &content[c.range.clone()]))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (CandidateKind::TaggedTemplate, "z-10 p-4"),
+                (CandidateKind::TaggedTemplate, "flex"),
+                (CandidateKind::TaggedTemplate, "m-${size}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_after_expression_attribute() {
+        let content = r#"<Card {...rest} active={open}className="z-10 p-4" />"#;
+        let candidates = scanner().scan(content);
+
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(&content[candidates[0].range.clone()], "z-10 p-4");
+    }
+
+    #[test]
+    fn test_tag_ranges() {
+        let content =
+            r#"<p class="a">class="b" <!-- <i class="c"> --></p><div onClick={() => x} class="d">"#;
+        let tags = TagRanges::new(content);
+
+        assert!(tags.contains(content.find("\"a\"").unwrap()));
+        assert!(!tags.contains(content.find("\"b\"").unwrap()));
+        assert!(!tags.contains(content.find("\"c\"").unwrap()));
+        assert!(tags.contains(content.find("\"d\"").unwrap()));
+    }
+
+    #[test]
+    fn test_tag_ranges_self_closing() {
+        let content = r#"<img class="a"/><br class='b'/>class="c"<hr class="d" /><p>"#;
+        let tags = TagRanges::new(content);
+
+        assert!(tags.contains(content.find("\"a\"").unwrap()));
+        assert!(tags.contains(content.find("'b'").unwrap()));
+        assert!(!tags.contains(content.find("\"c\"").unwrap()));
+        assert!(tags.contains(content.find("\"d\"").unwrap()));
+
+        // Each tag ends after its own `/>`
+        let first = content.find("\"a\"").unwrap();
+        let second = content.find("'b'").unwrap();
+        assert_ne!(tags.tag_at(first), tags.tag_at(second));
+        assert_eq!(tags.tag_name(content, first), Some("img"));
+        assert_eq!(tags.tag_name(content, second), Some("br"));
+        let close = content.find("/>").unwrap();
+        assert_eq!(tags.tag_at(close + 1), tags.tag_at(first));
+        assert_eq!(tags.tag_at(close + 2), tags.tag_at(second));
+    }
+
+    #[test]
+    fn test_scan_attribute_value_before_self_closing() {
+        let content = r#"<img class="w-8 h-8"/><br className='hidden'/>"#;
+        let candidates = scanner().scan(content);
+
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(&content[candidates[0].range.clone()], "w-8 h-8");
+        assert_eq!(&content[candidates[1].range.clone()], "hidden");
+    }
+
+    #[test]
+    fn test_tag_attribute() {
+        let tag = "<template\n\tlang=pug data-lang=\"html\" v-if>";
+        assert_eq!(tag_attribute(tag, 0, "lang"), Some("pug"));
+        assert_eq!(tag_attribute(tag, 0, "data-lang"), Some("html"));
+        assert_eq!(tag_attribute(tag, 0, "v-if"), Some(""));
+        assert_eq!(tag_attribute(tag, 0, "if"), None);
+
+        let tag = r#"<script generic="T extends Record<string, number>" lang='ts'>"#;
+        assert_eq!(tag_attribute(tag, 0, "LANG"), Some("ts"));
+        assert_eq!(
+            tag_attribute("<template data-lang=\"pug\">", 0, "lang"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_bare_attribute_runs() {
+        let content = "<div flex p-4 id=\"a\" m-2 {...rest} hover:bg-red-500 md:p-2\n  grid disabled/><p v-if=ok items-center>text flex p-4</p>";
+        let runs: Vec<Vec<&str>> = TagRanges::new(content)
+            .bare_attribute_runs(content)
+            .into_iter()
+            .map(|run| run.into_iter().map(|range| &content[range]).collect())
+            .collect();
+        assert_eq!(
+            runs,
+            [
+                vec!["flex", "p-4"],
+                vec!["m-2"],
+                vec!["hover:bg-red-500", "md:p-2"],
+                vec!["grid", "disabled"],
+                vec!["items-center"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tag_ranges_inside_templates() {
+        let content =
+            r#"<template id="t"><template><p class="a"></p></template><i class="b"></template>"#;
+        let tags = TagRanges::new(content);
+
+        assert!(tags.contains(content.find("\"a\"").unwrap()));
+        assert!(tags.contains(content.find("\"b\"").unwrap()));
+    }
+
+    #[test]
+    fn test_tag_ranges_skip_raw_text() {
+        let content = r#"<script>const s = '<div class="a">';</script><div class="b">"#;
+        let tags = TagRanges::new(content);
+
+        assert!(!tags.contains(content.find("\"a\"").unwrap()));
+        assert!(tags.contains(content.find("\"b\"").unwrap()));
+    }
+
+    #[test]
+    fn test_element_contents() {
+        let names = ["pre".to_string(), "Script".to_string(), "x-raw".to_string()];
+        let content = r#"<pre class="a"><pre>b</pre>c</PRE><!-- <pre> --><x-raw/><x-raw>d<script>e = "</pre>";</script>"#;
+        let contents: Vec<&str> = element_contents(content, &names)
+            .into_iter()
+            .map(|range| &content[range])
+            .collect();
+        assert_eq!(
+            contents,
+            ["<pre>b</pre>c", r#"d<script>e = "</pre>";</script>"#]
+        );
+        assert!(element_contents(content, &[]).is_empty());
+
+        let content = r#"<script>e = "</script>";</script><pre>"#;
+        let contents: Vec<&str> = element_contents(content, &names)
+            .into_iter()
+            .map(|range| &content[range])
+            .collect();
+        assert_eq!(contents, [r#"e = "</script>";"#, ""]);
+    }
+
+    #[test]
+    fn test_tag_ranges_skip_cdata_and_processing_instructions() {
+        let content = r#"<?xml-stylesheet href="<p class='a'>"?><x><![CDATA[<p class="b">]]></x><y class="c"/>"#;
+        let tags = TagRanges::new(content);
+
+        assert!(!tags.contains(content.find("'a'").unwrap()));
+        assert!(!tags.contains(content.find("\"b\"").unwrap()));
+        assert!(tags.contains(content.find("\"c\"").unwrap()));
+    }
+
+    #[test]
+    fn test_tag_ranges_skip_attribute_values() {
+        let content = r#"<iframe srcdoc='<p class="a">' class="b"></iframe><svg class="c"><path class="d"/></svg>"#;
+        let tags = TagRanges::new(content);
+        let name_before = |value: &str| content.find(value).unwrap() - "class=".len();
+
+        assert!(!tags.is_attribute_start(name_before("\"a\"")));
+        assert!(tags.is_attribute_start(name_before("\"b\"")));
+        assert!(tags.is_attribute_start(name_before("\"c\"")));
+        assert!(tags.is_attribute_start(name_before("\"d\"")));
+    }
+
+    #[test]
+    fn test_scan_attribute_value_ends_at_its_own_quote() {
+        let content = r#"<div class='content-["x"] p-4' className="a">"#;
+        let candidates = scanner().scan(content);
+
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(
+            &content[candidates[0].range.clone()],
+            r#"content-["x"] p-4"#
+        );
+        assert_eq!(&content[candidates[1].range.clone()], "a");
+    }
+
+    #[test]
+    fn test_markup_string_literals() {
+        let content = "{ &quot;z-10 p-4&quot;: a, &#39;flex&#39;: b, 'm-2': c, &amp; }";
+        let literals: Vec<&str> = markup_string_literals(content)
+            .into_iter()
+            .map(|literal| &content[literal])
+            .collect();
+
+        assert_eq!(literals, vec!["z-10 p-4", "flex", "m-2"]);
+    }
+
+    #[test]
+    fn test_string_literals_logical_operands() {
+        let content = r#""p-4", isActive && "z-10 flex", error || 'text-gray-500', variant === "primary" && "bg-blue-500", "it's" "#;
+        let literals: Vec<&str> = string_literals(content)
+            .into_iter()
+            .map(|literal| &content[literal])
+            .collect();
+
+        assert_eq!(
+            literals,
+            vec!["p-4", "z-10 flex", "text-gray-500", "bg-blue-500", "it's"]
+        );
+    }
+
+    #[test]
+    fn test_interpolation_segments() {
+        let value = "z-10 p-4 {active ? 'a' : 'b'} btn-{size} {x}-end mt-2";
+        let segments = interpolation_segments(value).unwrap();
+
+        let statics: Vec<&str> = segments.statics.into_iter().map(|r| &value[r]).collect();
+        let expressions: Vec<&str> = segments
+            .expressions
+            .into_iter()
+            .map(|r| &value[r])
+            .collect();
+        assert_eq!(statics, vec!["z-10 p-4", "mt-2"]);
+        assert_eq!(expressions, vec!["active ? 'a' : 'b'", "size", "x"]);
+
+        assert!(interpolation_segments("p-4 {broken").is_none());
+    }
+
+    #[test]
+    fn test_string_literals() {
+        let content = r#""a", 'b', `c`, "unterminated"#;
+        let literals = string_literals(content);
+
+        assert_eq!(literals.len(), 3);
+        assert_eq!(&content[literals[0].clone()], "a");
+        assert_eq!(&content[literals[2].clone()], "c");
+    }
+}
@@ -0,0 +1,90 @@
+//! Tailwind v4 `@source inline(...)` rules in CSS
+
+use std::ops::Range;
+
+use super::{
+    closing_quote, find_subslice, is_identifier_byte, matching_delimiter, skip_whitespace,
+};
+
+/// Find the argument lists of Tailwind v4 `@source inline(...)` and
+/// `@source not inline(...)` rules in CSS
+///
+/// Comments and strings outside of the rules are skipped, so commented-out
+/// rules are left alone. Returned ranges exclude the parentheses.
+pub fn source_inline_arguments(css: &str) -> Vec<Range<usize>> {
+    let bytes = css.as_bytes();
+    let mut arguments = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'/' if bytes.get(pos + 1) == Some(&b'*') => {
+                pos = find_subslice(bytes, pos + 2, b"*/").map_or(bytes.len(), |end| end + 1);
+            }
+            quote @ (b'"' | b'\'') => {
+                pos = closing_quote(bytes, pos + 1, quote).unwrap_or(bytes.len());
+            }
+            b'@' => {
+                let close = source_inline_open(bytes, pos + 1)
+                    .and_then(|open| Some(open..matching_delimiter(bytes, open)?));
+                if let Some(parens) = close {
+                    arguments.push(parens.start + 1..parens.end);
+                    pos = parens.end;
+                }
+            }
+            _ => {}
+        }
+        pos += 1;
+    }
+
+    arguments
+}
+
+/// Position of the `(` of `source inline(` or `source not inline(` at `pos`
+fn source_inline_open(bytes: &[u8], pos: usize) -> Option<usize> {
+    let mut pos = skip_whitespace(bytes, css_keyword(bytes, pos, b"source")?);
+    if let Some(end) = css_keyword(bytes, pos, b"not") {
+        pos = skip_whitespace(bytes, end);
+    }
+    let pos = skip_whitespace(bytes, css_keyword(bytes, pos, b"inline")?);
+    (bytes.get(pos) == Some(&b'(')).then_some(pos)
+}
+
+/// Position after `word` when it is a whole CSS identifier at `pos`
+fn css_keyword(bytes: &[u8], pos: usize, word: &[u8]) -> Option<usize> {
+    let end = pos + word.len();
+    let continues = bytes
+        .get(end)
+        .is_some_and(|&b| is_identifier_byte(b) || b == b'-');
+    (bytes.get(pos..end)? == word && !continues).then_some(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source_inline_texts(css: &str) -> Vec<&str> {
+        source_inline_arguments(css)
+            .into_iter()
+            .map(|range| &css[range])
+            .collect()
+    }
+
+    #[test]
+    fn test_source_inline_arguments() {
+        let css = r#"@import "tailwindcss";
+@source inline("z-10 p-4");
+@source not inline('underline flex');
+@source   inline ( "mt-2 (x)" );
+@source "../node_modules/@my/lib";
+@reference "../app.css";
+/* @source inline("b a"); */
+.a { content: "@source inline(\"b a\")"; }
+@sources inline("b a");"#;
+
+        assert_eq!(
+            source_inline_texts(css),
+            vec![r#""z-10 p-4""#, "'underline flex'", r#" "mt-2 (x)" "#]
+        );
+    }
+}
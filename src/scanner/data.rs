@@ -0,0 +1,234 @@
+//! Class list values in JSON and YAML data files
+
+use std::ops::Range;
+
+use super::{closing_quote, expression_end, skip_whitespace};
+
+/// Members of the JSON object whose `{` is at `open`
+///
+/// Returns the range of each key, without quotes, and of its value. Scanning
+/// stops at the first malformed member.
+pub fn json_object_members(content: &str, open: usize) -> Vec<(Range<usize>, Range<usize>)> {
+    let bytes = content.as_bytes();
+    let mut members = Vec::new();
+    if bytes.get(open) != Some(&b'{') {
+        return members;
+    }
+
+    let mut pos = open + 1;
+    loop {
+        let key_open = skip_whitespace(bytes, pos);
+        if bytes.get(key_open) != Some(&b'"') {
+            break;
+        }
+        let Some(key_close) = closing_quote(bytes, key_open + 1, b'"') else {
+            break;
+        };
+        let colon = skip_whitespace(bytes, key_close + 1);
+        if bytes.get(colon) != Some(&b':') {
+            break;
+        }
+        let value = json_value(bytes, colon + 1);
+        let end = value.end;
+        members.push((key_open + 1..key_close, value));
+        pos = skip_whitespace(bytes, end);
+        if bytes.get(pos) != Some(&b',') {
+            break;
+        }
+        pos += 1;
+    }
+
+    members
+}
+
+/// Elements of the JSON array whose `[` is at `open`
+pub fn json_array_elements(content: &str, open: usize) -> Vec<Range<usize>> {
+    let bytes = content.as_bytes();
+    let mut elements = Vec::new();
+    if bytes.get(open) != Some(&b'[') {
+        return elements;
+    }
+
+    let mut pos = open + 1;
+    loop {
+        let element = json_value(bytes, pos);
+        if element.is_empty() {
+            break;
+        }
+        pos = skip_whitespace(bytes, element.end);
+        elements.push(element);
+        if bytes.get(pos) != Some(&b',') {
+            break;
+        }
+        pos += 1;
+    }
+
+    elements
+}
+
+/// Range of the JSON value starting after whitespace at `pos`, without
+/// trailing whitespace
+fn json_value(bytes: &[u8], pos: usize) -> Range<usize> {
+    let start = skip_whitespace(bytes, pos);
+    let mut end = expression_end(bytes, start);
+    while end > start && bytes[end - 1].is_ascii_whitespace() {
+        end -= 1;
+    }
+    start..end
+}
+
+/// Decode the JSON string literal whose opening quote is at `open`
+///
+/// Returns the decoded text and, for every decoded byte, the position in
+/// `content` of the character or escape sequence it came from. Invalid
+/// escapes and unterminated literals return `None`.
+pub fn json_string(content: &str, open: usize) -> Option<(String, Vec<usize>)> {
+    if content.as_bytes().get(open) != Some(&b'"') {
+        return None;
+    }
+    let mut text = String::new();
+    let mut positions = Vec::new();
+    let mut chars = content[open + 1..].char_indices();
+
+    while let Some((offset, c)) = chars.next() {
+        let position = open + 1 + offset;
+        let decoded = match c {
+            '"' => return Some((text, positions)),
+            '\\' => match chars.next()?.1 {
+                'n' => '\n',
+                't' => '\t',
+                'r' => '\r',
+                'b' => '\u{8}',
+                'f' => '\u{c}',
+                'u' => {
+                    let unit = hex_unit(&mut chars)?;
+                    if (0xD800..0xDC00).contains(&unit) {
+                        // A high surrogate is followed by `\u` and the low one
+                        if chars.next()?.1 != '\\' || chars.next()?.1 != 'u' {
+                            return None;
+                        }
+                        let low = hex_unit(&mut chars)?.checked_sub(0xDC00)?;
+                        char::from_u32(0x10000 + ((unit - 0xD800) << 10) + low)?
+                    } else {
+                        char::from_u32(unit)?
+                    }
+                }
+                escaped @ ('"' | '\\' | '/') => escaped,
+                _ => return None,
+            },
+            c => c,
+        };
+        text.push(decoded);
+        positions.extend(std::iter::repeat_n(position, decoded.len_utf8()));
+    }
+
+    None
+}
+
+/// Read the four hex digits of a `\uXXXX` escape
+fn hex_unit(chars: &mut std::str::CharIndices<'_>) -> Option<u32> {
+    let digits: String = chars.take(4).map(|(_, c)| c).collect();
+    if digits.len() != 4 {
+        return None;
+    }
+    u32::from_str_radix(&digits, 16).ok()
+}
+
+/// Find the class list values of YAML mapping keys named in `names`
+///
+/// Handles `key: value` lines, including list items (`- key: value`) and
+/// quoted keys. Plain scalars end at a ` #` comment; quoted scalars are
+/// returned without their quotes. Block scalars, flow collections, anchors,
+/// aliases and tagged values are skipped.
+pub fn yaml_property_values(content: &str, names: &[String]) -> Vec<Range<usize>> {
+    let mut values = Vec::new();
+    let mut line_start = 0;
+
+    for line in content.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+
+        let entry = line.trim_start();
+        let entry = entry.strip_prefix("- ").unwrap_or(entry).trim_start();
+        let Some(colon) = entry.find(':') else {
+            continue;
+        };
+        let key = entry[..colon].trim_end();
+        let key = key
+            .strip_prefix('"')
+            .and_then(|key| key.strip_suffix('"'))
+            .or_else(|| {
+                key.strip_prefix('\'')
+                    .and_then(|key| key.strip_suffix('\''))
+            })
+            .unwrap_or(key);
+        if !names.iter().any(|name| name == key) {
+            continue;
+        }
+
+        let after = &entry[colon + 1..];
+        if !after.starts_with([' ', '\t']) {
+            continue;
+        }
+        let value = after.trim_start();
+        let value_start = start + (line.len() - value.len());
+        let value = value.trim_end_matches(['\r', '\n']);
+
+        match value.bytes().next() {
+            Some(quote @ (b'"' | b'\'')) => {
+                if let Some(close) = closing_quote(value.as_bytes(), 1, quote) {
+                    values.push(value_start + 1..value_start + close);
+                }
+            }
+            Some(b'|' | b'>' | b'[' | b'{' | b'&' | b'*' | b'!' | b'#') | None => {}
+            Some(_) => {
+                let end = value.find(" #").unwrap_or(value.len());
+                let scalar = value[..end].trim_end();
+                values.push(value_start..value_start + scalar.len());
+            }
+        }
+    }
+
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_string_positions() {
+        let content = r#"{"a": ["x\"\u00e9\ud83d\ude00é\n", 1], "b" : {"c": 2}}"#;
+        let members = json_object_members(content, 0);
+        let keys: Vec<&str> = members
+            .iter()
+            .map(|(key, _)| &content[key.clone()])
+            .collect();
+        assert_eq!(keys, ["a", "b"]);
+        assert_eq!(&content[members[1].1.clone()], r#"{"c": 2}"#);
+
+        let elements = json_array_elements(content, members[0].1.start);
+        assert_eq!(elements.len(), 2);
+        let (text, positions) = json_string(content, elements[0].start).unwrap();
+        assert_eq!(text, "x\"é😀é\n");
+        assert_eq!(positions.len(), text.len());
+        // Decoded bytes point at the escape they came from
+        assert_eq!(&content[positions[1]..positions[1] + 2], r#"\""#);
+        assert_eq!(&content[positions[2]..positions[2] + 6], r"\u00e9");
+        assert_eq!(&content[positions[4]..positions[4] + 6], r"\ud83d");
+        assert_eq!(json_string(r#""\x""#, 0), None);
+        assert_eq!(json_string(r#""open"#, 0), None);
+    }
+
+    #[test]
+    fn test_yaml_property_values() {
+        let names = vec!["wrapperClasses".to_string(), "class".to_string()];
+        let yaml = "hero:\n  wrapperClasses: z-10 p-4 # layout\n  title: wrapperClasses\nitems:\n  - class: \"b a\"\n    'class': 'd c'\n  - class: |\n      no\n  - class:no\n";
+        let values: Vec<&str> = yaml_property_values(yaml, &names)
+            .into_iter()
+            .map(|range| &yaml[range])
+            .collect();
+
+        assert_eq!(values, vec!["z-10 p-4", "b a", "d c"]);
+    }
+}
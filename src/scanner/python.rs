@@ -0,0 +1,190 @@
+//! Class-bearing keyword arguments and dict entries in Python code
+
+use std::ops::Range;
+
+use super::{find_byte, is_identifier_byte, skip_whitespace};
+
+/// String value of a class-bearing keyword argument or dict entry in Python
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PythonValue {
+    /// Contents of the string literal, without prefix and quotes
+    pub range: Range<usize>,
+    /// Whether the literal is an f-string, whose `{...}` fields are dynamic
+    pub formatted: bool,
+}
+
+/// Find the string values of keyword arguments and dict entries named in
+/// `names` in Python code
+///
+/// Matches keyword arguments such as FastHTML's `cls="..."` and dict entries
+/// such as Django's `attrs={"class": "..."}`, where the value is a single
+/// string literal. Triple-quoted and prefixed literals such as `r"..."` are
+/// supported. Comments, other strings and comparisons with `==` are
+/// skipped.
+pub fn python_class_values(content: &str, names: &[String]) -> Vec<PythonValue> {
+    let bytes = content.as_bytes();
+    let mut values = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'#' => {
+                pos = find_byte(bytes, pos, b"\n").unwrap_or(bytes.len());
+            }
+            b'"' | b'\'' => {
+                // An unterminated literal is stepped over one byte at a time
+                let Some(literal) = python_string(bytes, pos) else {
+                    pos += 1;
+                    continue;
+                };
+                pos = literal.end;
+                // A dict key: "class": "..."
+                let key = &content[literal.range.clone()];
+                let after = skip_whitespace(bytes, pos);
+                if bytes.get(after) == Some(&b':') && names.iter().any(|name| name == key) {
+                    if let Some(value) = python_value(bytes, after + 1) {
+                        pos = value.end;
+                        values.push(value.value);
+                    }
+                }
+            }
+            b if is_identifier_byte(b) => {
+                let start = pos;
+                while pos < bytes.len() && is_identifier_byte(bytes[pos]) {
+                    pos += 1;
+                }
+                // A prefixed literal such as r"..." is skipped as a string
+                if let Some(literal) = python_string(bytes, start) {
+                    pos = literal.end;
+                    continue;
+                }
+                let after = skip_whitespace(bytes, pos);
+                let is_keyword = bytes.get(after) == Some(&b'=')
+                    && bytes.get(after + 1) != Some(&b'=')
+                    && (start == 0 || bytes[start - 1] != b'.')
+                    && names
+                        .iter()
+                        .any(|name| name.as_bytes() == &bytes[start..pos]);
+                if is_keyword {
+                    if let Some(value) = python_value(bytes, after + 1) {
+                        pos = value.end;
+                        values.push(value.value);
+                    }
+                }
+            }
+            _ => pos += 1,
+        }
+    }
+
+    values
+}
+
+/// String literal found by `python_string`
+struct PythonString {
+    /// Contents, without prefix and quotes
+    range: Range<usize>,
+    /// Position after the closing quotes
+    end: usize,
+    formatted: bool,
+}
+
+/// Scan the Python string literal starting at `start`, which may be a
+/// prefix such as `f` or `rb` or the opening quote
+fn python_string(bytes: &[u8], start: usize) -> Option<PythonString> {
+    let mut open = start;
+    while bytes.get(open).is_some_and(u8::is_ascii_alphabetic) {
+        open += 1;
+    }
+    let prefix = &bytes[start..open];
+    let quote = *bytes.get(open)?;
+    let is_prefix = prefix.len() <= 2
+        && prefix
+            .iter()
+            .all(|b| matches!(b.to_ascii_lowercase(), b'r' | b'b' | b'u' | b'f'));
+    if !matches!(quote, b'"' | b'\'') || !is_prefix {
+        return None;
+    }
+    let triple = bytes.get(open..open + 3) == Some(&[quote; 3][..]);
+    let content_start = if triple { open + 3 } else { open + 1 };
+
+    let mut pos = content_start;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' => pos += 1,
+            b'\n' if !triple => return None,
+            b if b == quote => {
+                if !triple {
+                    return Some(PythonString {
+                        range: content_start..pos,
+                        end: pos + 1,
+                        formatted: prefix.iter().any(|b| b.eq_ignore_ascii_case(&b'f')),
+                    });
+                }
+                if bytes.get(pos..pos + 3) == Some(&[quote; 3][..]) {
+                    return Some(PythonString {
+                        range: content_start..pos,
+                        end: pos + 3,
+                        formatted: prefix.iter().any(|b| b.eq_ignore_ascii_case(&b'f')),
+                    });
+                }
+            }
+            _ => {}
+        }
+        pos += 1;
+    }
+    None
+}
+
+/// Value found by `python_value`
+struct PythonMatch {
+    value: PythonValue,
+    end: usize,
+}
+
+/// Match a string literal value at `pos`, after optional whitespace, that
+/// isn't followed by more of an expression such as `+ extra` or `.strip()`
+fn python_value(bytes: &[u8], pos: usize) -> Option<PythonMatch> {
+    let start = skip_whitespace(bytes, pos);
+    let literal = python_string(bytes, start)?;
+    let mut after = literal.end;
+    while bytes.get(after).is_some_and(|&b| b == b' ' || b == b'\t') {
+        after += 1;
+    }
+    let ends_value = bytes
+        .get(after)
+        .is_none_or(|&b| matches!(b, b',' | b')' | b'}' | b']' | b'\r' | b'\n' | b'#' | b';'));
+    ends_value.then_some(PythonMatch {
+        value: PythonValue {
+            range: literal.range,
+            formatted: literal.formatted,
+        },
+        end: literal.end,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_python_class_values() {
+        let code = r#"# Div(cls="no")
+doc = "cls='no'"
+Div(P("Hi", cls='a'), cls="""b""", hx_get="/x")
+widget = forms.TextInput(attrs={"class": r"c", 'id': "no"})
+Div(cls=f"d {size}", klass="e" + extra)
+if cls == "no": self.cls = "no"
+"#;
+        let names = ["cls".to_string(), "class".to_string(), "klass".to_string()];
+        let values = python_class_values(code, &names);
+
+        let contents: Vec<(&str, bool)> = values
+            .iter()
+            .map(|value| (&code[value.range.clone()], value.formatted))
+            .collect();
+        assert_eq!(
+            contents,
+            [("a", false), ("b", false), ("c", false), ("d {size}", true)]
+        );
+    }
+}
@@ -0,0 +1,328 @@
+use crate::sorter::variant_separator;
+
+const TOP: u8 = 1;
+const RIGHT: u8 = 2;
+const BOTTOM: u8 = 4;
+const LEFT: u8 = 8;
+const ALL: u8 = TOP | RIGHT | BOTTOM | LEFT;
+
+/// Utilities setting sides of a box property, by property, with the sides
+/// each one sets. Logical sides count as both horizontal ones
+const SIDES: &[(&str, &str, u8)] = &[
+    ("margin", "m", ALL),
+    ("margin", "mx", LEFT | RIGHT),
+    ("margin", "my", TOP | BOTTOM),
+    ("margin", "mt", TOP),
+    ("margin", "mr", RIGHT),
+    ("margin", "mb", BOTTOM),
+    ("margin", "ml", LEFT),
+    ("margin", "ms", LEFT | RIGHT),
+    ("margin", "me", LEFT | RIGHT),
+    ("padding", "p", ALL),
+    ("padding", "px", LEFT | RIGHT),
+    ("padding", "py", TOP | BOTTOM),
+    ("padding", "pt", TOP),
+    ("padding", "pr", RIGHT),
+    ("padding", "pb", BOTTOM),
+    ("padding", "pl", LEFT),
+    ("padding", "ps", LEFT | RIGHT),
+    ("padding", "pe", LEFT | RIGHT),
+    ("scroll-margin", "scroll-m", ALL),
+    ("scroll-margin", "scroll-mx", LEFT | RIGHT),
+    ("scroll-margin", "scroll-my", TOP | BOTTOM),
+    ("scroll-margin", "scroll-mt", TOP),
+    ("scroll-margin", "scroll-mr", RIGHT),
+    ("scroll-margin", "scroll-mb", BOTTOM),
+    ("scroll-margin", "scroll-ml", LEFT),
+    ("scroll-margin", "scroll-ms", LEFT | RIGHT),
+    ("scroll-margin", "scroll-me", LEFT | RIGHT),
+    ("scroll-padding", "scroll-p", ALL),
+    ("scroll-padding", "scroll-px", LEFT | RIGHT),
+    ("scroll-padding", "scroll-py", TOP | BOTTOM),
+    ("scroll-padding", "scroll-pt", TOP),
+    ("scroll-padding", "scroll-pr", RIGHT),
+    ("scroll-padding", "scroll-pb", BOTTOM),
+    ("scroll-padding", "scroll-pl", LEFT),
+    ("scroll-padding", "scroll-ps", LEFT | RIGHT),
+    ("scroll-padding", "scroll-pe", LEFT | RIGHT),
+    ("inset", "inset", ALL),
+    ("inset", "inset-x", LEFT | RIGHT),
+    ("inset", "inset-y", TOP | BOTTOM),
+    ("inset", "top", TOP),
+    ("inset", "right", RIGHT),
+    ("inset", "bottom", BOTTOM),
+    ("inset", "left", LEFT),
+    ("inset", "start", LEFT | RIGHT),
+    ("inset", "end", LEFT | RIGHT),
+    // Column and row gaps stand in for the horizontal and vertical sides
+    ("gap", "gap", LEFT | TOP),
+    ("gap", "gap-x", LEFT),
+    ("gap", "gap-y", TOP),
+];
+
+/// Pairs of utilities that together set the same sides as a shorthand
+const MERGES: &[(&str, &str, &str)] = &[
+    ("mt", "mb", "my"),
+    ("ml", "mr", "mx"),
+    ("mx", "my", "m"),
+    ("pt", "pb", "py"),
+    ("pl", "pr", "px"),
+    ("px", "py", "p"),
+    ("scroll-mt", "scroll-mb", "scroll-my"),
+    ("scroll-ml", "scroll-mr", "scroll-mx"),
+    ("scroll-mx", "scroll-my", "scroll-m"),
+    ("scroll-pt", "scroll-pb", "scroll-py"),
+    ("scroll-pl", "scroll-pr", "scroll-px"),
+    ("scroll-px", "scroll-py", "scroll-p"),
+    ("top", "bottom", "inset-y"),
+    ("left", "right", "inset-x"),
+    ("inset-x", "inset-y", "inset"),
+    ("gap-x", "gap-y", "gap"),
+];
+
+/// A class setting sides of a box property
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SideClass<'a> {
+    variants: &'a str,
+    important: &'a str,
+    trailing_important: &'a str,
+    negative: &'a str,
+    property: &'static str,
+    utility: &'static str,
+    value: &'a str,
+}
+
+impl<'a> SideClass<'a> {
+    fn parse(class: &'a str) -> Option<Self> {
+        let (variants, rest) =
+            class.split_at(variant_separator(class).map_or(0, |colon| colon + 1));
+        let (important, rest) = match rest.strip_prefix('!') {
+            Some(rest) => ("!", rest),
+            None => ("", rest),
+        };
+        let (rest, trailing_important) = match rest.strip_suffix('!') {
+            Some(rest) => (rest, "!"),
+            None => (rest, ""),
+        };
+        let (negative, rest) = match rest.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", rest),
+        };
+
+        // The longest utility wins, so `inset-x-4` isn't `inset` with `x-4`
+        let (property, utility, value) = SIDES
+            .iter()
+            .filter_map(|&(property, utility, _)| {
+                let value = rest.strip_prefix(utility)?.strip_prefix('-')?;
+                (!value.is_empty()).then_some((property, utility, value))
+            })
+            .max_by_key(|(_, utility, _)| utility.len())?;
+
+        Some(Self {
+            variants,
+            important,
+            trailing_important,
+            negative,
+            property,
+            utility,
+            value,
+        })
+    }
+
+    fn sides(&self) -> u8 {
+        sides_of(self.utility)
+    }
+
+    /// Whether `other` can be merged with this class: the same variants,
+    /// modifiers and value
+    fn pairs_with(&self, other: &Self) -> bool {
+        self.variants == other.variants
+            && self.important == other.important
+            && self.trailing_important == other.trailing_important
+            && self.negative == other.negative
+            && self.value == other.value
+    }
+
+    fn with_utility(&self, utility: &str) -> String {
+        format!(
+            "{}{}{}{}-{}{}",
+            self.variants,
+            self.important,
+            self.negative,
+            utility,
+            self.value,
+            self.trailing_important
+        )
+    }
+}
+
+fn sides_of(utility: &str) -> u8 {
+    SIDES
+        .iter()
+        .find(|(_, name, _)| *name == utility)
+        .map_or(0, |(_, _, sides)| *sides)
+}
+
+/// Merge pairs of side utilities with the same value into their shorthand,
+/// as `mt-4 mb-4` into `my-4` and `px-2 py-2` into `p-2`
+///
+/// Both classes need the same variants, `!` and sign. A pair is left alone
+/// when another class with the same variants also sets one of its sides,
+/// as in `mt-4 mb-4 my-2`, since merging would change which one applies.
+/// Merges repeat, so `mt-2 mb-2 ml-2 mr-2` becomes `m-2`. The merged class
+/// takes the place of the first of the pair, and the whitespace between the
+/// classes is kept, losing one separator per merge; returns the classes
+/// unchanged when nothing merges.
+pub fn merge_shorthands(classes: &str) -> String {
+    let mut tokens: Vec<String> = Vec::new();
+    // Whitespace in front of each token, then after the last one
+    let mut separators: Vec<&str> = Vec::new();
+    let mut rest = classes;
+    while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+        separators.push(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        tokens.push(rest[..end].to_string());
+        rest = &rest[end..];
+    }
+    separators.push(rest);
+
+    let mut merged = false;
+    while let Some((first, second, shorthand)) = find_merge(&tokens) {
+        tokens[first] = shorthand;
+        tokens.remove(second);
+        separators.remove(dropped_separator(&separators, second));
+        merged = true;
+    }
+
+    if !merged {
+        return classes.to_string();
+    }
+    let mut output = String::with_capacity(classes.len());
+    for (separator, token) in separators.iter().zip(&tokens) {
+        output.push_str(separator);
+        output.push_str(token);
+    }
+    output.push_str(separators[tokens.len()]);
+    output
+}
+
+/// Which of the separators around the token at `index`, never the first
+/// one, goes with it
+///
+/// The trailing whitespace of the list stays, and a line break is kept over
+/// a plain space, so the lines of a multi-line list survive.
+fn dropped_separator(separators: &[&str], index: usize) -> usize {
+    let before = separators[index];
+    let after = separators[index + 1];
+    if index + 2 == separators.len() {
+        index
+    } else if before.contains('\n') && !after.contains('\n') {
+        index + 1
+    } else {
+        index
+    }
+}
+
+/// Indices of the next pair to merge and the class replacing it
+fn find_merge(tokens: &[String]) -> Option<(usize, usize, String)> {
+    let parsed: Vec<Option<SideClass>> = tokens.iter().map(|t| SideClass::parse(t)).collect();
+
+    for (first, a) in parsed.iter().enumerate() {
+        let Some(a) = a else { continue };
+        for &(left, right, shorthand) in MERGES {
+            let partner = |utility: &str| {
+                parsed
+                    .iter()
+                    .position(|b| b.is_some_and(|b| b.utility == utility && b.pairs_with(a)))
+            };
+            let second = if a.utility == left {
+                partner(right)
+            } else if a.utility == right {
+                partner(left)
+            } else {
+                None
+            };
+            let Some(second) = second else { continue };
+
+            let sides = sides_of(shorthand);
+            let conflict = parsed.iter().enumerate().any(|(index, other)| {
+                index != first
+                    && index != second
+                    && other.is_some_and(|other| {
+                        other.variants == a.variants
+                            && other.property == a.property
+                            && other.sides() & sides != 0
+                    })
+            });
+            if !conflict {
+                return Some((
+                    first.min(second),
+                    first.max(second),
+                    a.with_utility(shorthand),
+                ));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_axis_pairs() {
+        assert_eq!(merge_shorthands("mt-4 flex mb-4"), "my-4 flex");
+        assert_eq!(merge_shorthands("px-2 py-2"), "p-2");
+        assert_eq!(merge_shorthands("mr-auto ml-auto"), "mx-auto");
+        assert_eq!(merge_shorthands("mt-2 mb-2 ml-2 mr-2"), "m-2");
+        assert_eq!(
+            merge_shorthands("top-0 bottom-0 gap-x-4 gap-y-4"),
+            "inset-y-0 gap-4"
+        );
+        assert_eq!(merge_shorthands("scroll-pt-8 scroll-pb-8"), "scroll-py-8");
+    }
+
+    #[test]
+    fn test_merge_keeps_whitespace() {
+        assert_eq!(
+            merge_shorthands("\n  flex mt-4\n  mb-4 p-2\n"),
+            "\n  flex my-4\n  p-2\n"
+        );
+        assert_eq!(merge_shorthands("mt-4\tmb-4  flex "), "my-4  flex ");
+        assert_eq!(merge_shorthands(" mb-4\nmt-4 flex"), " my-4\nflex");
+    }
+
+    #[test]
+    fn test_merge_keeps_modifiers() {
+        assert_eq!(merge_shorthands("hover:mt-4 hover:mb-4"), "hover:my-4");
+        assert_eq!(merge_shorthands("!pt-[3px] !pb-[3px]"), "!py-[3px]");
+        assert_eq!(merge_shorthands("-mt-2 -mb-2"), "-my-2");
+        assert_eq!(merge_shorthands("mt-2! mb-2!"), "my-2!");
+    }
+
+    #[test]
+    fn test_unmergeable_pairs_kept() {
+        for classes in [
+            "mt-4 mb-2",
+            "mt-4 hover:mb-4",
+            "!mt-4 mb-4",
+            "-mt-4 mb-4",
+            "mt-4 mb-4 my-2",
+            "mt-4 mb-4 mt-4",
+            "px-2 py-2 pl-4",
+            "inset-x-0 top-0",
+            "mt-4  p-4",
+        ] {
+            assert_eq!(merge_shorthands(classes), classes);
+        }
+    }
+
+    #[test]
+    fn test_conflicts_only_with_same_variants() {
+        assert_eq!(merge_shorthands("mt-4 mb-4 md:my-2"), "my-4 md:my-2");
+        assert_eq!(merge_shorthands("mt-4 mb-4 mx-2"), "my-4 mx-2");
+    }
+}
@@ -1,27 +1,222 @@
 use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
 
 /// Represents a parsed TailwindCSS class with its components
+///
+/// All components borrow from the original class string, so parsing a token
+/// only allocates for the ranks of its variants, if it has any.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[allow(dead_code)]
-pub struct TailwindClass {
+pub struct TailwindClass<'a> {
     /// Original class string
-    pub original: String,
+    pub original: &'a str,
     /// Important modifier (!)
     pub important: bool,
-    /// Variants (e.g., ["dark", "hover", "md"])
-    pub variants: Vec<String>,
+    /// Variant prefix without the trailing colon (e.g., "dark:hover:md")
+    variant_prefix: Option<&'a str>,
     /// Base class name (e.g., "text-red-500")
-    pub base: String,
+    pub base: &'a str,
     /// Negative modifier (-)
     pub negative: bool,
     /// Arbitrary value (e.g., "[100px]")
     pub arbitrary: bool,
+    /// Precomputed sort key so comparisons don't re-derive priorities
+    key: SortKey,
+    /// Rank of each variant, highest first, so comparisons don't rank them
+    variant_ranks: Vec<VariantRank<'a>>,
 }
 
-impl TailwindClass {
+/// Category priority of classes with an unrecognized utility prefix
+const UNKNOWN_PRIORITY: u32 = 9999;
+
+/// Category priority of the `group` and `peer` marker classes, which sort
+/// before every other class since later classes refer to them
+const MARKER_PRIORITY: u32 = 0;
+
+/// Category priority of safelisted classes with an unrecognized prefix:
+/// after every built-in category, but ahead of unknown classes
+const SAFELIST_PRIORITY: u32 = 1700;
+
+/// Category priority of arbitrary properties such as `[mask-type:alpha]`:
+/// after known and safelisted utilities, but ahead of unknown classes
+const ARBITRARY_PROPERTY_PRIORITY: u32 = 1800;
+
+/// Category priority of classes over the `ClassLimits`: after every other
+/// class, in their original order
+const OVERSIZED_PRIORITY: u32 = u32::MAX - 1;
+
+/// Category priority of placeholder tokens such as `...`: after every
+/// class, in their original order
+const PLACEHOLDER_PRIORITY: u32 = u32::MAX;
+
+/// Indices of the colons of a class that separate variants, ignoring
+/// colons inside brackets
+fn top_level_colons(class: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut depth = 0usize;
+    class.bytes().enumerate().filter_map(move |(index, byte)| {
+        match byte {
+            b'[' => depth += 1,
+            b']' => depth = depth.saturating_sub(1),
+            b':' if depth == 0 => return Some(index),
+            _ => {}
+        }
+        None
+    })
+}
+
+/// Index of the colon ending the variants of a class
+pub fn variant_separator(class: &str) -> Option<usize> {
+    top_level_colons(class).last()
+}
+
+/// Property name of an arbitrary property class, as `mask-type` in
+/// `[mask-type:alpha]`
+fn arbitrary_property(base: &str) -> Option<&str> {
+    let (property, _) = base.strip_prefix('[')?.strip_suffix(']')?.split_once(':')?;
+    let valid = !property.is_empty()
+        && property
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+    valid.then_some(property)
+}
+
+/// Whether a class is a `group` or `peer` marker, optionally named as in
+/// `group/item`
+fn is_marker(base: &str) -> bool {
+    let name = base.split_once('/').map_or(base, |(name, _)| name);
+    name == "group" || name == "peer"
+}
+
+/// Whether a token is a placeholder that isn't a class
+///
+/// Ellipses stand for more classes in documentation and examples, `*` is a
+/// wildcard, and `{...props}` is spread syntax that ended up in class text.
+pub fn is_placeholder(token: &str) -> bool {
+    matches!(token, "..." | "\u{2026}" | "*") || (token.starts_with("{...") && token.ends_with('}'))
+}
+
+/// Size limits past which a class is passed through without being parsed
+///
+/// Set by the `maxClassLength` and `maxVariants` options, so generated or
+/// pathological tokens, such as a class of thousands of characters or one
+/// with hundreds of stacked variants, can't slow down sorting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClassLimits {
+    /// Longest class, in bytes, that is parsed
+    pub max_length: usize,
+    /// Most variants a parsed class may have
+    pub max_variants: usize,
+}
+
+impl Default for ClassLimits {
+    fn default() -> Self {
+        Self {
+            max_length: 1000,
+            max_variants: 16,
+        }
+    }
+}
+
+impl ClassLimits {
+    /// Whether a class is too long or has too many variants to be parsed
+    pub fn exceeded_by(&self, class: &str) -> bool {
+        class.len() > self.max_length || top_level_colons(class).count() > self.max_variants
+    }
+}
+
+/// Options besides the safelist that change how classes are sorted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SortOptions {
+    /// Limits past which classes aren't parsed
+    pub limits: ClassLimits,
+    /// Whether `!important` classes sort after the others. When off, the
+    /// `!` is still parsed but ignored for ordering
+    pub important_affects_order: bool,
+}
+
+impl Default for SortOptions {
+    fn default() -> Self {
+        Self {
+            limits: ClassLimits::default(),
+            important_affects_order: true,
+        }
+    }
+}
+
+/// Classes from the `safelist` option that count as known utilities
+///
+/// Entries are exact class names such as `btn-primary`, or simple patterns
+/// where `*` matches any run of characters, as in `badge-*`. They are
+/// matched against the class without variants, `!` or a leading `-`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Safelist {
+    entries: Vec<String>,
+}
+
+impl Safelist {
+    pub fn new(entries: Vec<String>) -> Self {
+        Self { entries }
+    }
+
+    /// Hash of the entries, identifying the sort order they produce
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Whether a base class name is safelisted
+    pub fn contains(&self, base: &str) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| matches_pattern(entry, base))
+    }
+}
+
+/// Match a name against a pattern where `*` matches any run of characters
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let Some((first, rest)) = pattern.split_once('*') else {
+        return pattern == name;
+    };
+    let Some(mut remaining) = name.strip_prefix(first) else {
+        return false;
+    };
+
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let last = parts.pop().unwrap_or_default();
+    for part in parts {
+        match remaining.find(part) {
+            Some(index) => remaining = &remaining[index + part.len()..],
+            None => return false,
+        }
+    }
+    remaining.ends_with(last)
+}
+
+/// Compact sort key computed once per class at parse time
+///
+/// Field order matches the first comparison steps of `TailwindClass::cmp`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct SortKey {
+    important: bool,
+    category: u32,
+    has_variants: bool,
+}
+
+/// Priority, breakpoint width and name of a variant, compared in that order
+type VariantRank<'a> = (u32, Option<(u32, bool)>, &'a str);
+
+impl<'a> TailwindClass<'a> {
     /// Parse a TailwindCSS class string into its components
     #[allow(dead_code)]
-    pub fn parse(class: &str) -> Self {
+    pub fn parse(class: &'a str) -> Self {
+        Self::parse_with(class, &Safelist::default())
+    }
+
+    /// Parse a class, treating safelisted classes with an unknown prefix as
+    /// known utilities
+    pub fn parse_with(class: &'a str, safelist: &Safelist) -> Self {
         let class = class.trim();
         let mut remaining = class;
 
@@ -31,16 +226,11 @@ impl TailwindClass {
             remaining = &remaining[1..];
         }
 
-        // Split variants and base class
-        let parts: Vec<&str> = remaining.split(':').collect();
-        let (variants, base_part) = if parts.len() > 1 {
-            let variants = parts[..parts.len() - 1]
-                .iter()
-                .map(|s| s.to_string())
-                .collect();
-            (variants, parts[parts.len() - 1])
-        } else {
-            (Vec::new(), parts[0])
+        // Split variants and base class. Colons inside brackets, as in
+        // `[color:red]` or `bg-[url(https://...)]`, aren't variant separators
+        let (variant_prefix, base_part) = match variant_separator(remaining) {
+            Some(colon) => (Some(&remaining[..colon]), &remaining[colon + 1..]),
+            None => (None, remaining),
         };
 
         // Check for negative modifier
@@ -50,77 +240,231 @@ impl TailwindClass {
         // Check for arbitrary value
         let arbitrary = base_without_neg.contains('[');
 
-        TailwindClass {
-            original: class.to_string(),
+        let mut category = Self::category_priority(base_without_neg);
+        if category == UNKNOWN_PRIORITY && safelist.contains(base_without_neg) {
+            category = SAFELIST_PRIORITY;
+        } else if arbitrary_property(base_without_neg).is_some() {
+            category = ARBITRARY_PROPERTY_PRIORITY;
+        } else if is_marker(base_without_neg) {
+            category = MARKER_PRIORITY;
+        }
+
+        let mut parsed = TailwindClass {
+            original: class,
             important,
-            variants,
-            base: base_without_neg.to_string(),
+            variant_prefix,
+            base: base_without_neg,
             negative,
             arbitrary,
+            key: SortKey {
+                important,
+                category,
+                has_variants: variant_prefix.is_some(),
+            },
+            variant_ranks: Vec::new(),
+        };
+        parsed.variant_ranks = parsed.rank_variants();
+        parsed
+    }
+
+    /// Parse a class unless it exceeds the limits or is a placeholder, in
+    /// which case it is kept whole and sorts after every other class,
+    /// placeholders last
+    pub fn parse_within(class: &'a str, safelist: &Safelist, options: &SortOptions) -> Self {
+        let class = class.trim();
+        let category = if is_placeholder(class) {
+            PLACEHOLDER_PRIORITY
+        } else if options.limits.exceeded_by(class) {
+            OVERSIZED_PRIORITY
+        } else {
+            let mut parsed = Self::parse_with(class, safelist);
+            parsed.key.important &= options.important_affects_order;
+            return parsed;
+        };
+
+        TailwindClass {
+            original: class,
+            important: false,
+            variant_prefix: None,
+            base: class,
+            negative: false,
+            arbitrary: false,
+            // Sorted as important so they also follow important classes
+            key: SortKey {
+                important: true,
+                category,
+                has_variants: false,
+            },
+            variant_ranks: Vec::new(),
         }
     }
 
+    /// Group of the class between `groupSeparator` separators, or `None`
+    /// for placeholders, which belong to no group
+    ///
+    /// Groups are the sections of the Tailwind order, such as layout,
+    /// spacing, typography or backgrounds, with classes sorted as important
+    /// grouped apart from the others.
+    fn group(&self) -> Option<(bool, u32)> {
+        let group = match self.key.category {
+            PLACEHOLDER_PRIORITY => return None,
+            // Markers, visibility, position and display are all layout
+            category if category < 200 => 1,
+            category => category / 100,
+        };
+        Some((self.key.important, group))
+    }
+
+    /// Whether the class has a known utility prefix or is safelisted
+    pub fn is_known(&self) -> bool {
+        self.key.category != UNKNOWN_PRIORITY && self.key.category < OVERSIZED_PRIORITY
+    }
+
+    /// Variants in source order (e.g., ["dark", "hover", "md"])
+    pub fn variants(&self) -> impl Iterator<Item = &'a str> {
+        let mut rest = self.variant_prefix;
+        std::iter::from_fn(move || {
+            let prefix = rest?;
+            match top_level_colons(prefix).next() {
+                Some(colon) => {
+                    rest = Some(&prefix[colon + 1..]);
+                    Some(&prefix[..colon])
+                }
+                None => rest.take(),
+            }
+        })
+    }
+
     /// Get the category priority for sorting
     /// Based on TailwindCSS official class order
     #[allow(dead_code)]
-    fn category_priority(&self) -> u32 {
+    fn category_priority(base: &str) -> u32 {
         // Extract the utility prefix (e.g., "text" from "text-red-500")
-        let prefix = self.base.split('-').next().unwrap_or(&self.base);
+        let prefix = base.split('-').next().unwrap_or(base);
 
         // TailwindCSS recommended order following Prettier plugin
         match prefix {
-            // Layout - Display, Position, Overflow
+            // Layout - Visibility, Position, Inset, Isolation, Z-Index. Tailwind
+            // v4 orders these ahead of display and spacing, and the inset
+            // properties in the order of the CSS box
+            "visible" | "invisible" | "collapse" => 40,
+            "position" | "static" | "fixed" | "absolute" | "relative" | "sticky" => 50,
+            "inset" => 60,
+            "start" => 61,
+            "end" => 62,
+            "top" => 63,
+            "right" => 64,
+            "bottom" => 65,
+            "left" => 66,
+            "isolate" | "isolation" => 70,
+            "z" => 80,
+            "order" => 90,
+
+            // Layout - Display, Overflow
             "container" | "box" | "block" | "inline" | "hidden" => 100,
-            "float" | "clear" | "object" | "overflow" | "overscroll" => 110,
-
-            // Flexbox & Grid
-            "flex" | "grow" | "shrink" | "basis" | "order" => 200,
-            "grid" | "col" | "row" | "gap" | "auto" | "justify" | "items" | "content" | "place" => {
-                210
+            "float" | "clear" | "object" => 110,
+            "overflow" => 112,
+            "overscroll" => 113,
+            "scroll" if matches!(base, "scroll-auto" | "scroll-smooth") => 114,
+
+            // Flexbox & Grid - display, then item sizing and placement, then
+            // the container's tracks, direction, wrapping, alignment and gaps
+            "flex" | "grid" if base == prefix => 200,
+            "flex"
+                if matches!(
+                    base,
+                    "flex-row" | "flex-row-reverse" | "flex-col" | "flex-col-reverse"
+                ) =>
+            {
+                207
             }
+            "flex" if matches!(base, "flex-wrap" | "flex-wrap-reverse" | "flex-nowrap") => 208,
+            "flex" => 201,
+            "shrink" => 202,
+            "grow" => 203,
+            "basis" => 204,
+            "col" | "row" => 205,
+            "grid" | "auto" => 206,
+            "place" => 210,
+            "content" => 211,
+            "items" => 212,
+            "justify" => 213,
+            "gap" => 214,
+            "space" => 215,
+            "divide" => 216,
+            "self" => 217,
 
             // Spacing (margin, padding) - comes EARLY in Tailwind order
             "m" | "mx" | "my" | "mt" | "mr" | "mb" | "ml" | "margin" => 300,
             "p" | "px" | "py" | "pt" | "pr" | "pb" | "pl" | "padding" => 310,
-            "space" => 320,
 
             // Sizing
             "w" | "width" | "h" | "height" => 400,
             "min" | "max" => 410,
 
-            // Position & Z-Index - comes AFTER spacing
-            "position" | "static" | "fixed" | "absolute" | "relative" | "sticky" => 500,
-            "top" | "right" | "bottom" | "left" | "inset" => 510,
-            "z" => 520,
-
-            // Typography
-            "font" | "text" | "tracking" | "leading" | "list" | "align" => 600,
+            // Typography - alignment, then font family, size, smoothing,
+            // style, weight, tracking, line height and color
+            "list" | "align" => 600,
+            "text" => Self::text_priority(base),
+            "font" => Self::font_priority(base),
+            "antialiased" | "subpixel" => 603,
+            "italic" => 604,
+            "not" if base == "not-italic" => 604,
+            "tracking" => 606,
+            "leading" => 607,
             "whitespace" | "break" | "truncate" => 610,
 
-            // Backgrounds
-            "bg" | "from" | "via" | "to" => 700,
+            // Backgrounds - color, image and gradient stops, then size,
+            // attachment, clip, position, repeat and origin
+            "bg" => Self::background_priority(base),
+            "from" => 702,
+            "via" => 703,
+            "to" => 704,
 
-            // Borders
-            "border" | "divide" | "outline" | "ring" => 800,
-            "rounded" => 810,
+            // Borders - radius, width, color and style, each by side
+            "rounded" => Self::rounded_priority(base),
+            "border" => Self::border_priority(base),
+            "outline" | "ring" => 880,
 
             // Effects
-            "shadow" | "opacity" | "mix" | "blur" => 900,
-
-            // Filters
-            "filter" | "backdrop" | "brightness" | "contrast" | "grayscale" => 1000,
+            "shadow" | "opacity" | "mix" => 900,
+
+            // Filters, then backdrop filters, each in the order of the
+            // filter functions
+            "filter" => 1000,
+            "backdrop" => {
+                let function = base.strip_prefix("backdrop-").unwrap_or_default();
+                let function = function.split('-').next().unwrap_or(function);
+                1020 + Self::filter_index(function).unwrap_or(0)
+            }
+            function if Self::filter_index(function).is_some() => {
+                1000 + Self::filter_index(function).unwrap_or(0)
+            }
 
             // Tables
             "caption" | "table" => 1100,
 
+            // Transforms - origin, then translate, scale, rotate and skew as
+            // separate properties, then the transform itself
+            "origin" => 1150,
+            "translate" => 1151,
+            "scale" => 1152,
+            "rotate" => 1153,
+            "skew" => 1154,
+            "transform" => 1155,
+
             // Transitions & Animation
             "transition" | "duration" | "ease" | "delay" | "animate" => 1200,
 
-            // Transforms
-            "transform" | "origin" | "scale" | "rotate" | "translate" | "skew" => 1300,
-
-            // Interactivity
-            "cursor" | "select" | "resize" | "pointer" | "appearance" => 1400,
+            // Interactivity - cursor, touch action and resizing, then scroll
+            // snapping, scroll margin and scroll padding
+            "cursor" => 1400,
+            "touch" => 1401,
+            "resize" => 1402,
+            "snap" => Self::snap_priority(base),
+            "scroll" if base.starts_with("scroll-m") => 1406,
+            "scroll" if base.starts_with("scroll-p") => 1407,
+            "select" | "pointer" | "appearance" => 1410,
 
             // SVG
             "fill" | "stroke" => 1500,
@@ -129,131 +473,322 @@ impl TailwindClass {
             "sr" | "screen" => 1600,
 
             // Custom/Unknown - last
-            _ => 9999,
+            _ => UNKNOWN_PRIORITY,
+        }
+    }
+
+    /// Priority of a `text-*` class: alignment, font size, color, or one of
+    /// the text wrapping and overflow utilities
+    fn text_priority(base: &str) -> u32 {
+        let value = base.strip_prefix("text-").unwrap_or_default();
+        // A line height modifier, as in `text-sm/6`, doesn't change the kind
+        let value = value.split('/').next().unwrap_or(value);
+        match value {
+            "left" | "center" | "right" | "justify" | "start" | "end" => 600,
+            "xs" | "sm" | "base" | "lg" | "xl" => 602,
+            size if size.ends_with("xl") && size[..size.len() - 2].parse::<u8>().is_ok() => 602,
+            "ellipsis" | "clip" | "wrap" | "nowrap" | "balance" | "pretty" => 609,
+            arbitrary if arbitrary.starts_with("[length:") => 602,
+            arbitrary
+                if arbitrary.starts_with('[')
+                    && arbitrary[1..].starts_with(|c: char| c.is_ascii_digit()) =>
+            {
+                602
+            }
+            _ => 608,
+        }
+    }
+
+    /// Position of a filter function among the filter utilities, counting
+    /// from 1, with `drop` for `drop-shadow` and `hue` for `hue-rotate`
+    fn filter_index(function: &str) -> Option<u32> {
+        const FILTERS: [&str; 10] = [
+            "blur",
+            "brightness",
+            "contrast",
+            "drop",
+            "grayscale",
+            "hue",
+            "invert",
+            "opacity",
+            "saturate",
+            "sepia",
+        ];
+        FILTERS
+            .iter()
+            .position(|filter| *filter == function)
+            .map(|index| index as u32 + 1)
+    }
+
+    /// Priority of a `snap-*` class: snap type, then alignment, then stop
+    fn snap_priority(base: &str) -> u32 {
+        match base.strip_prefix("snap-").unwrap_or_default() {
+            "start" | "end" | "center" | "align-none" => 1404,
+            "normal" | "always" => 1405,
+            _ => 1403,
+        }
+    }
+
+    /// Priority of a `bg-*` class by the background property it sets
+    fn background_priority(base: &str) -> u32 {
+        let value = base.strip_prefix("bg-").unwrap_or_default();
+        if let Some(arbitrary) = value.strip_prefix('[') {
+            return match arbitrary.split_once(':').map(|(hint, _)| hint) {
+                Some("length" | "size") => 705,
+                Some("position") => 708,
+                Some("image" | "url") => 701,
+                _ if arbitrary.starts_with("url(") || arbitrary.contains("gradient(") => 701,
+                _ => 700,
+            };
+        }
+
+        let kind = value.split('-').next().unwrap_or(value);
+        match kind {
+            "none" | "gradient" | "linear" | "radial" | "conic" => 701,
+            "auto" | "cover" | "contain" => 705,
+            "fixed" | "local" | "scroll" => 706,
+            "clip" => 707,
+            "bottom" | "center" | "left" | "right" | "top" => 708,
+            "repeat" | "no" => 709,
+            "origin" => 710,
+            "blend" => 900,
+            _ => 700,
+        }
+    }
+
+    /// Priority of a `rounded-*` class: all corners, then sides, then
+    /// single corners
+    fn rounded_priority(base: &str) -> u32 {
+        const CORNERS: [&str; 14] = [
+            "s", "e", "t", "r", "b", "l", "ss", "se", "ee", "es", "tl", "tr", "br", "bl",
+        ];
+        let corner = base
+            .strip_prefix("rounded-")
+            .and_then(|value| value.split('-').next())
+            .and_then(|corner| CORNERS.iter().position(|c| *c == corner));
+        800 + corner.map_or(0, |index| index as u32 + 1)
+    }
+
+    /// Priority of a `border-*` class: width, color or style, all sides
+    /// first and then each side
+    fn border_priority(base: &str) -> u32 {
+        const SIDES: [&str; 8] = ["x", "y", "s", "e", "t", "r", "b", "l"];
+        let value = base.strip_prefix("border").unwrap_or_default();
+        let value = value.strip_prefix('-').unwrap_or(value);
+        if value == "collapse" || value == "separate" || value.starts_with("spacing") {
+            return 1100;
+        }
+
+        let (side, value) = match value.split_once('-') {
+            Some((side, rest)) if SIDES.contains(&side) => (Some(side), rest),
+            _ if SIDES.contains(&value) => (Some(value), ""),
+            _ => (None, value),
+        };
+        let side = side
+            .and_then(|side| SIDES.iter().position(|s| *s == side))
+            .map_or(0, |index| index as u32 + 1);
+
+        let is_width = value.is_empty()
+            || value.bytes().all(|b| b.is_ascii_digit())
+            || value.starts_with("[length:")
+            || (value.starts_with('[') && value[1..].starts_with(|c: char| c.is_ascii_digit()));
+        let property = match value {
+            _ if is_width => 820,
+            "solid" | "dashed" | "dotted" | "double" | "hidden" | "none" => 860,
+            _ => 840,
+        };
+        property + side
+    }
+
+    /// Priority of a `font-*` class: font family or font weight
+    fn font_priority(base: &str) -> u32 {
+        match base.strip_prefix("font-").unwrap_or_default() {
+            "sans" | "serif" | "mono" => 601,
+            arbitrary
+                if arbitrary.starts_with('[')
+                    && !arbitrary[1..].starts_with(|c: char| c.is_ascii_digit()) =>
+            {
+                601
+            }
+            _ => 605,
         }
     }
 
     /// Get the variant priority for sorting
+    ///
+    /// Follows Tailwind v4's variant order: group and peer variants, then
+    /// structural and state pseudo-classes, then breakpoints and dark mode.
     #[allow(dead_code)]
     fn variant_priority(variant: &str) -> u32 {
         match variant {
-            // Responsive breakpoints
-            "sm" => 100,
-            "md" => 110,
-            "lg" => 120,
-            "xl" => 130,
-            "2xl" => 140,
+            // Group/Peer, including `group-hover` and named groups
+            _ if variant.starts_with("group") => 100,
+            _ if variant.starts_with("peer") => 110,
 
-            // Dark mode
-            "dark" => 200,
+            // Position
+            "first" => 200,
+            "last" => 210,
+            "only" => 215,
+            "odd" => 220,
+            "even" => 230,
 
             // State variants
-            "hover" => 300,
-            "focus" => 310,
-            "active" => 320,
-            "visited" => 330,
-            "disabled" => 340,
-            "enabled" => 350,
+            "visited" => 300,
+            "focus-within" => 305,
+            "hover" => 310,
+            "focus" => 320,
+            "focus-visible" => 325,
+            "active" => 330,
+            "enabled" => 340,
+            "disabled" => 350,
+
+            // Responsive breakpoints, ordered among themselves by width
+            _ if Self::breakpoint(variant).is_some() => 400,
 
-            // Group/Peer
-            "group" => 400,
-            "peer" => 410,
-
-            // Position
-            "first" => 500,
-            "last" => 510,
-            "odd" => 520,
-            "even" => 530,
+            // Dark mode
+            "dark" => 500,
 
             // Other
             _ => 9999,
         }
     }
 
+    /// Width in px of a breakpoint variant such as `md`, `max-sm` or
+    /// `min-[600px]`, and whether it is a `max-*` variant
+    ///
+    /// `max-*` variants sort right after the `min` breakpoint of the same
+    /// width.
+    fn breakpoint(variant: &str) -> Option<(u32, bool)> {
+        if let Some(name) = variant.strip_prefix("max-") {
+            let width = Self::screen_width(name).or_else(|| Self::arbitrary_width(name))?;
+            return Some((width, true));
+        }
+        if let Some(value) = variant.strip_prefix("min-") {
+            return Some((Self::arbitrary_width(value)?, false));
+        }
+        Some((Self::screen_width(variant)?, false))
+    }
+
+    /// Width in px of a default screen
+    fn screen_width(name: &str) -> Option<u32> {
+        match name {
+            "sm" => Some(640),
+            "md" => Some(768),
+            "lg" => Some(1024),
+            "xl" => Some(1280),
+            "2xl" => Some(1536),
+            _ => None,
+        }
+    }
+
+    /// Width in px of an arbitrary breakpoint value such as `[600px]` or
+    /// `[40rem]`
+    fn arbitrary_width(value: &str) -> Option<u32> {
+        let value = value.strip_prefix('[')?.strip_suffix(']')?;
+        let (number, scale) = if let Some(px) = value.strip_suffix("px") {
+            (px, 1.0)
+        } else if let Some(rem) = value.strip_suffix("rem") {
+            (rem, 16.0)
+        } else {
+            (value.strip_suffix("em")?, 16.0)
+        };
+        Some((number.parse::<f64>().ok()? * scale).round() as u32)
+    }
+
     /// Compare variants for sorting
+    ///
+    /// Like Tailwind, which compares the variants of two classes as bit
+    /// masks, the highest ranked variant of each class decides first, then
+    /// the next highest, and so on. A class with a subset of the other's
+    /// variants sorts first.
     #[allow(dead_code)]
     fn compare_variants(&self, other: &Self) -> Ordering {
-        // Compare variant count first
-        match self.variants.len().cmp(&other.variants.len()) {
-            Ordering::Equal => {
-                // Compare each variant by priority
-                for (v1, v2) in self.variants.iter().zip(other.variants.iter()) {
-                    let p1 = Self::variant_priority(v1);
-                    let p2 = Self::variant_priority(v2);
-                    match p1.cmp(&p2) {
-                        Ordering::Equal => {
-                            // If same priority, compare alphabetically
-                            match v1.cmp(v2) {
-                                Ordering::Equal => continue,
-                                other => return other,
-                            }
-                        }
-                        other => return other,
-                    }
-                }
-                Ordering::Equal
-            }
-            other => other,
-        }
+        self.variant_ranks.cmp(&other.variant_ranks)
+    }
+
+    /// Rank of each variant, highest first: its priority, then breakpoint
+    /// width, then its name
+    fn rank_variants(&self) -> Vec<VariantRank<'a>> {
+        let mut ranks: Vec<_> = self
+            .variants()
+            .map(|variant| {
+                (
+                    Self::variant_priority(variant),
+                    Self::breakpoint(variant),
+                    variant,
+                )
+            })
+            .collect();
+        ranks.sort_unstable_by(|a, b| b.cmp(a));
+        ranks
     }
 }
 
-impl PartialOrd for TailwindClass {
+impl PartialOrd for TailwindClass<'_> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl Ord for TailwindClass {
+impl Ord for TailwindClass<'_> {
     fn cmp(&self, other: &Self) -> Ordering {
         // 1. Non-important classes first, important classes last
-        match self.important.cmp(&other.important) {
-            Ordering::Equal => {}
-            other => return other,
-        }
-
         // 2. Compare by category priority
-        let cat1 = self.category_priority();
-        let cat2 = other.category_priority();
-        match cat1.cmp(&cat2) {
-            Ordering::Equal => {}
-            other => return other,
-        }
-
         // 3. Within same category: classes without variants first
-        match self.variants.len().cmp(&other.variants.len()) {
+        match self.key.cmp(&other.key) {
+            Ordering::Equal if self.key.category >= OVERSIZED_PRIORITY => return Ordering::Equal,
             Ordering::Equal => {}
             other => return other,
         }
 
         // 4. Compare by variants if both have variants
-        if !self.variants.is_empty() || !other.variants.is_empty() {
+        if self.variant_prefix.is_some() || other.variant_prefix.is_some() {
             match self.compare_variants(other) {
                 Ordering::Equal => {}
                 other => return other,
             }
         }
 
-        // 5. Positive values before negative
-        match self.negative.cmp(&other.negative) {
+        // 5. Non-arbitrary before arbitrary values
+        match self.arbitrary.cmp(&other.arbitrary) {
             Ordering::Equal => {}
             other => return other,
         }
 
-        // 6. Non-arbitrary before arbitrary values
-        match self.arbitrary.cmp(&other.arbitrary) {
+        // 6. Compare base class names alphabetically, arbitrary properties by
+        // property name first. The base excludes the `-`, so a negative
+        // class stays next to its positive counterpart
+        if self.key.category == ARBITRARY_PROPERTY_PRIORITY {
+            let order = arbitrary_property(self.base).cmp(&arbitrary_property(other.base));
+            if order != Ordering::Equal {
+                return order;
+            }
+        }
+        match self.base.cmp(other.base) {
             Ordering::Equal => {}
             other => return other,
         }
 
-        // 7. Finally, compare base class names alphabetically
-        self.base.cmp(&other.base)
+        // 7. Finally, positive values before negative
+        self.negative.cmp(&other.negative)
     }
 }
 
 /// Sort a space-separated list of TailwindCSS classes
 #[allow(dead_code)]
 pub fn sort_classes(classes: &str) -> String {
+    sort_classes_with(classes, &Safelist::default())
+}
+
+/// Sort a space-separated list of classes, placing safelisted classes with
+/// known utilities rather than with unknown classes
+pub fn sort_classes_with(classes: &str, safelist: &Safelist) -> String {
+    sort_classes_within(classes, safelist, &SortOptions::default())
+}
+
+/// Sort a space-separated list of classes, moving classes over the limits
+/// to the end in their original order without parsing them
+pub fn sort_classes_within(classes: &str, safelist: &Safelist, options: &SortOptions) -> String {
     let trimmed = classes.trim();
     if trimmed.is_empty() {
         return String::new();
@@ -262,24 +797,148 @@ pub fn sort_classes(classes: &str) -> String {
     // Parse all classes
     let mut parsed_classes: Vec<TailwindClass> = trimmed
         .split_whitespace()
-        .map(TailwindClass::parse)
+        .map(|class| TailwindClass::parse_within(class, safelist, options))
         .collect();
 
-    // Sort the classes
+    // Sort the classes. The sort is stable, so classes over the limits,
+    // which compare equal, keep their order
     parsed_classes.sort();
 
-    // Reconstruct the string
-    parsed_classes
-        .iter()
-        .map(|c| c.original.as_str())
+    // Different classes can compare equal, such as `hover:focus:p-4` and
+    // `focus:hover:p-4`, and so do classes over the limits, so a duplicate
+    // could still end up apart from its first copy. Move later copies up by
+    // counting the copies of each class at the place of its first one
+    let mut first_index: HashMap<&str, usize> = HashMap::with_capacity(parsed_classes.len());
+    let mut counted: Vec<(&str, usize)> = Vec::with_capacity(parsed_classes.len());
+    for class in &parsed_classes {
+        match first_index.get(class.original) {
+            Some(&index) => counted[index].1 += 1,
+            None => {
+                first_index.insert(class.original, counted.len());
+                counted.push((class.original, 1));
+            }
+        }
+    }
+
+    counted
+        .into_iter()
+        .flat_map(|(class, count)| std::iter::repeat_n(class, count))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Put the classes of `sorted` in the places of the classes of `original`
+///
+/// The leading and trailing whitespace of `original` and the separators
+/// between its classes, such as line breaks or non-breaking spaces, are kept
+/// as they are.
+pub fn with_separators_of(original: &str, sorted: &str) -> String {
+    let mut classes = sorted.split_whitespace();
+    let mut result = String::with_capacity(original.len());
+    let mut rest = original;
+
+    while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+        result.push_str(&rest[..start]);
+        let class = &rest[start..];
+        let end = class.find(char::is_whitespace).unwrap_or(class.len());
+        result.push_str(classes.next().unwrap_or_default());
+        rest = &class[end..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Move the placeholders that sorting put at the end of `sorted` to its
+/// start, keeping their order
+pub fn placeholders_first(sorted: &str) -> String {
+    let (placeholders, classes): (Vec<&str>, Vec<&str>) = sorted
+        .split_whitespace()
+        .partition(|token| is_placeholder(token));
+    placeholders
+        .into_iter()
+        .chain(classes)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Move the placeholders of `sorted` back to their indices in `original`
+pub fn placeholders_in_place(original: &str, sorted: &str) -> String {
+    let mut classes = sorted
+        .split_whitespace()
+        .filter(|token| !is_placeholder(token));
+    original
+        .split_whitespace()
+        .map(|token| {
+            if is_placeholder(token) {
+                token
+            } else {
+                classes.next().unwrap_or_default()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Put `separator` between the classes of `sorted` where a new group of
+/// the Tailwind order starts
+///
+/// Placeholders neither start nor end a group, so they never get a
+/// separator of their own.
+pub fn with_group_separators(
+    sorted: &str,
+    separator: &str,
+    safelist: &Safelist,
+    options: &SortOptions,
+) -> String {
+    let mut tokens = Vec::new();
+    let mut current = None;
+    for token in sorted.split_whitespace() {
+        let group = TailwindClass::parse_within(token, safelist, options).group();
+        if group.is_some() {
+            if current.is_some() && current != group {
+                tokens.push(separator);
+            }
+            current = group;
+        }
+        tokens.push(token);
+    }
+    tokens.join(" ")
+}
+
+/// The classes of `classes` without the group separators in it
+pub fn without_group_separators(classes: &str, separator: &str) -> String {
+    classes
+        .split_whitespace()
+        .filter(|token| *token != separator)
         .collect::<Vec<_>>()
         .join(" ")
 }
 
+/// Whether two class lists hold the same classes in the same order
+///
+/// Separators are ignored, so a list that another formatter re-wrapped
+/// across lines compares equal to its single-line form.
+pub fn same_class_order(a: &str, b: &str) -> bool {
+    a.split_whitespace().eq(b.split_whitespace())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_with_separators_of() {
+        let original = "\n  p-4\u{a0}z-10\n  flex ";
+        let sorted = sort_classes(original);
+        assert_eq!(sorted, "z-10 flex p-4");
+        assert_eq!(
+            with_separators_of(original, &sorted),
+            "\n  z-10\u{a0}flex\n  p-4 "
+        );
+        assert_eq!(with_separators_of("z-10\r\np-4", "p-4 z-10"), "p-4\r\nz-10");
+        assert_eq!(with_separators_of("", ""), "");
+    }
+
     #[test]
     fn test_parse_simple_class() {
         let class = TailwindClass::parse("text-red-500");
@@ -287,7 +946,7 @@ mod tests {
         assert!(!class.important);
         assert!(!class.negative);
         assert!(!class.arbitrary);
-        assert!(class.variants.is_empty());
+        assert_eq!(class.variants().count(), 0);
     }
 
     #[test]
@@ -321,7 +980,7 @@ mod tests {
     fn test_parse_with_variants() {
         let class = TailwindClass::parse("hover:bg-blue-500");
         assert_eq!(class.base, "bg-blue-500");
-        assert_eq!(class.variants, vec!["hover"]);
+        assert_eq!(class.variants().collect::<Vec<_>>(), vec!["hover"]);
         assert!(!class.important);
     }
 
@@ -329,7 +988,10 @@ mod tests {
     fn test_parse_with_multiple_variants() {
         let class = TailwindClass::parse("dark:hover:focus:text-white");
         assert_eq!(class.base, "text-white");
-        assert_eq!(class.variants, vec!["dark", "hover", "focus"]);
+        assert_eq!(
+            class.variants().collect::<Vec<_>>(),
+            vec!["dark", "hover", "focus"]
+        );
     }
 
     #[test]
@@ -339,13 +1001,13 @@ mod tests {
         assert!(class.important);
         assert!(class.negative);
         assert!(class.arbitrary);
-        assert_eq!(class.variants, vec!["md", "hover"]);
+        assert_eq!(class.variants().collect::<Vec<_>>(), vec!["md", "hover"]);
     }
 
     #[test]
     fn test_sort_simple_classes() {
         let input = "z-10 p-4 mt-2";
-        let expected = "mt-2 p-4 z-10";
+        let expected = "z-10 mt-2 p-4";
         assert_eq!(sort_classes(input), expected);
     }
 
@@ -358,8 +1020,8 @@ mod tests {
 
     #[test]
     fn test_sort_responsive_breakpoints() {
-        let input = "xl:text-xl md:text-md text-base";
-        let expected = "text-base md:text-md xl:text-xl";
+        let input = "xl:text-xl md:text-lg text-base";
+        let expected = "text-base md:text-lg xl:text-xl";
         assert_eq!(sort_classes(input), expected);
     }
 
@@ -384,10 +1046,286 @@ mod tests {
         assert_eq!(sort_classes(input), expected);
     }
 
+    #[test]
+    fn test_sort_layout_classes() {
+        let input =
+            "p-4 z-10 flex left-0 isolate top-2 -end-1 invisible start-1 inset-x-0 absolute";
+        let expected =
+            "invisible absolute inset-x-0 start-1 -end-1 top-2 left-0 isolate z-10 flex p-4";
+        assert_eq!(sort_classes(input), expected);
+    }
+
+    #[test]
+    fn test_sort_flex_container_and_item_classes() {
+        let input = "gap-2 justify-between flex-wrap items-center flex-col order-2 basis-1/2 shrink-0 grow flex-1 flex";
+        let expected = "order-2 flex flex-1 shrink-0 grow basis-1/2 flex-col flex-wrap items-center justify-between gap-2";
+        assert_eq!(sort_classes(input), expected);
+
+        let input = "gap-4 self-end place-items-center grid-cols-3 col-span-2 grid";
+        let expected = "grid col-span-2 grid-cols-3 place-items-center gap-4 self-end";
+        assert_eq!(sort_classes(input), expected);
+    }
+
+    #[test]
+    fn test_sort_gap_space_and_divide() {
+        let fixtures = [
+            (
+                "space-y-4 gap-2 flex-col flex",
+                "flex flex-col gap-2 space-y-4",
+            ),
+            ("mt-2 space-x-2 gap-x-4 flex", "flex gap-x-4 space-x-2 mt-2"),
+            (
+                "border-t divide-y space-y-2 self-start",
+                "space-y-2 divide-y self-start border-t",
+            ),
+            (
+                "divide-dashed -space-x-1 gap-y-1 grid",
+                "grid gap-y-1 -space-x-1 divide-dashed",
+            ),
+        ];
+        for (input, expected) in fixtures {
+            assert_eq!(sort_classes(input), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_sort_typography_classes() {
+        let input = "text-red-500 leading-6 tracking-wide font-bold italic antialiased text-sm font-sans text-center";
+        let expected = "text-center font-sans text-sm antialiased italic font-bold tracking-wide leading-6 text-red-500";
+        assert_eq!(sort_classes(input), expected);
+
+        let input = "text-[#333] font-[600] text-ellipsis text-2xl/7 font-[Inter] text-[14px]";
+        let expected = "font-[Inter] text-2xl/7 text-[14px] font-[600] text-[#333] text-ellipsis";
+        assert_eq!(sort_classes(input), expected);
+    }
+
+    #[test]
+    fn test_sort_border_classes() {
+        let input = "rounded-t-none border-b-2 border-gray-200 rounded-lg border";
+        let expected = "rounded-lg rounded-t-none border border-b-2 border-gray-200";
+        assert_eq!(sort_classes(input), expected);
+
+        let input =
+            "border-dashed border-l-red-500 border-t border-x-4 rounded-tl-md rounded-[4px] ring-2";
+        let expected =
+            "rounded-[4px] rounded-tl-md border-x-4 border-t border-l-red-500 border-dashed ring-2";
+        assert_eq!(sort_classes(input), expected);
+
+        assert_eq!(
+            sort_classes("border-collapse table-auto border"),
+            "border border-collapse table-auto"
+        );
+    }
+
+    #[test]
+    fn test_sort_background_classes() {
+        let input = "to-y from-x bg-gradient-to-r bg-no-repeat bg-center bg-cover bg-white";
+        let expected = "bg-white bg-gradient-to-r from-x to-y bg-cover bg-center bg-no-repeat";
+        assert_eq!(sort_classes(input), expected);
+
+        let input =
+            "bg-origin-border bg-left-top bg-clip-text bg-fixed via-z bg-[url(/a.png)] bg-[#fff]";
+        let expected =
+            "bg-[#fff] bg-[url(/a.png)] via-z bg-fixed bg-clip-text bg-left-top bg-origin-border";
+        assert_eq!(sort_classes(input), expected);
+    }
+
+    #[test]
+    fn test_sort_transform_and_filter_classes() {
+        let input = "transform skew-x-3 rotate-45 scale-95 -translate-x-1/2 origin-top transition";
+        let expected =
+            "origin-top -translate-x-1/2 scale-95 rotate-45 skew-x-3 transform transition";
+        assert_eq!(sort_classes(input), expected);
+
+        let input =
+            "backdrop-opacity-50 backdrop-blur-sm sepia drop-shadow-md contrast-125 blur-sm filter";
+        let expected =
+            "filter blur-sm contrast-125 drop-shadow-md sepia backdrop-blur-sm backdrop-opacity-50";
+        assert_eq!(sort_classes(input), expected);
+
+        assert_eq!(
+            sort_classes("backdrop-filter backdrop-saturate-150 hue-rotate-15"),
+            "hue-rotate-15 backdrop-filter backdrop-saturate-150"
+        );
+    }
+
+    #[test]
+    fn test_sort_interactivity_and_scroll_classes() {
+        let input = "overscroll-contain snap-x scroll-mt-4 scroll-smooth touch-pan-x select-none cursor-pointer";
+        let expected = "overscroll-contain scroll-smooth cursor-pointer touch-pan-x snap-x scroll-mt-4 select-none";
+        assert_eq!(sort_classes(input), expected);
+
+        let input = "scroll-px-2 snap-always snap-start snap-mandatory resize overflow-auto";
+        let expected = "overflow-auto resize snap-mandatory snap-start snap-always scroll-px-2";
+        assert_eq!(sort_classes(input), expected);
+    }
+
+    #[test]
+    fn test_sort_arbitrary_and_max_breakpoints() {
+        let input = "lg:p-4 max-sm:p-1 min-[700px]:p-3 md:p-2 hover:p-5 sm:p-1 min-[600px]:p-0";
+        let expected = "hover:p-5 min-[600px]:p-0 sm:p-1 max-sm:p-1 min-[700px]:p-3 md:p-2 lg:p-4";
+        assert_eq!(sort_classes(input), expected);
+
+        assert_eq!(
+            sort_classes("max-[50rem]:flex min-[48rem]:flex max-md:flex"),
+            "min-[48rem]:flex max-md:flex max-[50rem]:flex"
+        );
+    }
+
+    #[test]
+    fn test_sort_dark_and_combined_variants() {
+        // Matches prettier-plugin-tailwindcss with Tailwind v4
+        let fixtures = [
+            (
+                "sm:dark:bg-y dark:hover:bg-x",
+                "dark:hover:bg-x sm:dark:bg-y",
+            ),
+            (
+                "dark:bg-x sm:bg-x hover:bg-x",
+                "hover:bg-x sm:bg-x dark:bg-x",
+            ),
+            ("dark:bg-x hover:focus:bg-x", "hover:focus:bg-x dark:bg-x"),
+            (
+                "md:hover:bg-x sm:bg-x lg:bg-x",
+                "sm:bg-x md:hover:bg-x lg:bg-x",
+            ),
+            (
+                "group-hover:bg-x first:bg-x focus:bg-x",
+                "group-hover:bg-x first:bg-x focus:bg-x",
+            ),
+        ];
+        for (input, expected) in fixtures {
+            assert_eq!(sort_classes(input), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_variant_count_does_not_decide_order() {
+        assert_eq!(
+            sort_classes("sm:p-4 hover:focus:p-4"),
+            "hover:focus:p-4 sm:p-4"
+        );
+        assert_eq!(sort_classes("lg:p-2 sm:hover:p-2"), "sm:hover:p-2 lg:p-2");
+        assert_eq!(sort_classes("sm:hover:p-2 sm:p-2"), "sm:p-2 sm:hover:p-2");
+    }
+
+    #[test]
+    fn test_placeholders_sort_last() {
+        assert_eq!(sort_classes("... z-10 p-4"), "z-10 p-4 ...");
+        assert_eq!(
+            sort_classes("\u{2026} p-4 !mt-2 * custom {...props} flex"),
+            "flex p-4 custom !mt-2 \u{2026} * {...props}"
+        );
+        assert!(!is_placeholder("*:p-4"));
+        assert!(!is_placeholder("{size}"));
+
+        let sorted = sort_classes("* p-4 ... z-10");
+        assert_eq!(sorted, "z-10 p-4 * ...");
+        assert_eq!(placeholders_first(&sorted), "* ... z-10 p-4");
+        assert_eq!(
+            placeholders_in_place("* p-4 ... z-10", &sorted),
+            "* z-10 ... p-4"
+        );
+    }
+
+    #[test]
+    fn test_group_separators() {
+        let separate = |sorted: &str| {
+            with_group_separators(sorted, "|", &Safelist::default(), &SortOptions::default())
+        };
+        let sorted =
+            sort_classes("text-red-500 p-4 hover:p-6 flex mt-2 absolute bg-blue-500 !bg-white");
+        assert_eq!(
+            separate(&sorted),
+            "absolute | flex | mt-2 p-4 hover:p-6 | text-red-500 | bg-blue-500 | !bg-white"
+        );
+        assert_eq!(without_group_separators(&separate(&sorted), "|"), sorted);
+
+        // Placeholders don't get separators of their own
+        assert_eq!(separate("flex ... p-4 *"), "flex ... | p-4 *");
+        assert_eq!(separate("... z-10 block"), "... z-10 block");
+        assert_eq!(separate(""), "");
+    }
+
+    #[test]
+    fn test_sort_arbitrary_properties() {
+        let input = "[mask-type:luminance] custom [color-scheme:dark] p-4 [color:red] z-10";
+        let expected = "z-10 p-4 [color:red] [color-scheme:dark] [mask-type:luminance] custom";
+        assert_eq!(sort_classes(input), expected);
+
+        let class = TailwindClass::parse("hover:[color:red]");
+        assert_eq!(class.base, "[color:red]");
+        assert_eq!(class.variants().collect::<Vec<_>>(), vec!["hover"]);
+        assert!(class.is_known());
+
+        let class = TailwindClass::parse("[&:hover]:md:bg-[url(https://a.png)]");
+        assert_eq!(class.base, "bg-[url(https://a.png)]");
+        assert_eq!(
+            class.variants().collect::<Vec<_>>(),
+            vec!["[&:hover]", "md"]
+        );
+    }
+
+    #[test]
+    fn test_negative_classes_next_to_positive() {
+        assert_eq!(sort_classes("-mt-4 mt-8 mt-4"), "mt-4 -mt-4 mt-8");
+        assert_eq!(
+            sort_classes("-translate-x-1 translate-y-2 translate-x-1"),
+            "translate-x-1 -translate-x-1 translate-y-2"
+        );
+        assert_eq!(
+            sort_classes("-inset-1 inset-x-0 inset-1"),
+            "inset-1 -inset-1 inset-x-0"
+        );
+    }
+
+    #[test]
+    fn test_group_and_peer_markers_first() {
+        assert_eq!(
+            sort_classes("z-10 group-hover:p-4 peer p-2 group"),
+            "group peer z-10 p-2 group-hover:p-4"
+        );
+        assert_eq!(
+            sort_classes("flex peer/email group/item group-hover/item:flex"),
+            "group/item peer/email flex group-hover/item:flex"
+        );
+        assert!(TailwindClass::parse("group/item").is_known());
+    }
+
+    #[test]
+    fn test_duplicates_are_adjacent() {
+        assert_eq!(
+            sort_classes("p-4 z-10 p-4 flex z-10"),
+            "z-10 z-10 flex p-4 p-4"
+        );
+        assert_eq!(
+            sort_classes("hover:focus:p-4 focus:hover:p-4 hover:focus:p-4"),
+            "hover:focus:p-4 hover:focus:p-4 focus:hover:p-4"
+        );
+        assert_eq!(sort_classes("... * p-4 ..."), "p-4 ... ... *");
+    }
+
+    #[test]
+    fn test_important_ignored_for_order() {
+        let options = SortOptions {
+            important_affects_order: false,
+            ..SortOptions::default()
+        };
+        let safelist = Safelist::default();
+
+        assert_eq!(
+            sort_classes_within("!mt-2 p-4 z-10 ...", &safelist, &options),
+            "z-10 !mt-2 p-4 ..."
+        );
+        let class = TailwindClass::parse_within("!p-4", &safelist, &options);
+        assert!(class.important);
+        assert_eq!(class.base, "p-4");
+    }
+
     #[test]
     fn test_sort_mixed_complex() {
         let input = "z-10 hover:bg-blue-500 p-4 mt-2 !font-bold md:text-lg -mb-4 bg-white";
-        let expected = "mt-2 -mb-4 p-4 z-10 md:text-lg bg-white hover:bg-blue-500 !font-bold";
+        let expected = "z-10 -mb-4 mt-2 p-4 md:text-lg bg-white hover:bg-blue-500 !font-bold";
         assert_eq!(sort_classes(input), expected);
     }
 
@@ -411,6 +1349,56 @@ mod tests {
         assert!(result.contains("p-4"));
     }
 
+    #[test]
+    fn test_same_class_order_ignores_separators() {
+        assert!(same_class_order("flex p-4", "flex\n    p-4"));
+        assert!(same_class_order("  flex\tp-4 ", "flex p-4"));
+        assert!(!same_class_order("flex p-4", "p-4 flex"));
+        assert!(!same_class_order("flex p-4", "flex"));
+    }
+
+    #[test]
+    fn test_safelist_patterns() {
+        let safelist = Safelist::new(vec![
+            "btn-primary".to_string(),
+            "badge-*".to_string(),
+            "*-card".to_string(),
+            "icon-*-lg".to_string(),
+        ]);
+        assert!(safelist.contains("btn-primary"));
+        assert!(!safelist.contains("btn-secondary"));
+        assert!(safelist.contains("badge-red"));
+        assert!(safelist.contains("badge-"));
+        assert!(safelist.contains("profile-card"));
+        assert!(safelist.contains("icon-arrow-lg"));
+        assert!(!safelist.contains("icon-arrow-sm"));
+        assert!(!Safelist::default().contains("btn-primary"));
+    }
+
+    #[test]
+    fn test_sort_with_safelist() {
+        let input = "custom zeta-card p-4 btn-primary flex";
+
+        // Without a safelist, unknown classes sink to the end
+        assert_eq!(sort_classes(input), "flex p-4 btn-primary custom zeta-card");
+
+        // Safelisted classes sort ahead of the remaining unknown ones
+        let safelist = Safelist::new(vec!["zeta-*".to_string(), "btn-primary".to_string()]);
+        assert_eq!(
+            sort_classes_with(input, &safelist),
+            "flex p-4 btn-primary zeta-card custom"
+        );
+    }
+
+    #[test]
+    fn test_safelist_does_not_move_known_utilities() {
+        let safelist = Safelist::new(vec!["p-*".to_string(), "mt-2".to_string()]);
+        assert_eq!(
+            sort_classes_with("z-10 p-4 mt-2", &safelist),
+            sort_classes("z-10 p-4 mt-2")
+        );
+    }
+
     #[test]
     fn test_category_priority_layout() {
         let c1 = TailwindClass::parse("block");
@@ -439,6 +1427,54 @@ mod tests {
         assert!(c1 < c2);
     }
 
+    #[test]
+    fn test_classes_over_limits_are_not_parsed() {
+        let limits = SortOptions {
+            limits: ClassLimits {
+                max_length: 20,
+                max_variants: 3,
+            },
+            ..SortOptions::default()
+        };
+        let safelist = Safelist::default();
+        let long = format!("bg-[url({})]", "a".repeat(30));
+        let stacked = "a:b:c:d:p-2";
+        let input = format!("{stacked} z-10 {long} !mt-2 p-4");
+
+        assert_eq!(
+            sort_classes_within(&input, &safelist, &limits),
+            format!("z-10 p-4 !mt-2 {stacked} {long}")
+        );
+        assert!(!TailwindClass::parse_within(stacked, &safelist, &limits).is_known());
+        assert_eq!(
+            sort_classes_within("a:b:c:p-2 p-4", &safelist, &limits),
+            "p-4 a:b:c:p-2"
+        );
+    }
+
+    #[test]
+    fn test_colons_in_brackets_are_not_variants_for_limits() {
+        let limits = ClassLimits {
+            max_length: 100,
+            max_variants: 1,
+        };
+
+        assert!(!limits.exceeded_by("[color:red]"));
+        assert!(!limits.exceeded_by("hover:bg-[url(http://example.com/a.png)]"));
+        assert!(!limits.exceeded_by("supports-[display:grid]:grid"));
+        assert!(limits.exceeded_by("sm:hover:[color:red]"));
+    }
+
+    #[test]
+    fn test_deep_variant_stack_passes_through() {
+        let stacked = format!("{}p-2", "hover:".repeat(253));
+
+        assert_eq!(
+            sort_classes(&format!("{stacked} z-10 p-4")),
+            format!("z-10 p-4 {stacked}")
+        );
+    }
+
     #[test]
     fn test_real_world_example_1() {
         let input =
@@ -0,0 +1,10 @@
+export function Card({ active }) {
+  return (
+    <div className="p-6 bg-white rounded shadow">
+      <h2 className={clsx("text-xl font-semibold", active && "text-blue-600 underline")}>
+        Card
+      </h2>
+      <p className={`text-gray-500 mt-2 ${active ? "block" : "hidden"}`}>Body</p>
+    </div>
+  );
+}
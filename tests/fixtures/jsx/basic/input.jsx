@@ -0,0 +1,10 @@
+export function Card({ active }) {
+  return (
+    <div className="rounded shadow p-6 bg-white">
+      <h2 className={clsx("font-semibold text-xl", active && "text-blue-600 underline")}>
+        Card
+      </h2>
+      <p className={`text-gray-500 mt-2 ${active ? "block" : "hidden"}`}>Body</p>
+    </div>
+  );
+}
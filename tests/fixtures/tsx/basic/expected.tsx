@@ -0,0 +1,14 @@
+import { cva } from "class-variance-authority";
+
+const button = cva("inline-flex px-4 py-2 font-medium rounded", {
+  variants: {
+    intent: {
+      primary: "text-white bg-blue-600 hover:bg-blue-700",
+      secondary: "text-gray-900 bg-white border",
+    },
+  },
+});
+
+export const Button = (props: { label: string }) => (
+  <button className="mt-4 w-full focus:outline-none">{props.label}</button>
+);
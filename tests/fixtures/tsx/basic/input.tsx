@@ -0,0 +1,14 @@
+import { cva } from "class-variance-authority";
+
+const button = cva("rounded px-4 py-2 font-medium inline-flex", {
+  variants: {
+    intent: {
+      primary: "hover:bg-blue-700 bg-blue-600 text-white",
+      secondary: "border bg-white text-gray-900",
+    },
+  },
+});
+
+export const Button = (props: { label: string }) => (
+  <button className="focus:outline-none mt-4 w-full">{props.label}</button>
+);